@@ -0,0 +1,38 @@
+//! Measures `AttributeIter` on tags with many widely-spaced attributes.
+//!
+//! Run with `cargo bench --bench attributes`. The time per byte should stay
+//! flat as the tag grows, confirming that attribute parsing scales linearly.
+
+use lazyxml::AttributeIter;
+use std::{hint::black_box, time::Instant};
+
+const GAP: usize = 4096;
+const ROUNDS: u32 = 20;
+
+fn tag(attributes: usize) -> Vec<u8> {
+    let mut tag = Vec::new();
+    for i in 0..attributes {
+        tag.extend_from_slice(format!("a{}=\"{}\"", i, i).as_bytes());
+        tag.resize(tag.len() + GAP, b' ');
+    }
+    tag
+}
+
+fn main() {
+    for &attributes in &[64, 128, 256, 512, 1024] {
+        let tag = tag(attributes);
+        let start = Instant::now();
+        for _ in 0..ROUNDS {
+            let count = AttributeIter::new(black_box(&tag[..])).count();
+            assert_eq!(count, attributes);
+        }
+        let elapsed = start.elapsed() / ROUNDS;
+        println!(
+            "{:>5} attributes, {:>8} bytes: {:>10?} ({:.3} ns/byte)",
+            attributes,
+            tag.len(),
+            elapsed,
+            elapsed.as_nanos() as f64 / tag.len() as f64,
+        );
+    }
+}
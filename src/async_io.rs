@@ -0,0 +1,101 @@
+//! Streaming reader over tokio's [`AsyncRead`] sources.
+
+use crate::{
+    io::{Buffer, Found},
+    Error, Event, OwnedEvent, ReaderBuilder,
+};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// XML reader over an [`AsyncRead`] source, such as a socket.
+///
+/// This is the async counterpart of [`IoReader`](crate::IoReader), and buffers the same way:
+/// only as much of the source as is needed to produce the next event is kept in memory.\
+/// Error offsets are relative to the start of the source.
+///
+/// # Example
+/// ```rust
+/// use lazyxml::{AsyncReader, Event};
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let socket: &[u8] = b"<Frames><Frame/><Frame/></Frames>";
+/// let mut reader = AsyncReader::new(socket);
+/// let mut frames = 0;
+/// while let Some(event) = reader.next_event().await {
+///     if let Event::Empty(tag) = event.unwrap() {
+///         frames += (tag.name() == b"Frame") as usize;
+///     }
+/// }
+/// assert_eq!(frames, 2);
+/// # });
+/// ```
+pub struct AsyncReader<R> {
+    inner: R,
+    buf: Buffer,
+}
+
+impl<R: AsyncRead + Unpin> AsyncReader<R> {
+    /// Constructs a new [`AsyncReader`] over `inner`.
+    ///
+    /// The source is read in chunks, so it doesn't need to be buffered itself.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            buf: Buffer::new(),
+        }
+    }
+
+    /// Enables or disables trimming whitespace in [`Text`](crate::Text) events.
+    ///
+    /// Defaults to enabled (`true`).
+    pub fn trim_whitespace(&mut self, trim: bool) -> &mut Self {
+        self.buf.settings = self.buf.settings.trim_whitespace(trim);
+        self
+    }
+
+    /// Sets how events are read, with every setting of a [`Reader`](crate::Reader) at once.
+    ///
+    /// State is kept from one event to the next like a [`Reader`](crate::Reader) does,
+    /// so structure checks and limits apply to the whole source.\
+    /// This applies to the events read afterwards.
+    ///
+    /// Defaults to the default settings of a [`ReaderBuilder`].
+    pub fn settings(&mut self, settings: ReaderBuilder) -> &mut Self {
+        self.buf.settings = settings;
+        self
+    }
+
+    /// Gets the underlying source.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Reads the next event, borrowing it from the internal buffer.
+    ///
+    /// I/O errors are returned as [`Error::Io`].
+    /// After any error, reading is over and `None` is returned from then on.
+    pub async fn next_event(&mut self) -> Option<Result<Event<'_, [u8]>, Error>> {
+        loop {
+            // SAFETY: The buffer stays borrowed only when the event is returned,
+            // which the borrow checker can't tell apart from carrying on yet.
+            let buf = unsafe { &mut *(&mut self.buf as *mut Buffer) };
+            match buf.next_event() {
+                Ok(Found::Event(event)) => return Some(Ok(event)),
+                Ok(Found::Done) => return None,
+                Ok(Found::More) => (),
+                Err(error) => return Some(Err(error)),
+            }
+            let len = self.buf.reserve();
+            let read = self.inner.read(self.buf.spare(len)).await;
+            if let Err(error) = self.buf.filled(len, read) {
+                return Some(Err(error));
+            }
+        }
+    }
+
+    /// Reads the next event, converted to an [`OwnedEvent`].
+    pub async fn next_owned_event(&mut self) -> Option<Result<OwnedEvent<[u8]>, Error>> {
+        self.next_event()
+            .await
+            .map(|event| event.map(Event::into_owned))
+    }
+}
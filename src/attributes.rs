@@ -0,0 +1,142 @@
+//! Eagerly collected attributes of a tag, for repeated lookups.
+
+use crate::{Attribute, AttributeIter, Error, Tag};
+use alloc::vec::Vec;
+use core::slice;
+
+/// How [`Attributes`] treats a key that appears more than once in a tag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Every occurrence of a key is kept, and lookups find the first one.
+    KeepAll,
+    /// The first occurrence of a key is kept, and later ones are ignored.
+    FirstWins,
+    /// The value of the last occurrence of a key is kept, at the position of the first one.
+    ///
+    /// This is how Flash reads attributes.
+    LastWins,
+    /// A repeated key fails with [`Error::DuplicateAttribute`], as the XML standard requires.
+    Error,
+}
+
+/// The attributes of a [`Tag`], parsed once into a small map that keeps document order.
+///
+/// Iterating [`Tag::attributes`] is cheap, but probing the same tag for several keys
+/// parses it over and over again. This collects them up front instead,
+/// and lookups are linear scans, which beat hashing for the few attributes tags usually have.
+pub struct Attributes<'xml, T: ?Sized> {
+    attrs: Vec<Attribute<'xml, T>>,
+}
+
+impl<'xml, T> Attributes<'xml, T>
+where
+    T: ?Sized + AsRef<[u8]>,
+    AttributeIter<'xml, T>: Iterator<Item = Result<Attribute<'xml, T>, Error>>,
+{
+    /// Collects the attributes of the tag, keeping the first of any duplicate keys.
+    ///
+    /// Fails with the first malformed attribute, as there's no telling where the next one starts.
+    pub fn parse(tag: &Tag<'xml, T>) -> Result<Self, Error> {
+        Self::parse_with(tag, DuplicatePolicy::FirstWins)
+    }
+
+    /// Collects the attributes of the tag, handling duplicate keys with the given policy.
+    pub fn parse_with(tag: &Tag<'xml, T>, policy: DuplicatePolicy) -> Result<Self, Error> {
+        let mut attrs: Vec<Attribute<'xml, T>> = Vec::new();
+        for attr in tag.attributes() {
+            let attr = attr?;
+            let key = attr.key().as_ref();
+            let existing = match policy {
+                DuplicatePolicy::KeepAll => None,
+                _ => attrs.iter_mut().find(|a| a.key().as_ref() == key),
+            };
+            match (existing, policy) {
+                (Some(existing), DuplicatePolicy::LastWins) => {
+                    existing.value = attr.value;
+                    existing.value_start = attr.value_start;
+                }
+                (Some(_), DuplicatePolicy::Error) => {
+                    return Err(Error::DuplicateAttribute {
+                        offset: attr.key_start,
+                    })
+                }
+                (Some(_), _) => (),
+                (None, _) => attrs.push(attr),
+            }
+        }
+        Ok(Self { attrs })
+    }
+
+    /// Gets the attribute with the given key.
+    pub fn get(&self, key: &T) -> Option<&Attribute<'xml, T>> {
+        let key = key.as_ref();
+        self.attrs.iter().find(|attr| attr.key().as_ref() == key)
+    }
+
+    /// Returns an iterator over every attribute with the given key,
+    /// which only finds more than one with [`DuplicatePolicy::KeepAll`].
+    pub fn get_all<'a>(&'a self, key: &'a T) -> impl Iterator<Item = &'a Attribute<'xml, T>> {
+        let key = key.as_ref();
+        self.attrs
+            .iter()
+            .filter(move |attr| attr.key().as_ref() == key)
+    }
+
+    /// Gets the attribute with the given key, comparing ASCII letters case-insensitively.
+    pub fn get_ignore_case(&self, key: &T) -> Option<&Attribute<'xml, T>> {
+        let key = key.as_ref();
+        self.attrs
+            .iter()
+            .find(|attr| attr.key().as_ref().eq_ignore_ascii_case(key))
+    }
+}
+
+impl<'xml, T> Tag<'xml, T>
+where
+    T: ?Sized + AsRef<[u8]>,
+    AttributeIter<'xml, T>: Iterator<Item = Result<Attribute<'xml, T>, Error>>,
+{
+    /// Collects the tag's attributes, handling duplicate keys with the given policy.
+    ///
+    /// Shorthand for [`Attributes::parse_with`].
+    pub fn attributes_deduped(
+        &self,
+        policy: DuplicatePolicy,
+    ) -> Result<Attributes<'xml, T>, Error> {
+        Attributes::parse_with(self, policy)
+    }
+}
+
+impl<'xml, T: ?Sized> Attributes<'xml, T> {
+    /// Gets the number of distinct attributes.
+    pub fn len(&self) -> usize {
+        self.attrs.len()
+    }
+
+    /// Checks whether there are no attributes at all.
+    pub fn is_empty(&self) -> bool {
+        self.attrs.is_empty()
+    }
+
+    /// Returns an iterator over the attributes in document order.
+    pub fn iter(&self) -> slice::Iter<'_, Attribute<'xml, T>> {
+        self.attrs.iter()
+    }
+}
+
+impl<'a, 'xml, T: ?Sized> IntoIterator for &'a Attributes<'xml, T> {
+    type Item = &'a Attribute<'xml, T>;
+    type IntoIter = slice::Iter<'a, Attribute<'xml, T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.attrs.iter()
+    }
+}
+
+impl<'xml, T: ?Sized> Clone for Attributes<'xml, T> {
+    fn clone(&self) -> Self {
+        Self {
+            attrs: self.attrs.clone(),
+        }
+    }
+}
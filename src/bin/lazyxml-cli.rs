@@ -0,0 +1,135 @@
+//! Command line tool for looking into XML files, built on the reader.
+
+use lazyxml::{
+    decode::decode_text_str,
+    encoding::decode_input,
+    fmt::{pretty, PrettyOptions},
+    tree::Document,
+    xpath::Path,
+    Event, Reader,
+};
+use std::{
+    env,
+    error::Error,
+    fs,
+    io::{self, BufWriter, Write},
+    process,
+};
+
+const USAGE: &str = "\
+usage: lazyxml-cli <command> <file> [args]
+
+commands:
+    events <file>        dump the event stream, one event per line
+    stat <file>          summarize what the document is made of
+    fmt <file>           pretty-print the document
+    get <file> <path>    print the text of the nodes matching a path, such as //Item[@id='1']";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (command, file, rest) = match args.as_slice() {
+        [command, file, rest @ ..] => (command.as_str(), file, rest),
+        _ => usage(),
+    };
+    let input = match fs::read(file) {
+        Ok(input) => input,
+        Err(error) => fail(&format!("{}: {}", file, error)),
+    };
+    let xml = decode_input(&input);
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let result = match (command, rest) {
+        ("events", []) => events(&xml, &mut out),
+        ("stat", []) => stat(&xml, &mut out),
+        ("fmt", []) => format(&xml, &mut out),
+        ("get", [path]) => get(&xml, path, &mut out),
+        _ => usage(),
+    };
+    if let Err(error) = result.and_then(|_| Ok(out.flush()?)) {
+        fail(&format!("{}: {}", file, error));
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2)
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("lazyxml-cli: {}", message);
+    process::exit(1)
+}
+
+/// Prints every event with its offset, indented by depth.
+fn events(xml: &str, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    let mut reader = Reader::from_str(xml).indexed();
+    while let Some(event) = reader.next() {
+        let (offset, event) = event?;
+        // Start tags are printed at the depth they open, which the reader has already entered.
+        let depth = match event {
+            Event::Start(_) => reader.reader().depth() - 1,
+            _ => reader.reader().depth(),
+        };
+        let (kind, detail) = match &event {
+            Event::Start(tag) => ("start", tag.name()),
+            Event::End(tag) => ("end", tag.name()),
+            Event::Empty(tag) => ("empty", tag.name()),
+            Event::ProcessingInstruction(tag) => ("pi", tag.name()),
+            Event::Text(text) => ("text", text.content()),
+            Event::Comment(text) => ("comment", text.content()),
+            Event::CData(text) => ("cdata", text.content()),
+            Event::DocType(text) => ("doctype", text.content().trim()),
+        };
+        writeln!(
+            out,
+            "{:>8} {:indent$}{} {:?}",
+            offset,
+            "",
+            kind,
+            detail,
+            indent = depth * 2
+        )?;
+    }
+    Ok(())
+}
+
+/// Prints the counters of the reader after reading the whole document.
+fn stat(xml: &str, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    let mut reader = Reader::from_str(xml);
+    reader.check_structure(true);
+    for event in reader.by_ref() {
+        event?;
+    }
+    let stats = reader.stats();
+    let counts = [
+        ("elements", stats.start + stats.empty),
+        ("empty elements", stats.empty),
+        ("text", stats.text),
+        ("cdata", stats.cdata),
+        ("comments", stats.comment),
+        ("processing instructions", stats.processing_instruction),
+        ("doctypes", stats.doctype),
+        ("max depth", stats.max_depth),
+        ("bytes", stats.bytes),
+    ];
+    for (name, count) in counts.iter() {
+        writeln!(out, "{:<24}{}", name, count)?;
+    }
+    Ok(())
+}
+
+/// Writes the document back out, indented by two spaces per level.
+fn format(xml: &str, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    out.write_all(pretty(xml, &PrettyOptions::new())?.as_bytes())?;
+    Ok(())
+}
+
+/// Prints the decoded text of every node matching `path`, one per line.
+fn get(xml: &str, path: &str, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    let path = Path::compile(path)?;
+    let doc = Document::parse(xml)?;
+    for node in path.select_document(&doc) {
+        writeln!(out, "{}", decode_text_str(&node.text()))?;
+    }
+    Ok(())
+}
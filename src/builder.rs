@@ -0,0 +1,238 @@
+//! Builder gathering the settings of a [`Reader`] before constructing it.
+
+use crate::{Dialect, Reader};
+
+/// Settings for constructing a [`Reader`] in one expression.
+///
+/// Each setting mirrors the [`Reader`] setter of the same name, and defaults to the same value.
+///
+/// # Example
+/// ```rust
+/// use lazyxml::{Event, ReaderBuilder};
+///
+/// let reader = ReaderBuilder::new()
+///     .emit_comments(false)
+///     .check_structure(true)
+///     .from_str("<a><!-- skipped --></a>");
+/// assert_eq!(reader.count(), 2);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct ReaderBuilder {
+    trim: bool,
+    skip_bom: bool,
+    recover: bool,
+    check_structure: bool,
+    stop_after_root: bool,
+    comments: bool,
+    empty_text: bool,
+    strict_names: bool,
+    ignore_name_case: bool,
+    honor_xml_space: bool,
+    max_tag_len: Option<usize>,
+    max_depth: Option<usize>,
+    max_text_len: Option<usize>,
+    max_events: Option<usize>,
+    strict_attributes: bool,
+    max_attribute_value_len: Option<usize>,
+    max_attributes: Option<usize>,
+    unquoted_attributes: bool,
+    bare_attributes: bool,
+    dialect: Dialect,
+}
+
+impl ReaderBuilder {
+    /// Constructs a builder with the default settings of a [`Reader`].
+    pub const fn new() -> Self {
+        Self {
+            trim: true,
+            skip_bom: false,
+            recover: false,
+            check_structure: false,
+            stop_after_root: false,
+            comments: true,
+            empty_text: false,
+            strict_names: false,
+            ignore_name_case: false,
+            honor_xml_space: false,
+            max_tag_len: None,
+            max_depth: None,
+            max_text_len: None,
+            max_events: None,
+            strict_attributes: false,
+            max_attribute_value_len: None,
+            max_attributes: None,
+            unquoted_attributes: false,
+            bare_attributes: false,
+            dialect: Dialect::Xml,
+        }
+    }
+
+    /// See [`Reader::trim_whitespace`].
+    pub const fn trim_whitespace(mut self, trim: bool) -> Self {
+        self.trim = trim;
+        self
+    }
+
+    /// See [`Reader::skip_bom_per_document`].
+    pub const fn skip_bom_per_document(mut self, skip: bool) -> Self {
+        self.skip_bom = skip;
+        self
+    }
+
+    /// See [`Reader::recover`].
+    pub const fn recover(mut self, recover: bool) -> Self {
+        self.recover = recover;
+        self
+    }
+
+    /// See [`Reader::check_structure`].
+    pub const fn check_structure(mut self, check: bool) -> Self {
+        self.check_structure = check;
+        self
+    }
+
+    /// See [`Reader::stop_after_root`].
+    pub const fn stop_after_root(mut self, stop: bool) -> Self {
+        self.stop_after_root = stop;
+        self
+    }
+
+    /// See [`Reader::emit_comments`].
+    pub const fn emit_comments(mut self, emit: bool) -> Self {
+        self.comments = emit;
+        self
+    }
+
+    /// See [`Reader::emit_empty_text`].
+    pub const fn emit_empty_text(mut self, emit: bool) -> Self {
+        self.empty_text = emit;
+        self
+    }
+
+    /// See [`Reader::strict_names`].
+    pub const fn strict_names(mut self, strict: bool) -> Self {
+        self.strict_names = strict;
+        self
+    }
+
+    /// See [`Reader::ignore_name_case`].
+    pub const fn ignore_name_case(mut self, ignore: bool) -> Self {
+        self.ignore_name_case = ignore;
+        self
+    }
+
+    /// See [`Reader::honor_xml_space`].
+    pub const fn honor_xml_space(mut self, honor: bool) -> Self {
+        self.honor_xml_space = honor;
+        self
+    }
+
+    /// See [`Reader::max_tag_len`].
+    pub const fn max_tag_len(mut self, limit: Option<usize>) -> Self {
+        self.max_tag_len = limit;
+        self
+    }
+
+    /// See [`Reader::max_depth`].
+    pub const fn max_depth(mut self, limit: Option<usize>) -> Self {
+        self.max_depth = limit;
+        self
+    }
+
+    /// See [`Reader::max_text_len`].
+    pub const fn max_text_len(mut self, limit: Option<usize>) -> Self {
+        self.max_text_len = limit;
+        self
+    }
+
+    /// See [`Reader::max_events`].
+    pub const fn max_events(mut self, limit: Option<usize>) -> Self {
+        self.max_events = limit;
+        self
+    }
+
+    /// See [`Reader::strict_attributes`].
+    pub const fn strict_attributes(mut self, strict: bool) -> Self {
+        self.strict_attributes = strict;
+        self
+    }
+
+    /// See [`Reader::max_attribute_value_len`].
+    pub const fn max_attribute_value_len(mut self, limit: Option<usize>) -> Self {
+        self.max_attribute_value_len = limit;
+        self
+    }
+
+    /// See [`Reader::max_attributes`].
+    pub const fn max_attributes(mut self, limit: Option<usize>) -> Self {
+        self.max_attributes = limit;
+        self
+    }
+
+    /// See [`Reader::allow_unquoted_attributes`].
+    pub const fn allow_unquoted_attributes(mut self, allow: bool) -> Self {
+        self.unquoted_attributes = allow;
+        self
+    }
+
+    /// See [`Reader::allow_bare_attributes`].
+    pub const fn allow_bare_attributes(mut self, allow: bool) -> Self {
+        self.bare_attributes = allow;
+        self
+    }
+
+    /// See [`Reader::dialect`].
+    ///
+    /// This is applied after the other settings, so [`Dialect::Html`] enables
+    /// the leniency it comes with regardless of them.
+    /// Switching the constructed reader back to [`Dialect::Xml`] restores those settings
+    /// to what they are here.
+    pub const fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Constructs a [`Reader`] over a UTF-8 string with these settings.
+    pub fn from_str<'xml>(&self, xml: &'xml str) -> Reader<'xml, str> {
+        let mut reader = Reader::from_str(xml);
+        self.apply(&mut reader);
+        reader
+    }
+
+    /// Constructs a [`Reader`] over ASCII-compatible XML bytes with these settings.
+    pub fn from_bytes<'xml>(&self, xml: &'xml [u8]) -> Reader<'xml, [u8]> {
+        let mut reader = Reader::from_bytes(xml);
+        self.apply(&mut reader);
+        reader
+    }
+
+    pub(crate) fn apply<T: ?Sized>(&self, reader: &mut Reader<'_, T>) {
+        reader
+            .trim_whitespace(self.trim)
+            .skip_bom_per_document(self.skip_bom)
+            .recover(self.recover)
+            .check_structure(self.check_structure)
+            .stop_after_root(self.stop_after_root)
+            .emit_comments(self.comments)
+            .emit_empty_text(self.empty_text)
+            .strict_names(self.strict_names)
+            .ignore_name_case(self.ignore_name_case)
+            .honor_xml_space(self.honor_xml_space)
+            .max_tag_len(self.max_tag_len)
+            .max_depth(self.max_depth)
+            .max_text_len(self.max_text_len)
+            .max_events(self.max_events)
+            .strict_attributes(self.strict_attributes)
+            .max_attribute_value_len(self.max_attribute_value_len)
+            .max_attributes(self.max_attributes)
+            .allow_unquoted_attributes(self.unquoted_attributes)
+            .allow_bare_attributes(self.bare_attributes)
+            .dialect(self.dialect);
+    }
+}
+
+impl Default for ReaderBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,104 @@
+//! Reading input that's split across several buffers, without joining them first.
+
+use crate::{Error, Event, Reader};
+use core::iter::FusedIterator;
+
+/// Reader over XML split into chunks, such as data still sitting in network buffers.
+///
+/// Created with [`Reader::from_chunks`]. Every event borrows from the chunk it's in,
+/// so its spans are relative to that chunk, see [`ChunkedReader::chunk_offset`].\
+/// Text running across a boundary is yielded in pieces, one per chunk.
+/// Any other token that crosses a boundary yields [`Error::SplitToken`] and ends the reader,
+/// after which [`ChunkedReader::remaining`] gives what's left to join and read from there.
+///
+/// The reader is made with the default settings, and keeps track of the depth
+/// across chunks, but doesn't check the structure of the document.
+pub struct ChunkedReader<'xml> {
+    chunks: &'xml [&'xml [u8]],
+    /// Offset of the current chunk in the whole input.
+    chunk_offset: usize,
+    reader: Reader<'xml, [u8]>,
+    done: bool,
+}
+
+impl<'xml> Reader<'xml, [u8]> {
+    /// Constructs a new [`ChunkedReader`] over XML split into chunks, read in order.
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::Reader;
+    ///
+    /// let chunks: &[&[u8]] = &[b"<a><b/>te", b"xt<c/><d", b"/></a>"];
+    /// let mut reader = Reader::from_chunks(chunks);
+    /// assert_eq!(reader.by_ref().take_while(Result::is_ok).count(), 5);
+    /// let (tail, rest) = reader.remaining();
+    /// assert_eq!((tail, rest.len()), (&b"<d"[..], 1));
+    /// ```
+    pub fn from_chunks(chunks: &'xml [&'xml [u8]]) -> ChunkedReader<'xml> {
+        const EMPTY: &[u8] = &[];
+        let (first, chunks) = chunks.split_first().unwrap_or((&EMPTY, &[]));
+        ChunkedReader {
+            chunks,
+            chunk_offset: 0,
+            reader: Reader::from_bytes(first),
+            done: false,
+        }
+    }
+}
+
+impl<'xml> ChunkedReader<'xml> {
+    /// Gets the offset of the chunk the last event came from in the whole input.
+    ///
+    /// Add this to the spans of events to make them relative to the whole input.
+    pub fn chunk_offset(&self) -> usize {
+        self.chunk_offset
+    }
+
+    /// Gets how deep into elements the reader is, see [`Reader::depth`].
+    pub fn depth(&self) -> usize {
+        self.reader.depth
+    }
+
+    /// Gets what hasn't been read yet, as the unread tail of the current chunk
+    /// and the chunks after it.
+    ///
+    /// After [`Error::SplitToken`], the tail starts with the token that was split.
+    pub fn remaining(&self) -> (&'xml [u8], &'xml [&'xml [u8]]) {
+        (&self.reader.source[self.reader.offset..], self.chunks)
+    }
+}
+
+impl<'xml> Iterator for ChunkedReader<'xml> {
+    type Item = Result<Event<'xml, [u8]>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let start = self.reader.remaining_start();
+            match self.reader.next() {
+                Some(Err(Error::UnexpectedEof)) if !self.chunks.is_empty() => {
+                    self.done = true;
+                    self.reader.offset = start;
+                    return Some(Err(Error::SplitToken {
+                        offset: self.chunk_offset + start,
+                    }));
+                }
+                Some(Err(error)) => {
+                    self.done = true;
+                    return Some(Err(error));
+                }
+                Some(Ok(event)) => return Some(Ok(event)),
+                None => {
+                    let (next, chunks) = self.chunks.split_first()?;
+                    self.chunk_offset += self.reader.source.len();
+                    self.chunks = chunks;
+                    let depth = self.reader.depth;
+                    self.reader = Reader::from_bytes(next);
+                    self.reader.depth = depth;
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<'xml> FusedIterator for ChunkedReader<'xml> {}
@@ -0,0 +1,512 @@
+//! serde deserialization on top of the lenient [`Reader`](crate::Reader).
+//!
+//! Elements map to structs and maps, where fields are looked up as:
+//!
+//! - `@key`: the attribute `key`, for use with `#[serde(rename = "@key")]`.
+//! - `$value`: the text content of the element.
+//! - anything else: the child elements with that name, or else the attribute with that name.
+//!
+//! Repeated child elements deserialize into sequences, and text and attribute values
+//! are parsed into numbers, booleans and unit enum variants as needed.
+
+use crate::{
+    decode::decode_text_str,
+    tree::{Document, Node, NodeKind},
+};
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use std::{borrow::Cow, fmt};
+
+/// Errors from deserializing with [`from_str`], or serializing with [`ser`](crate::ser).
+#[derive(Debug)]
+pub enum Error {
+    /// The document itself couldn't be parsed.
+    Xml(crate::Error),
+    /// The document didn't match the shape of the type being deserialized.
+    Custom(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Xml(error) => write!(f, "malformed XML: {}", error),
+            Error::Custom(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Xml(error) => Some(error),
+            Error::Custom(_) => None,
+        }
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
+
+impl serde::ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
+
+impl From<crate::Error> for Error {
+    fn from(error: crate::Error) -> Self {
+        Error::Xml(error)
+    }
+}
+
+/// Deserializes an instance of `T` from the root element of `xml`.
+///
+/// # Example
+/// ```rust
+/// #[derive(serde::Deserialize)]
+/// struct Script {
+///     #[serde(rename = "@time")]
+///     time: u32,
+///     #[serde(rename = "$value")]
+///     body: String,
+/// }
+///
+/// let script: Script = lazyxml::de::from_str("<Script time='5'>a &lt; b</Script>").unwrap();
+/// assert_eq!((script.time, script.body.as_str()), (5, "a < b"));
+/// ```
+pub fn from_str<'de, T: de::Deserialize<'de>>(xml: &'de str) -> Result<T, Error> {
+    let doc = Document::parse(xml)?;
+    let root = doc
+        .root()
+        .ok_or_else(|| Error::Custom("document has no root element".into()))?;
+    T::deserialize(NodesDeserializer { nodes: vec![root] })
+}
+
+/// Deserializer over text, such as an attribute value or the content of an element.
+struct TextDeserializer<'de>(Cow<'de, str>);
+
+/// Deserializer over one or more elements with the same name.
+struct NodesDeserializer<'a, 'de> {
+    nodes: Vec<&'a Node<'de>>,
+}
+
+/// Value of a field found on an element.
+enum Field<'a, 'de> {
+    Text(Cow<'de, str>),
+    Nodes(Vec<&'a Node<'de>>),
+}
+
+/// Map over the fields of an element, collected ahead of time.
+struct FieldMap<'a, 'de> {
+    fields: std::vec::IntoIter<(Cow<'de, str>, Field<'a, 'de>)>,
+    value: Option<Field<'a, 'de>>,
+}
+
+/// Sequence of elements with the same name.
+struct NodeSeq<'a, 'de> {
+    nodes: std::vec::IntoIter<&'a Node<'de>>,
+}
+
+/// Gets the text of a node like [`Node::text`], but decoded, leaving CDATA sections as they are.
+fn decoded_text<'de>(node: &Node<'de>) -> Cow<'de, str> {
+    match node.kind() {
+        NodeKind::Text(text) => return decode_text_str(text.content()),
+        NodeKind::CData(text) | NodeKind::Comment(text) => return Cow::Borrowed(text.content()),
+        NodeKind::ProcessingInstruction(_) => return Cow::Borrowed(""),
+        NodeKind::Element(_) => (),
+    }
+    let mut pieces = node.children().iter().filter_map(|x| match x.kind() {
+        NodeKind::Text(text) => Some(decode_text_str(text.content())),
+        NodeKind::CData(text) => Some(Cow::Borrowed(text.content())),
+        _ => None,
+    });
+    let mut text = pieces.next().unwrap_or_default();
+    for piece in pieces {
+        text.to_mut().push_str(&piece);
+    }
+    text
+}
+
+fn attribute<'de>(node: &Node<'de>, key: &str) -> Result<Option<Cow<'de, str>>, Error> {
+    if let NodeKind::Element(tag) = node.kind() {
+        for attr in tag.attributes() {
+            let attr = attr?;
+            if attr.key() == key {
+                return Ok(Some(decode_text_str(attr.value())));
+            }
+        }
+    }
+    Ok(None)
+}
+
+fn has_structure(node: &Node<'_>) -> bool {
+    let has_attributes = match node.kind() {
+        NodeKind::Element(tag) => tag.attributes().next().is_some(),
+        _ => false,
+    };
+    has_attributes || node.elements().next().is_some()
+}
+
+impl<'a, 'de> NodesDeserializer<'a, 'de> {
+    fn first(&self) -> &'a Node<'de> {
+        self.nodes[0]
+    }
+
+    fn text(&self) -> TextDeserializer<'de> {
+        TextDeserializer(decoded_text(self.first()))
+    }
+
+    /// Looks up the given fields for a struct.
+    fn struct_fields(&self, fields: &'static [&'static str]) -> Result<FieldMap<'a, 'de>, Error> {
+        let node = self.first();
+        let mut found = Vec::new();
+        for &field in fields {
+            let value = if field == "$value" {
+                Some(Field::Text(decoded_text(node)))
+            } else if let Some(key) = field.strip_prefix('@') {
+                attribute(node, key)?.map(Field::Text)
+            } else {
+                let children: Vec<_> = node
+                    .elements()
+                    .filter(|x| x.name() == Some(field))
+                    .collect();
+                if !children.is_empty() {
+                    Some(Field::Nodes(children))
+                } else {
+                    attribute(node, field)?.map(Field::Text)
+                }
+            };
+            if let Some(value) = value {
+                found.push((Cow::Borrowed(field), value));
+            }
+        }
+        Ok(FieldMap {
+            fields: found.into_iter(),
+            value: None,
+        })
+    }
+
+    /// Collects every attribute, child element name and the text for a map.
+    fn map_fields(&self) -> Result<FieldMap<'a, 'de>, Error> {
+        let node = self.first();
+        let mut found: Vec<(Cow<'de, str>, Field<'a, 'de>)> = Vec::new();
+        if let NodeKind::Element(tag) = node.kind() {
+            for attr in tag.attributes() {
+                let attr = attr?;
+                let key = Cow::Owned(format!("@{}", attr.key()));
+                found.push((key, Field::Text(decode_text_str(attr.value()))));
+            }
+        }
+        for child in node.elements() {
+            let name = child.name().unwrap_or_default();
+            match found.iter_mut().find(|(key, _)| key == name) {
+                Some((_, Field::Nodes(nodes))) => nodes.push(child),
+                _ => found.push((Cow::Borrowed(name), Field::Nodes(vec![child]))),
+            }
+        }
+        let text = decoded_text(node);
+        if !text.is_empty() {
+            found.push((Cow::Borrowed("$value"), Field::Text(text)));
+        }
+        Ok(FieldMap {
+            fields: found.into_iter(),
+            value: None,
+        })
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                let text = self.0.trim();
+                match text.parse() {
+                    Ok(value) => visitor.$visit(value),
+                    Err(_) => Err(de::Error::invalid_value(de::Unexpected::Str(text), &visitor)),
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! deserialize_text {
+    ($($method:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                self.text().$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for TextDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Cow::Borrowed(text) => visitor.visit_borrowed_str(text),
+            Cow::Owned(text) => visitor.visit_string(text),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0.trim() {
+            "true" | "1" => visitor.visit_bool(true),
+            "false" | "0" => visitor.visit_bool(false),
+            text => Err(de::Error::invalid_value(
+                de::Unexpected::Str(text),
+                &visitor,
+            )),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        i128 u128 str string bytes byte_buf unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for TextDeserializer<'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let variant = seed.deserialize(TextDeserializer(self.0.clone()))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for TextDeserializer<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, _seed: T) -> Result<T::Value, Error> {
+        Err(de::Error::invalid_type(
+            de::Unexpected::UnitVariant,
+            &"newtype variant",
+        ))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, _visitor: V) -> Result<V::Value, Error> {
+        Err(de::Error::invalid_type(
+            de::Unexpected::UnitVariant,
+            &"tuple variant",
+        ))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Error> {
+        Err(de::Error::invalid_type(
+            de::Unexpected::UnitVariant,
+            &"struct variant",
+        ))
+    }
+}
+
+impl<'a, 'de> de::Deserializer<'de> for NodesDeserializer<'a, 'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.nodes.len() > 1 {
+            self.deserialize_seq(visitor)
+        } else if has_structure(self.first()) {
+            self.deserialize_map(visitor)
+        } else {
+            self.text().deserialize_any(visitor)
+        }
+    }
+
+    deserialize_text! {
+        deserialize_bool,
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+        deserialize_f32,
+        deserialize_f64,
+        deserialize_char,
+        deserialize_str,
+        deserialize_string,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(NodeSeq {
+            nodes: self.nodes.into_iter(),
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_map(self.map_fields()?)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_map(self.struct_fields(fields)?)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.text().deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        i128 u128 bytes byte_buf identifier
+    }
+}
+
+impl<'a, 'de> de::MapAccess<'de> for FieldMap<'a, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.fields.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(TextDeserializer(key)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        match self.value.take() {
+            Some(Field::Text(text)) => seed.deserialize(TextDeserializer(text)),
+            Some(Field::Nodes(nodes)) => seed.deserialize(NodesDeserializer { nodes }),
+            None => Err(de::Error::custom("value requested before key")),
+        }
+    }
+}
+
+impl<'a, 'de> de::SeqAccess<'de> for NodeSeq<'a, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        match self.nodes.next() {
+            Some(node) => seed
+                .deserialize(NodesDeserializer { nodes: vec![node] })
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.nodes.len())
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for TextDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
@@ -0,0 +1,87 @@
+//! The `<?xml ...?>` declaration leading a document.
+
+use crate::{Attribute, AttributeIter, Error, Event, Reader, Tag};
+
+/// XML declaration of a document, as found by [`Reader::xml_decl`].
+///
+/// Its pseudo-attributes are parsed leniently, in any order and with any quoting
+/// the [`AttributeIter`] accepts, and their values are raw.
+pub struct XmlDecl<'xml, T: ?Sized> {
+    tag: Tag<'xml, T>,
+}
+
+impl<'xml, T> XmlDecl<'xml, T>
+where
+    T: ?Sized + AsRef<[u8]>,
+    AttributeIter<'xml, T>: Iterator<Item = Result<Attribute<'xml, T>, Error>>,
+{
+    fn get(&self, key: &[u8]) -> Option<&'xml T> {
+        self.tag
+            .attributes()
+            .map_while(Result::ok)
+            .find(|attr| attr.key().as_ref() == key)
+            .map(|attr| attr.value())
+    }
+
+    /// Gets the declared XML version, such as `1.0`.
+    pub fn version(&self) -> Option<&'xml T> {
+        self.get(b"version")
+    }
+
+    /// Gets the declared encoding, such as `UTF-8`.
+    ///
+    /// See [`Encoding::for_label`](crate::encoding::Encoding::for_label) to make sense of it.
+    pub fn encoding(&self) -> Option<&'xml T> {
+        self.get(b"encoding")
+    }
+
+    /// Gets whether the document is declared standalone, with `yes` or `no`.
+    ///
+    /// Anything else is taken as not being declared at all.
+    pub fn standalone(&self) -> Option<bool> {
+        match self.get(b"standalone")?.as_ref() {
+            b"yes" => Some(true),
+            b"no" => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Gets the declaration as a processing instruction tag.
+    pub fn tag(&self) -> &Tag<'xml, T> {
+        &self.tag
+    }
+}
+
+impl<'xml, T> Reader<'xml, T>
+where
+    T: ?Sized + AsRef<[u8]>,
+    Self: Iterator<Item = Result<Event<'xml, T>, Error>>,
+{
+    /// Gets the `<?xml ...?>` declaration leading the input, if there is one.
+    ///
+    /// This looks at the start of the input regardless of where the reader is,
+    /// skipping a UTF-8 BOM and whitespace, and doesn't move the reader.
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::Reader;
+    ///
+    /// let reader = Reader::from_str("<?xml version='1.0' encoding='UTF-8' standalone='yes'?><a/>");
+    /// let decl = reader.xml_decl().unwrap();
+    /// assert_eq!(decl.version(), Some("1.0"));
+    /// assert_eq!(decl.encoding(), Some("UTF-8"));
+    /// assert_eq!(decl.standalone(), Some(true));
+    /// ```
+    pub fn xml_decl(&self) -> Option<XmlDecl<'xml, T>> {
+        let bom = match self.source.as_ref().starts_with(b"\xEF\xBB\xBF") {
+            true => 3,
+            false => 0,
+        };
+        match Reader::resume(self.source, bom, true)?.next()? {
+            Ok(Event::ProcessingInstruction(tag)) if tag.name().as_ref() == b"xml" => {
+                Some(XmlDecl { tag })
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,320 @@
+//! Entity decoding utilities for raw text and attribute values.
+//!
+//! Only the predefined entities (`&amp;`, `&lt;`, `&gt;`, `&quot;`, `&apos;`) and numeric
+//! character references (`&#38;`, `&#x26;`) are known.\
+//! The lenient functions leave anything else untouched like AS3 does,
+//! while the `try_` functions report it as [`Error::UnknownEntity`].\
+//! More entities, such as HTML's `&nbsp;`, can be decoded with an [`EntityTable`].
+
+use crate::{memchr, sl, sl_to, Error};
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::str;
+
+/// Decodes the entities in `text`, leaving unknown ones untouched.
+///
+/// This only allocates if there's anything to decode.
+///
+/// # Example
+/// ```rust
+/// let text = lazyxml::decode::decode_text(b"Tom &amp; Jerry &copy;");
+/// assert_eq!(text, &b"Tom & Jerry &copy;"[..]);
+/// ```
+pub fn decode_text(text: &[u8]) -> Cow<'_, [u8]> {
+    match decode(text, false, None) {
+        Ok(decoded) => decoded,
+        Err(_) => unreachable!(),
+    }
+}
+
+/// Decodes the entities in `text`, leaving unknown ones untouched.
+///
+/// See [`decode_text`] for details.
+pub fn decode_text_str(text: &str) -> Cow<'_, str> {
+    into_str(decode_text(text.as_bytes()))
+}
+
+/// Decodes the entities in `text`, failing on the first unknown or malformed one.
+///
+/// This only allocates if there's anything to decode.
+pub fn try_decode_text(text: &[u8]) -> Result<Cow<'_, [u8]>, Error> {
+    decode(text, true, None)
+}
+
+/// Decodes the entities in `text`, failing on the first unknown or malformed one.
+///
+/// See [`try_decode_text`] for details.
+pub fn try_decode_text_str(text: &str) -> Result<Cow<'_, str>, Error> {
+    try_decode_text(text.as_bytes()).map(into_str)
+}
+
+/// Entities known beyond the predefined ones, for decoding legacy content.
+///
+/// The predefined entities and numeric character references are always decoded,
+/// and take precedence over entities of the same name in the table.
+///
+/// # Example
+/// ```rust
+/// use lazyxml::decode::EntityTable;
+///
+/// let mut entities = EntityTable::html();
+/// entities.insert("flash", "Macromedia Flash");
+/// assert_eq!(entities.decode_str("&flash;&nbsp;8 &copy; &amp; &unknown;"), "Macromedia Flash\u{a0}8 \u{a9} & &unknown;");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct EntityTable {
+    entities: Vec<(String, String)>,
+    /// Length of the longest name, past which there's no use looking for the `;`.
+    longest: usize,
+}
+
+impl EntityTable {
+    /// Constructs an empty table, which only decodes the predefined entities.
+    pub const fn new() -> Self {
+        Self {
+            entities: Vec::new(),
+            longest: 0,
+        }
+    }
+
+    /// Constructs a table with the HTML entities most often found in legacy content,
+    /// such as `&nbsp;`, `&copy;` and `&mdash;`.
+    pub fn html() -> Self {
+        const HTML: &[(&str, char)] = &[
+            ("nbsp", '\u{a0}'),
+            ("iexcl", '\u{a1}'),
+            ("cent", '\u{a2}'),
+            ("pound", '\u{a3}'),
+            ("yen", '\u{a5}'),
+            ("sect", '\u{a7}'),
+            ("copy", '\u{a9}'),
+            ("laquo", '\u{ab}'),
+            ("reg", '\u{ae}'),
+            ("deg", '\u{b0}'),
+            ("plusmn", '\u{b1}'),
+            ("micro", '\u{b5}'),
+            ("para", '\u{b6}'),
+            ("middot", '\u{b7}'),
+            ("raquo", '\u{bb}'),
+            ("iquest", '\u{bf}'),
+            ("times", '\u{d7}'),
+            ("divide", '\u{f7}'),
+            ("ndash", '\u{2013}'),
+            ("mdash", '\u{2014}'),
+            ("lsquo", '\u{2018}'),
+            ("rsquo", '\u{2019}'),
+            ("ldquo", '\u{201c}'),
+            ("rdquo", '\u{201d}'),
+            ("bull", '\u{2022}'),
+            ("hellip", '\u{2026}'),
+            ("euro", '\u{20ac}'),
+            ("trade", '\u{2122}'),
+        ];
+        let mut table = Self::new();
+        for &(name, ch) in HTML {
+            table.insert(name, ch.encode_utf8(&mut [0; 4]));
+        }
+        table
+    }
+
+    /// Adds an entity, replacing any previous one of the same name.
+    ///
+    /// `name` is what goes between the `&` and `;`.
+    pub fn insert(&mut self, name: &str, replacement: &str) -> &mut Self {
+        self.longest = self.longest.max(name.len());
+        match self.entities.iter_mut().find(|(x, _)| x == name) {
+            Some((_, existing)) => *existing = replacement.to_string(),
+            None => self
+                .entities
+                .push((name.to_string(), replacement.to_string())),
+        }
+        self
+    }
+
+    /// Gets what the entity of the given name is replaced with, if it's in the table.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.lookup(name.as_bytes())
+    }
+
+    fn lookup(&self, name: &[u8]) -> Option<&str> {
+        self.entities
+            .iter()
+            .find(|(x, _)| x.as_bytes() == name)
+            .map(|(_, replacement)| replacement.as_str())
+    }
+
+    /// Decodes the entities in `text`, leaving unknown ones untouched.
+    ///
+    /// This only allocates if there's anything to decode.
+    pub fn decode<'a>(&self, text: &'a [u8]) -> Cow<'a, [u8]> {
+        match decode(text, false, Some(self)) {
+            Ok(decoded) => decoded,
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// Decodes the entities in `text`, leaving unknown ones untouched.
+    ///
+    /// See [`EntityTable::decode`] for details.
+    pub fn decode_str<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        into_str(self.decode(text.as_bytes()))
+    }
+
+    /// Decodes the entities in `text`, failing on the first unknown or malformed one.
+    ///
+    /// This only allocates if there's anything to decode.
+    pub fn try_decode<'a>(&self, text: &'a [u8]) -> Result<Cow<'a, [u8]>, Error> {
+        decode(text, true, Some(self))
+    }
+
+    /// Decodes the entities in `text`, failing on the first unknown or malformed one.
+    ///
+    /// See [`EntityTable::try_decode`] for details.
+    pub fn try_decode_str<'a>(&self, text: &'a str) -> Result<Cow<'a, str>, Error> {
+        self.try_decode(text.as_bytes()).map(into_str)
+    }
+}
+
+fn decode<'a>(
+    text: &'a [u8],
+    strict: bool,
+    table: Option<&EntityTable>,
+) -> Result<Cow<'a, [u8]>, Error> {
+    let mut out = Vec::new();
+    let (mut literal_start, mut offset) = (0, 0);
+    while let Some(amp) = memchr(b'&', sl(text, offset)) {
+        offset += amp;
+        let rest = sl(text, offset);
+        let mut buf = [0; 4];
+        let decoded = match entity_at(rest) {
+            Some((len, ch)) => Some((len, ch.encode_utf8(&mut buf).as_bytes())),
+            None => table.and_then(|table| {
+                // Only look as far as the `;` of the longest name, so `&&&&` stays linear.
+                let window = sl_to(rest, rest.len().min(table.longest + 2));
+                let semi = memchr(b';', window)?;
+                let replacement = table.lookup(sl_to(sl(rest, 1), semi - 1))?;
+                Some((semi + 1, replacement.as_bytes()))
+            }),
+        };
+        match decoded {
+            Some((len, replacement)) => {
+                out.extend_from_slice(&text[literal_start..offset]);
+                out.extend_from_slice(replacement);
+                offset += len;
+                literal_start = offset;
+            }
+            None if strict => return Err(Error::UnknownEntity(offset)),
+            None => offset += 1,
+        }
+    }
+    if literal_start == 0 {
+        Ok(Cow::Borrowed(text))
+    } else {
+        out.extend_from_slice(sl(text, literal_start));
+        Ok(Cow::Owned(out))
+    }
+}
+
+fn into_str(bytes: Cow<'_, [u8]>) -> Cow<'_, str> {
+    // SAFETY: Entities are replaced with whole characters, so the output is still valid UTF-8.
+    match bytes {
+        Cow::Borrowed(x) => Cow::Borrowed(unsafe { str::from_utf8_unchecked(x) }),
+        Cow::Owned(x) => Cow::Owned(unsafe { String::from_utf8_unchecked(x) }),
+    }
+}
+
+/// Resolves an entity reference, given what's between the `&` and `;`.
+///
+/// Only the predefined entities and numeric character references are known.
+pub(crate) fn resolve_entity(name: &[u8]) -> Option<char> {
+    let (digits, radix) = match name {
+        b"amp" => return Some('&'),
+        b"lt" => return Some('<'),
+        b"gt" => return Some('>'),
+        b"quot" => return Some('"'),
+        b"apos" => return Some('\''),
+        [b'#', b'x', hex @ ..] | [b'#', b'X', hex @ ..] => (hex, 16),
+        [b'#', dec @ ..] => (dec, 10),
+        _ => return None,
+    };
+    if digits.is_empty() || digits.len() > 8 {
+        return None;
+    }
+    let mut code = 0u32;
+    for &ch in digits {
+        code = code * radix + (ch as char).to_digit(radix)?;
+    }
+    char::from_u32(code)
+}
+
+/// Finds the entity reference at the start of `text` (which starts with `&`),
+/// returning its length up to and including the `;` along with what it resolves to.
+pub(crate) fn entity_at(text: &[u8]) -> Option<(usize, char)> {
+    // Longest known reference is a numeric one like `&#x10FFFF;`, anything longer is literal.
+    let window = text.get(1..).map(|x| sl_to(x, x.len().min(10)))?;
+    let semi = memchr(b';', window)?;
+    resolve_entity(sl_to(window, semi)).map(|ch| (semi + 2, ch))
+}
+
+/// Compares the raw `value` with entities decoded against `other`, without allocating.
+///
+/// Unknown entities are compared literally, like AS3 leaves them untouched.
+pub(crate) fn eq_decoded(value: &[u8], mut other: &[u8]) -> bool {
+    let mut rest = value;
+    while let Some(amp) = memchr(b'&', rest) {
+        match other.get(..amp) {
+            Some(literal) if literal == sl_to(rest, amp) => other = sl(other, amp),
+            _ => return false,
+        }
+        rest = sl(rest, amp);
+        let (len, decoded) = match entity_at(rest) {
+            Some((len, ch)) => (len, ch),
+            None => (1, '&'),
+        };
+        let mut buf = [0; 4];
+        let decoded = decoded.encode_utf8(&mut buf).as_bytes();
+        if !other.starts_with(decoded) {
+            return false;
+        }
+        other = sl(other, decoded.len());
+        rest = sl(rest, len);
+    }
+    rest == other
+}
+
+pub(crate) enum Chunk<'a> {
+    Literal(&'a [u8]),
+    Entity(char),
+}
+
+/// Splits off the next chunk of `text` for [`DecodeChunks`](crate::DecodeChunks).
+pub(crate) fn next_decode_chunk<'a>(text: &mut &'a [u8]) -> Option<Chunk<'a>> {
+    if text.is_empty() {
+        return None;
+    }
+    if let Some((len, ch)) = entity_at(text) {
+        *text = sl(text, len);
+        return Some(Chunk::Entity(ch));
+    }
+    // Unknown entities stay part of the literal run, so skip past their `&`.
+    let mut end = 1;
+    loop {
+        match memchr(b'&', sl(text, end)) {
+            Some(amp) if entity_at(sl(text, end + amp)).is_some() => {
+                end += amp;
+                break;
+            }
+            Some(amp) => end += amp + 1,
+            None => {
+                end = text.len();
+                break;
+            }
+        }
+    }
+    let literal = sl_to(text, end);
+    *text = sl(text, end);
+    Some(Chunk::Literal(literal))
+}
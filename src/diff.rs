@@ -0,0 +1,182 @@
+//! Comparing two documents event by event, such as to check that a round-trip kept everything.
+
+use crate::{decode::decode_text_str, Error, Event, Position, Reader, Tag};
+use alloc::{borrow::Cow, vec::Vec};
+
+/// Settings for [`equivalent`] and [`diff`], deciding which differences don't count.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DiffOptions {
+    attribute_order: bool,
+    whitespace: bool,
+    comments: bool,
+}
+
+impl DiffOptions {
+    /// Constructs the default options, under which whitespace, comments
+    /// and the order of attributes all count.
+    pub const fn new() -> Self {
+        Self {
+            attribute_order: false,
+            whitespace: false,
+            comments: false,
+        }
+    }
+
+    /// Sets whether the same attributes in a different order are equivalent.
+    pub const fn ignore_attribute_order(mut self, ignore: bool) -> Self {
+        self.attribute_order = ignore;
+        self
+    }
+
+    /// Sets whether whitespace is ignored, which drops whitespace between tags
+    /// and collapses it within text, as [`Text::normalized`](crate::Text::normalized) does.
+    pub const fn ignore_whitespace(mut self, ignore: bool) -> Self {
+        self.whitespace = ignore;
+        self
+    }
+
+    /// Sets whether comments are ignored.
+    pub const fn ignore_comments(mut self, ignore: bool) -> Self {
+        self.comments = ignore;
+        self
+    }
+}
+
+/// Where two documents stop being equivalent, as found by [`diff`].
+///
+/// Each position is at the start of the first differing event in that document,
+/// or at its end if it ran out of events first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// Position in the first document.
+    pub left: Position,
+    /// Position in the second document.
+    pub right: Position,
+}
+
+/// Event reduced to what's compared, with entities decoded.
+#[derive(PartialEq)]
+enum Item<'xml> {
+    Start(&'xml str, Vec<(&'xml str, Cow<'xml, str>)>),
+    End(&'xml str),
+    Text(Cow<'xml, str>),
+    Comment(&'xml str),
+    ProcessingInstruction(&'xml str, &'xml str),
+    DocType(&'xml str),
+}
+
+/// One of the documents being compared.
+struct Side<'xml> {
+    reader: Reader<'xml, str>,
+    options: DiffOptions,
+    /// End of an empty tag, which compares like a start tag followed by an end tag.
+    pending_end: Option<&'xml str>,
+}
+
+impl<'xml> Side<'xml> {
+    fn new(xml: &'xml str, options: DiffOptions) -> Self {
+        let mut reader = Reader::from_str(xml);
+        reader
+            .trim_whitespace(options.whitespace)
+            .emit_comments(!options.comments);
+        Self {
+            reader,
+            options,
+            pending_end: None,
+        }
+    }
+
+    /// Reads the next item along with its offset.
+    fn next(&mut self) -> Result<Option<(usize, Item<'xml>)>, Error> {
+        if let Some(name) = self.pending_end.take() {
+            return Ok(Some((self.reader.event_start, Item::End(name))));
+        }
+        let event = match self.reader.next() {
+            Some(event) => event?,
+            None => return Ok(None),
+        };
+        let item = match event {
+            Event::Start(tag) => self.start(&tag)?,
+            Event::Empty(tag) => {
+                self.pending_end = Some(tag.name());
+                self.start(&tag)?
+            }
+            Event::End(tag) => Item::End(tag.name()),
+            Event::Text(text) => match self.options.whitespace {
+                true => Item::Text(match text.normalized() {
+                    Cow::Borrowed(text) => decode_text_str(text),
+                    Cow::Owned(text) => Cow::Owned(decode_text_str(&text).into_owned()),
+                }),
+                false => Item::Text(text.decoded()),
+            },
+            Event::CData(text) => Item::Text(Cow::Borrowed(text.content())),
+            Event::Comment(text) => Item::Comment(text.content()),
+            Event::ProcessingInstruction(tag) => {
+                Item::ProcessingInstruction(tag.name(), tag.content().trim_end())
+            }
+            Event::DocType(text) => Item::DocType(text.content().trim()),
+        };
+        Ok(Some((self.reader.event_start, item)))
+    }
+
+    fn start(&self, tag: &Tag<'xml, str>) -> Result<Item<'xml>, Error> {
+        let mut attributes = tag
+            .attributes()
+            .map(|attr| attr.map(|attr| (attr.key(), attr.decoded_value())))
+            .collect::<Result<Vec<_>, _>>()?;
+        if self.options.attribute_order {
+            attributes.sort_by(|a, b| a.0.cmp(b.0));
+        }
+        Ok(Item::Start(tag.name(), attributes))
+    }
+}
+
+/// Checks whether two documents are equivalent under the given options.
+///
+/// See [`diff`] for what's compared.
+///
+/// # Example
+/// ```rust
+/// use lazyxml::diff::{equivalent, DiffOptions};
+///
+/// let options = DiffOptions::new().ignore_attribute_order(true);
+/// assert!(equivalent("<a x='1' y=\"&amp;\"/>", "<a y='&#38;' x='1'></a>", options).unwrap());
+/// assert!(!equivalent("<a x='1'/>", "<a x='2'/>", options).unwrap());
+/// ```
+pub fn equivalent(a: &str, b: &str, options: DiffOptions) -> Result<bool, Error> {
+    diff(a, b, options).map(|divergence| divergence.is_none())
+}
+
+/// Finds where two documents stop being equivalent under the given options,
+/// or `None` if they are.
+///
+/// Documents are compared event by event, with entities decoded in text and attribute values.
+/// Empty tags are equivalent to a start tag directly followed by its end tag,
+/// and CDATA sections to text with the same content.\
+/// An error in either document is returned as-is, even if they diverge before it.
+///
+/// # Example
+/// ```rust
+/// use lazyxml::diff::{diff, DiffOptions};
+///
+/// let divergence = diff("<a>\n  <b/>\n</a>", "<a>\n  <c/>\n</a>", DiffOptions::new()).unwrap();
+/// let divergence = divergence.unwrap();
+/// assert_eq!((divergence.left.line, divergence.left.column), (2, 3));
+/// ```
+pub fn diff(a: &str, b: &str, options: DiffOptions) -> Result<Option<Divergence>, Error> {
+    let (mut left, mut right) = (Side::new(a, options), Side::new(b, options));
+    loop {
+        let (l_offset, r_offset) = match (left.next()?, right.next()?) {
+            (None, None) => return Ok(None),
+            (Some((_, l)), Some((_, r))) if l == r => continue,
+            (l, r) => (l.map_or(a.len(), |x| x.0), r.map_or(b.len(), |x| x.0)),
+        };
+        // Errors further down still count, as the documents would be compared up to them.
+        while left.next()?.is_some() {}
+        while right.next()?.is_some() {}
+        return Ok(Some(Divergence {
+            left: left.reader.position_of(l_offset),
+            right: right.reader.position_of(r_offset),
+        }));
+    }
+}
@@ -0,0 +1,129 @@
+//! E4X-style queries over a [`Document`], mirroring ActionScript's `XMLList`.
+//!
+//! | ActionScript           | lazyxml                                  |
+//! |------------------------|------------------------------------------|
+//! | `xml.Script`           | `xml.children("Script")`                 |
+//! | `xml.*`                | `xml.children("*")`                      |
+//! | `xml..Frame`           | `xml.descendants("Frame")`               |
+//! | `xml.Script.@time`     | `xml.children("Script").attr("time")`    |
+//! | `xml.Script[0]`        | `xml.children("Script").get(0)`          |
+//! | `xml.Script.(@id == 2)`| `xml.children("Script").filter(...)`     |
+//! | `xml.Script.length()`  | `xml.children("Script").len()`           |
+
+use crate::tree::{Document, Node};
+use alloc::{string::String, vec, vec::Vec};
+
+/// Ordered list of elements, like an `XMLList` in ActionScript.
+///
+/// Queries on a list apply to every element in it, so they can be chained
+/// the same way E4X expressions are.
+///
+/// # Example
+/// ```rust
+/// use lazyxml::{e4x::XmlList, tree::Document};
+///
+/// let doc = Document::parse("<Anim><Script time=\"0\"/><Layer><Frame/><Frame/></Layer></Anim>")
+///     .unwrap();
+/// let xml = XmlList::new(&doc);
+/// assert_eq!(xml.children("Script").attr("time"), Some("0"));
+/// assert_eq!(xml.descendants("Frame").len(), 2);
+/// ```
+#[derive(Clone, Debug)]
+pub struct XmlList<'a, 'xml> {
+    nodes: Vec<&'a Node<'xml>>,
+}
+
+impl<'a, 'xml> XmlList<'a, 'xml> {
+    /// Constructs a list holding the root element of `doc`, like `new XML(text)` in ActionScript.
+    pub fn new(doc: &'a Document<'xml>) -> Self {
+        Self {
+            nodes: doc.root().into_iter().collect(),
+        }
+    }
+
+    /// Constructs a list holding only `node`.
+    pub fn from_node(node: &'a Node<'xml>) -> Self {
+        Self { nodes: vec![node] }
+    }
+
+    /// Gets the child elements named `name` of every element, or all of them for `"*"`.
+    pub fn children(&self, name: &str) -> Self {
+        let nodes = self
+            .nodes
+            .iter()
+            .flat_map(|node| node.elements())
+            .filter(|node| matches_name(node, name))
+            .collect();
+        Self { nodes }
+    }
+
+    /// Gets the descendant elements named `name` of every element in document order,
+    /// or all of them for `"*"`.
+    pub fn descendants(&self, name: &str) -> Self {
+        // Walked with a stack of the elements still to visit rather than recursively,
+        // as documents can be nested deeper than the call stack allows.
+        let mut nodes = Vec::new();
+        let mut stack = Vec::new();
+        for node in &self.nodes {
+            stack.extend(node.children().iter().rev());
+            while let Some(node) = stack.pop() {
+                if node.name().is_none() {
+                    continue;
+                }
+                if matches_name(node, name) {
+                    nodes.push(node);
+                }
+                stack.extend(node.children().iter().rev());
+            }
+        }
+        Self { nodes }
+    }
+
+    /// Keeps only the elements matching `predicate`, like `list.(predicate)` in ActionScript.
+    pub fn filter(mut self, mut predicate: impl FnMut(&Node<'xml>) -> bool) -> Self {
+        self.nodes.retain(|node| predicate(node));
+        self
+    }
+
+    /// Gets the raw value of the attribute `key` on the first element that has it.
+    pub fn attr(&self, key: &str) -> Option<&'xml str> {
+        self.nodes.iter().find_map(|node| node.attribute(key))
+    }
+
+    /// Gets the raw values of the attribute `key` on every element that has it.
+    pub fn attrs(&self, key: &str) -> Vec<&'xml str> {
+        self.nodes
+            .iter()
+            .filter_map(|node| node.attribute(key))
+            .collect()
+    }
+
+    /// Gets the raw text of every element joined together, like `toString()` on simple content.
+    pub fn text(&self) -> String {
+        self.nodes.iter().map(|node| node.text()).collect()
+    }
+
+    /// Gets the element at `index`, like `list[index]` in ActionScript.
+    pub fn get(&self, index: usize) -> Option<&'a Node<'xml>> {
+        self.nodes.get(index).copied()
+    }
+
+    /// Gets the number of elements in the list, like `length()` in ActionScript.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Checks whether the list has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns an iterator over the elements in the list.
+    pub fn iter(&self) -> impl Iterator<Item = &'a Node<'xml>> + '_ {
+        self.nodes.iter().copied()
+    }
+}
+
+fn matches_name(node: &Node<'_>, name: &str) -> bool {
+    name == "*" || node.name() == Some(name)
+}
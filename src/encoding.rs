@@ -0,0 +1,145 @@
+//! Detection and transcoding of the input's character encoding, ahead of the [`Reader`].
+//!
+//! The reader itself only understands ASCII-compatible bytes, so anything else
+//! (such as the UTF-16 files Flash tools like to write) has to go through here first.\
+//! With the `encoding` feature, legacy encodings such as `windows-1252` are supported as well.
+
+use crate::Reader;
+use alloc::{borrow::Cow, string::String};
+use core::{char, str};
+
+/// Character encoding of an XML input, as determined by [`detect_encoding`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Encoding {
+    /// UTF-8, which the [`Reader`] reads directly. This also covers US-ASCII.
+    Utf8,
+    /// UTF-16, little endian.
+    Utf16Le,
+    /// UTF-16, big endian.
+    Utf16Be,
+    /// ISO-8859-1, where every byte is the code point of the same value.
+    Latin1,
+    /// Any other encoding known to `encoding_rs`.
+    #[cfg(feature = "encoding")]
+    Other(&'static encoding_rs::Encoding),
+}
+
+impl Encoding {
+    /// Gets the encoding of the given label from an XML declaration, such as `UTF-16`.
+    ///
+    /// Labels are matched case-insensitively. Plain `UTF-16` is taken as little endian,
+    /// as it only gets here without a BOM saying otherwise.\
+    /// With the `encoding` feature, any label of the WHATWG Encoding Standard is recognized.
+    pub fn for_label(label: &[u8]) -> Option<Self> {
+        const LABELS: &[(&[u8], Encoding)] = &[
+            (b"utf-8", Encoding::Utf8),
+            (b"utf8", Encoding::Utf8),
+            (b"us-ascii", Encoding::Utf8),
+            (b"ascii", Encoding::Utf8),
+            (b"utf-16", Encoding::Utf16Le),
+            (b"utf-16le", Encoding::Utf16Le),
+            (b"utf-16be", Encoding::Utf16Be),
+            (b"iso-8859-1", Encoding::Latin1),
+            (b"latin1", Encoding::Latin1),
+        ];
+        let known = LABELS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(label))
+            .map(|&(_, encoding)| encoding);
+        #[cfg(feature = "encoding")]
+        let known = known.or_else(|| {
+            encoding_rs::Encoding::for_label(label).map(|encoding| match encoding.name() {
+                "UTF-8" => Encoding::Utf8,
+                "UTF-16LE" => Encoding::Utf16Le,
+                "UTF-16BE" => Encoding::Utf16Be,
+                _ => Encoding::Other(encoding),
+            })
+        });
+        known
+    }
+}
+
+/// Determines the encoding of `xml` and the length of its byte order mark, if any.
+///
+/// The BOM takes precedence. Without one, UTF-16 is recognized from how the leading `<`
+/// is laid out, and otherwise the `encoding` of the `<?xml ...?>` declaration is used.\
+/// Anything else, including unknown declared encodings, is taken as UTF-8.
+pub fn detect_encoding(xml: &[u8]) -> (Encoding, usize) {
+    match xml {
+        [0xEF, 0xBB, 0xBF, ..] => (Encoding::Utf8, 3),
+        [0xFF, 0xFE, ..] => (Encoding::Utf16Le, 2),
+        [0xFE, 0xFF, ..] => (Encoding::Utf16Be, 2),
+        [b'<', 0, _, 0, ..] => (Encoding::Utf16Le, 0),
+        [0, b'<', 0, _, ..] => (Encoding::Utf16Be, 0),
+        _ => match declared_encoding(xml).and_then(Encoding::for_label) {
+            // A declaration readable as ASCII can't be right about being UTF-16.
+            Some(Encoding::Utf16Le | Encoding::Utf16Be) | None => (Encoding::Utf8, 0),
+            Some(encoding) => (encoding, 0),
+        },
+    }
+}
+
+/// Gets the value of `encoding` in the leading `<?xml ...?>` declaration, if any.
+fn declared_encoding(xml: &[u8]) -> Option<&[u8]> {
+    Reader::from_bytes(xml).xml_decl()?.encoding()
+}
+
+/// Decodes `xml` into UTF-8 as detected by [`detect_encoding`], without its BOM.
+///
+/// Invalid sequences are replaced with `U+FFFD`, as [`String::from_utf8_lossy`] does.\
+/// This only allocates if the input isn't valid UTF-8 to begin with.
+pub fn decode_input(xml: &[u8]) -> Cow<'_, str> {
+    let (encoding, bom) = detect_encoding(xml);
+    let xml = &xml[bom..];
+    match encoding {
+        Encoding::Utf8 => String::from_utf8_lossy(xml),
+        Encoding::Utf16Le => Cow::Owned(decode_utf16(xml, u16::from_le_bytes)),
+        Encoding::Utf16Be => Cow::Owned(decode_utf16(xml, u16::from_be_bytes)),
+        Encoding::Latin1 => match str::from_utf8(xml) {
+            Ok(xml) if xml.is_ascii() => Cow::Borrowed(xml),
+            _ => Cow::Owned(xml.iter().map(|&ch| ch as char).collect()),
+        },
+        #[cfg(feature = "encoding")]
+        Encoding::Other(encoding) => encoding.decode_without_bom_handling(xml).0,
+    }
+}
+
+fn decode_utf16(xml: &[u8], unit: fn([u8; 2]) -> u16) -> String {
+    let pairs = xml.chunks_exact(2);
+    let odd = !pairs.remainder().is_empty();
+    let units = pairs.map(|pair| unit([pair[0], pair[1]]));
+    let mut text: String = char::decode_utf16(units)
+        .map(|ch| ch.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect();
+    if odd {
+        text.push(char::REPLACEMENT_CHARACTER);
+    }
+    text
+}
+
+impl<'xml> Reader<'xml, str> {
+    /// Constructs a new [`Reader`] from XML bytes in any encoding [`detect_encoding`] knows.
+    ///
+    /// UTF-8 input is read in place, while anything else is transcoded into `buf` first,
+    /// see [`decode_input`] for details.
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::{Event, Reader};
+    ///
+    /// let utf16: Vec<u8> = "\u{feff}<Frame/>".encode_utf16().flat_map(u16::to_le_bytes).collect();
+    /// let mut buf = String::new();
+    /// let mut reader = Reader::from_bytes_auto(&utf16, &mut buf);
+    /// assert!(matches!(reader.next(), Some(Ok(Event::Empty(tag))) if tag.name() == "Frame"));
+    /// ```
+    pub fn from_bytes_auto(xml: &'xml [u8], buf: &'xml mut String) -> Reader<'xml, str> {
+        match decode_input(xml) {
+            Cow::Borrowed(xml) => Reader::from_str(xml),
+            Cow::Owned(xml) => {
+                *buf = xml;
+                Reader::from_str(buf)
+            }
+        }
+    }
+}
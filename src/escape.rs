@@ -0,0 +1,122 @@
+//! Escaping text and attribute values for output, and composing tags from them.
+//!
+//! These are what the [`Writer`](crate::Writer) uses, for building markup without one.
+
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Escapes `&`, `<` and `>` in `text`, so it can be put between tags.
+///
+/// This only allocates if there's anything to escape.
+///
+/// # Example
+/// ```rust
+/// use lazyxml::escape::escape_text;
+///
+/// assert_eq!(escape_text("a < b & c"), "a &lt; b &amp; c");
+/// assert_eq!(escape_text("\"quoted\""), "\"quoted\"");
+/// ```
+pub fn escape_text(text: &str) -> Cow<'_, str> {
+    into_str(escape(text.as_bytes(), false))
+}
+
+/// Escapes `&`, `<`, `>` and `"` in `value`, so it can be put in a double-quoted attribute.
+///
+/// This only allocates if there's anything to escape.
+///
+/// # Example
+/// ```rust
+/// use lazyxml::escape::escape_attribute;
+///
+/// assert_eq!(escape_attribute("say \"hi\" & go"), "say &quot;hi&quot; &amp; go");
+/// ```
+pub fn escape_attribute(value: &str) -> Cow<'_, str> {
+    into_str(escape(value.as_bytes(), true))
+}
+
+/// Escapes the characters of `text` that would otherwise be parsed as markup,
+/// along with `"` if it's going in a double-quoted attribute.
+pub(crate) fn escape(text: &[u8], in_quotes: bool) -> Cow<'_, [u8]> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    for (idx, &ch) in text.iter().enumerate() {
+        let entity: &[u8] = match ch {
+            b'&' => b"&amp;",
+            b'<' => b"&lt;",
+            b'>' => b"&gt;",
+            b'"' if in_quotes => b"&quot;",
+            _ => continue,
+        };
+        out.extend_from_slice(&text[start..idx]);
+        out.extend_from_slice(entity);
+        start = idx + 1;
+    }
+    match start {
+        0 => Cow::Borrowed(text),
+        _ => {
+            out.extend_from_slice(&text[start..]);
+            Cow::Owned(out)
+        }
+    }
+}
+
+fn into_str(bytes: Cow<'_, [u8]>) -> Cow<'_, str> {
+    // SAFETY: Only ASCII characters were replaced with ASCII, so it's still valid UTF-8.
+    unsafe {
+        match bytes {
+            Cow::Borrowed(bytes) => Cow::Borrowed(core::str::from_utf8_unchecked(bytes)),
+            Cow::Owned(bytes) => Cow::Owned(String::from_utf8_unchecked(bytes)),
+        }
+    }
+}
+
+/// Builder composing a start or empty tag as a string, escaping attribute values.
+///
+/// Names and keys are written as-is.
+///
+/// # Example
+/// ```rust
+/// use lazyxml::escape::TagBuilder;
+///
+/// let tag = TagBuilder::new("Item").attr("name", "a & b").attr("id", "1").empty();
+/// assert_eq!(tag, "<Item name=\"a &amp; b\" id=\"1\"/>");
+/// assert_eq!(TagBuilder::new("List").start(), "<List>");
+/// ```
+#[derive(Clone, Debug)]
+pub struct TagBuilder {
+    buf: String,
+}
+
+impl TagBuilder {
+    /// Constructs a builder for a tag named `name`, without any attributes.
+    pub fn new(name: &str) -> Self {
+        let mut buf = "<".to_string();
+        buf.push_str(name);
+        Self { buf }
+    }
+
+    /// Appends an attribute, escaping its value.
+    pub fn attr(mut self, key: &str, value: &str) -> Self {
+        self.buf.push(' ');
+        self.buf.push_str(key);
+        self.buf.push_str("=\"");
+        self.buf.push_str(&escape_attribute(value));
+        self.buf.push('"');
+        self
+    }
+
+    /// Finishes the tag as a `<Start>` tag.
+    pub fn start(mut self) -> String {
+        self.buf.push('>');
+        self.buf
+    }
+
+    /// Finishes the tag as an `<Empty/>` tag.
+    pub fn empty(mut self) -> String {
+        self.buf.push_str("/>");
+        self.buf
+    }
+}
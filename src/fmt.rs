@@ -0,0 +1,368 @@
+//! Reformatting whole documents, such as re-indenting the single-line output of exporters
+//! or shrinking assets before shipping them.
+
+use crate::{Attribute, Error, Event, Reader, Tag};
+use alloc::string::String;
+use core::iter;
+
+/// Settings for [`pretty`].
+#[derive(Clone, Copy, Debug)]
+pub struct PrettyOptions {
+    indent: &'static str,
+    wrap_attributes: Option<usize>,
+    comments: bool,
+}
+
+impl PrettyOptions {
+    /// Constructs the default options, which indent with two spaces, keep comments,
+    /// and never wrap attributes.
+    pub const fn new() -> Self {
+        Self {
+            indent: "  ",
+            wrap_attributes: None,
+            comments: true,
+        }
+    }
+
+    /// Sets what to indent every level of nesting with, such as `"\t"`.
+    pub const fn indent(mut self, indent: &'static str) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Sets how many attributes a tag can have before they're put on their own lines,
+    /// or `None` to always keep them on the tag's line.
+    pub const fn wrap_attributes(mut self, threshold: Option<usize>) -> Self {
+        self.wrap_attributes = threshold;
+        self
+    }
+
+    /// Sets whether comments are kept.
+    pub const fn comments(mut self, keep: bool) -> Self {
+        self.comments = keep;
+        self
+    }
+}
+
+impl Default for PrettyOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Settings for [`minify_with`].
+#[derive(Clone, Copy, Debug)]
+pub struct MinifyOptions {
+    comments: bool,
+}
+
+impl MinifyOptions {
+    /// Constructs the default options, which drop comments.
+    pub const fn new() -> Self {
+        Self { comments: false }
+    }
+
+    /// Sets whether comments are kept.
+    pub const fn comments(mut self, keep: bool) -> Self {
+        self.comments = keep;
+        self
+    }
+}
+
+impl Default for MinifyOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What was last written, which decides where line breaks go.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Last {
+    Nothing,
+    Start,
+    Text,
+    Other,
+}
+
+/// Where line breaks go when indenting, shared with the [`Writer`](crate::Writer)
+/// so that both lay documents out the same way.
+///
+/// Markup is put on its own line unless it directly follows text,
+/// so that indenting never changes the text content of an element.
+pub(crate) struct Layout {
+    depth: usize,
+    last: Last,
+}
+
+impl Layout {
+    pub(crate) const fn new() -> Self {
+        Self {
+            depth: 0,
+            last: Last::Nothing,
+        }
+    }
+
+    /// Line break to write before the next markup, indented `extra` levels past the current one.
+    pub(crate) fn line_break<'i>(
+        &self,
+        indent: &'i str,
+        extra: usize,
+    ) -> impl Iterator<Item = &'i str> {
+        let len = match self.last {
+            Last::Nothing | Last::Text => 0,
+            Last::Start | Last::Other => self.depth + extra + 1,
+        };
+        iter::once("\n").chain(iter::repeat(indent)).take(len)
+    }
+
+    /// Line break to write before an end tag, which stays on its start tag's line if empty.
+    pub(crate) fn end<'i>(&mut self, indent: &'i str) -> impl Iterator<Item = &'i str> {
+        self.depth = self.depth.saturating_sub(1);
+        if self.last == Last::Start {
+            self.last = Last::Nothing;
+        }
+        let line_break = self.line_break(indent, 0);
+        self.last = Last::Other;
+        line_break
+    }
+
+    /// Marks a start tag as written, nesting what follows.
+    pub(crate) fn start(&mut self) {
+        self.depth += 1;
+        self.last = Last::Start;
+    }
+
+    /// Marks text as written, which keeps the next markup on the same line.
+    pub(crate) fn text(&mut self) {
+        self.last = Last::Text;
+    }
+
+    /// Marks any other markup as written.
+    pub(crate) fn other(&mut self) {
+        self.last = Last::Other;
+    }
+}
+
+struct Pretty<'o> {
+    out: String,
+    options: &'o PrettyOptions,
+    layout: Layout,
+}
+
+impl Pretty<'_> {
+    /// Puts the next markup on its own line, unless it directly follows text.
+    fn line_break(&mut self, extra: usize) {
+        self.out
+            .extend(self.layout.line_break(self.options.indent, extra));
+    }
+
+    fn tag(&mut self, tag: &Tag<'_, str>, close: &str) -> Result<(), Error> {
+        self.line_break(0);
+        self.out.push('<');
+        self.out.push_str(tag.name());
+        let count = tag.attributes().count();
+        let wrap = self.options.wrap_attributes.is_some_and(|x| count > x);
+        for attr in tag.attributes() {
+            let attr = attr?;
+            match wrap {
+                true => {
+                    self.layout.other();
+                    self.line_break(1);
+                }
+                false => self.out.push(' '),
+            }
+            push_attribute(&mut self.out, &attr);
+        }
+        self.out.push_str(close);
+        Ok(())
+    }
+
+    fn markup(&mut self, open: &str, content: &str, close: &str) {
+        self.line_break(0);
+        self.out.push_str(open);
+        self.out.push_str(content);
+        self.out.push_str(close);
+        self.layout.other();
+    }
+}
+
+/// Re-indents `xml`, putting every tag on its own line unless it directly follows text.
+///
+/// Whitespace between tags is dropped, and any other text is kept as-is, whitespace and all.
+/// The same goes for CDATA sections, which count as text so that mixed content stays on one line.\
+/// Attributes are written with single spaces between them and their raw values,
+/// and the result ends with a line break.
+///
+/// # Example
+/// ```rust
+/// use lazyxml::fmt::{pretty, PrettyOptions};
+///
+/// let xml = "<Config><Item a='1' b='2'>text</Item><!-- note --><Empty/></Config>";
+/// let options = PrettyOptions::new().indent("\t").wrap_attributes(Some(1));
+/// assert_eq!(
+///     pretty(xml, &options).unwrap(),
+///     "<Config>\n\t<Item\n\t\ta=\"1\"\n\t\tb=\"2\">text</Item>\n\t<!-- note -->\n\t<Empty/>\n</Config>\n"
+/// );
+/// ```
+pub fn pretty(xml: &str, options: &PrettyOptions) -> Result<String, Error> {
+    let mut pretty = Pretty {
+        out: String::with_capacity(xml.len() + xml.len() / 2),
+        options,
+        layout: Layout::new(),
+    };
+    let mut reader = Reader::from_str(xml);
+    reader.trim_whitespace(false);
+    for event in reader {
+        match event? {
+            Event::Start(tag) => {
+                pretty.tag(&tag, ">")?;
+                pretty.layout.start();
+            }
+            Event::Empty(tag) => {
+                pretty.tag(&tag, "/>")?;
+                pretty.layout.other();
+            }
+            Event::End(tag) => {
+                pretty.out.extend(pretty.layout.end(options.indent));
+                pretty.out.push_str("</");
+                pretty.out.push_str(tag.name());
+                pretty.out.push('>');
+            }
+            Event::Text(text) if text.content().trim().is_empty() => (),
+            Event::Text(text) => {
+                pretty.out.push_str(text.content());
+                pretty.layout.text();
+            }
+            Event::CData(text) => {
+                pretty.out.push_str("<![CDATA[");
+                pretty.out.push_str(text.content());
+                pretty.out.push_str("]]>");
+                pretty.layout.text();
+            }
+            Event::Comment(text) if options.comments => {
+                pretty.markup("<!--", text.content(), "-->")
+            }
+            Event::Comment(_) => (),
+            Event::ProcessingInstruction(tag) => match tag.content() {
+                "" => pretty.markup("<?", tag.name(), "?>"),
+                content => {
+                    pretty.markup("<?", tag.name(), " ");
+                    pretty.out.push_str(content);
+                    pretty.out.push_str("?>");
+                }
+            },
+            Event::DocType(text) => pretty.markup("<!DOCTYPE", text.content(), ">"),
+        }
+    }
+    if !pretty.out.is_empty() {
+        pretty.out.push('\n');
+    }
+    Ok(pretty.out)
+}
+
+/// Shrinks `xml` by dropping whitespace between tags and comments,
+/// and collapsing elements without content into `<Empty/>` tags.
+///
+/// See [`minify_with`] for details.
+///
+/// # Example
+/// ```rust
+/// use lazyxml::fmt::minify;
+///
+/// let xml = "<Config>\n  <Item a = '1'>  text </Item>\n  <!-- note -->\n  <Empty></Empty>\n</Config>\n";
+/// assert_eq!(minify(xml).unwrap(), "<Config><Item a=\"1\">  text </Item><Empty/></Config>");
+/// ```
+pub fn minify(xml: &str) -> Result<String, Error> {
+    minify_with(xml, MinifyOptions::new())
+}
+
+/// Shrinks `xml` with the given options, in a single pass over its events.
+///
+/// Whitespace-only text is dropped, and any other text is kept as-is, whitespace and all.
+/// Attributes are written with single spaces between them and their raw values.\
+/// Elements are collapsed if nothing is kept between their start and end tags,
+/// so an element only holding comments is collapsed when comments are dropped.
+pub fn minify_with(xml: &str, options: MinifyOptions) -> Result<String, Error> {
+    let mut out = String::with_capacity(xml.len());
+    let mut reader = Reader::from_str(xml);
+    reader.trim_whitespace(false);
+    // Start tag waiting to see if the element has any content, which is written up to its `>`.
+    let mut pending = false;
+    for event in reader {
+        let event = event?;
+        match (&event, pending) {
+            (Event::Text(text), _) if text.content().trim().is_empty() => continue,
+            (Event::Comment(_), _) if !options.comments => continue,
+            (Event::End(_), true) => {
+                out.push_str("/>");
+                pending = false;
+                continue;
+            }
+            (_, true) => out.push('>'),
+            _ => (),
+        }
+        pending = false;
+        match event {
+            Event::Start(ref tag) | Event::Empty(ref tag) => {
+                out.push('<');
+                out.push_str(tag.name());
+                for attr in tag.attributes() {
+                    out.push(' ');
+                    push_attribute(&mut out, &attr?);
+                }
+                match event {
+                    Event::Start(_) => pending = true,
+                    _ => out.push_str("/>"),
+                }
+            }
+            Event::End(tag) => {
+                out.push_str("</");
+                out.push_str(tag.name());
+                out.push('>');
+            }
+            Event::Text(text) => out.push_str(text.content()),
+            Event::CData(text) => {
+                out.push_str("<![CDATA[");
+                out.push_str(text.content());
+                out.push_str("]]>");
+            }
+            Event::Comment(text) => {
+                out.push_str("<!--");
+                out.push_str(text.content());
+                out.push_str("-->");
+            }
+            Event::ProcessingInstruction(tag) => {
+                out.push_str("<?");
+                out.push_str(tag.name());
+                if !tag.content().is_empty() {
+                    out.push(' ');
+                    out.push_str(tag.content());
+                }
+                out.push_str("?>");
+            }
+            Event::DocType(text) => {
+                out.push_str("<!DOCTYPE");
+                out.push_str(text.content());
+                out.push('>');
+            }
+        }
+    }
+    if pending {
+        out.push('>');
+    }
+    Ok(out)
+}
+
+/// Writes an attribute as `key="value"`, keeping the value raw.
+fn push_attribute(out: &mut String, attr: &Attribute<'_, str>) {
+    // Raw values are still escaped, except for the other kind of quote.
+    let quote = match attr.value().contains('"') {
+        true => '\'',
+        false => '"',
+    };
+    out.push_str(attr.key());
+    out.push('=');
+    out.push(quote);
+    out.push_str(attr.value());
+    out.push(quote);
+}
@@ -0,0 +1,100 @@
+//! Entry points for fuzzing harnesses, such as `cargo fuzz` targets.
+//!
+//! These read arbitrary input with every part of the crate that works on untrusted documents,
+//! so a harness only has to hand the bytes over:
+//!
+//! ```rust,ignore
+//! #![no_main]
+//! libfuzzer_sys::fuzz_target!(|data: &[u8]| lazyxml::fuzz::parse_all(data));
+//! ```
+
+use crate::{decode, validate, Dialect, Event, Reader};
+use core::str;
+
+/// Reads `data` to completion, going through every attribute and decoding everything on the way.
+///
+/// The first byte picks the reader's settings, so that the fuzzer explores them too,
+/// and the rest is the document.
+/// If the document is valid UTF-8, it's also read as a string,
+/// and [`validate`] is checked to agree with the reader.
+///
+/// # Panics
+/// Panics if any of that panics, or if [`validate`] disagrees with the reader,
+/// which is what fuzzing is meant to find.
+pub fn parse_all(data: &[u8]) {
+    let (&flags, xml) = match data.split_first() {
+        Some(split) => split,
+        None => return,
+    };
+    let mut reader = Reader::from_bytes(xml);
+    configure(&mut reader, flags);
+    drive(reader, flags);
+    if let Ok(xml) = str::from_utf8(xml) {
+        let mut reader = Reader::from_str(xml);
+        configure(&mut reader, flags);
+        for event in reader {
+            if let Ok(Event::Start(tag) | Event::Empty(tag)) = event {
+                tag.attributes().map_while(Result::ok).for_each(|attr| {
+                    attr.decoded_value();
+                });
+            }
+        }
+
+        let mut reader = Reader::from_str(xml);
+        reader.check_structure(true);
+        let expected = reader.all(|event| event.is_ok());
+        assert_eq!(
+            validate(xml),
+            expected,
+            "validate disagrees with the reader"
+        );
+    }
+}
+
+fn configure<T: ?Sized>(reader: &mut Reader<'_, T>, flags: u8) {
+    let flag = |bit: u8| flags & 1 << bit != 0;
+    reader
+        .trim_whitespace(flag(0))
+        .recover(flag(1))
+        .check_structure(flag(2))
+        .strict_attributes(flag(3))
+        .allow_unquoted_attributes(flag(4))
+        .allow_bare_attributes(flag(5))
+        .strict_names(flag(6));
+    if flag(7) {
+        reader.dialect(Dialect::Html);
+    }
+}
+
+fn drive(mut reader: Reader<'_, [u8]>, flags: u8) {
+    while let Some(event) = reader.next() {
+        let event = match event {
+            Ok(event) => event,
+            Err(_) => continue,
+        };
+        let start = matches!(event, Event::Start(_));
+        match event {
+            Event::Start(tag) | Event::Empty(tag) | Event::ProcessingInstruction(tag) => {
+                for attr in tag.attributes().flatten() {
+                    attr.decoded_value();
+                    let _ = decode::try_decode_text(attr.value());
+                    let _ = (attr.span(), attr.value_span(), attr.local_name());
+                }
+            }
+            Event::Text(text)
+            | Event::CData(text)
+            | Event::Comment(text)
+            | Event::DocType(text) => {
+                text.decoded();
+                text.normalized();
+                text.decode_chunks().for_each(drop);
+                let _ = (text.trim_info(), decode::try_decode_text(text.content()));
+            }
+            Event::End(_) => (),
+        }
+        // Reading raw content skips over whole elements, which is its own code path.
+        if start && flags & 0b11 == 0b11 && reader.depth() % 3 == 1 {
+            let _ = reader.read_text();
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! Dictionary of names, to tell tags apart by small integers instead of comparing bytes.
+
+use alloc::{boxed::Box, collections::BTreeMap, vec::Vec};
+use core::{convert::TryFrom, fmt};
+
+/// Dictionary mapping names to symbols, which are small integers handed out in insertion order.
+///
+/// Set it on a [`Reader`](crate::Reader) with [`Reader::with_interner`](crate::Reader::with_interner)
+/// to get the symbol of every tag's name with [`Tag::symbol`](crate::Tag::symbol).
+/// Attribute keys can be looked up directly with [`Interner::get`].\
+/// As symbols start at `0` and go up by one, they can be matched against constants
+/// as long as the names are interned in the same order.
+///
+/// # Example
+/// ```rust
+/// use lazyxml::{Event, Interner, Reader};
+///
+/// const FRAME: u32 = 0;
+/// const SHAPE: u32 = 1;
+///
+/// let mut interner = Interner::new();
+/// assert_eq!(interner.intern("Frame"), FRAME);
+/// assert_eq!(interner.intern("Shape"), SHAPE);
+///
+/// let mut reader = Reader::from_str("<Frame><Shape/><Other/></Frame>");
+/// reader.with_interner(&interner);
+/// let (mut frames, mut shapes, mut others) = (0, 0, 0);
+/// for event in reader {
+///     match event.unwrap() {
+///         Event::Start(tag) | Event::Empty(tag) => match tag.symbol() {
+///             Some(FRAME) => frames += 1,
+///             Some(SHAPE) => shapes += 1,
+///             _ => others += 1,
+///         },
+///         _ => (),
+///     }
+/// }
+/// assert_eq!((frames, shapes, others), (1, 1, 1));
+/// ```
+#[derive(Clone, Default)]
+pub struct Interner {
+    names: Vec<Box<[u8]>>,
+    symbols: BTreeMap<Box<[u8]>, u32>,
+}
+
+impl Interner {
+    /// Constructs an empty dictionary.
+    pub const fn new() -> Self {
+        Self {
+            names: Vec::new(),
+            symbols: BTreeMap::new(),
+        }
+    }
+
+    /// Gets the symbol of `name`, adding it to the dictionary if it isn't in there yet.
+    ///
+    /// # Panics
+    /// Panics if there are more names than symbols fit in a [`u32`], `u32::MAX` excluded.
+    pub fn intern<N: ?Sized + AsRef<[u8]>>(&mut self, name: &N) -> u32 {
+        let name = name.as_ref();
+        if let Some(symbol) = self.get(name) {
+            return symbol;
+        }
+        let symbol = u32::try_from(self.names.len())
+            .ok()
+            .filter(|&symbol| symbol != u32::MAX)
+            .expect("too many interned names");
+        self.names.push(name.into());
+        self.symbols.insert(name.into(), symbol);
+        symbol
+    }
+
+    /// Gets the symbol of `name`, if it's in the dictionary.
+    pub fn get<N: ?Sized + AsRef<[u8]>>(&self, name: &N) -> Option<u32> {
+        self.symbols.get(name.as_ref()).copied()
+    }
+
+    /// Gets the name a symbol stands for, if it was handed out by this dictionary.
+    pub fn resolve(&self, symbol: u32) -> Option<&[u8]> {
+        self.names.get(symbol as usize).map(|name| &**name)
+    }
+
+    /// Gets how many names are in the dictionary.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Checks whether the dictionary is empty.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+// Readers hold on to the dictionary, which would otherwise be printed in full along with them.
+impl fmt::Debug for Interner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Interner")
+            .field("len", &self.len())
+            .finish()
+    }
+}
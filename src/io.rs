@@ -0,0 +1,408 @@
+//! Streaming reader over [`std::io::Read`] sources.
+
+use crate::{Error, Event, OpenElement, OwnedEvent, Reader, ReaderBuilder, ReaderState};
+use std::{
+    io::{self, Read},
+    iter::FusedIterator,
+    mem,
+    ops::Range,
+};
+
+/// How many bytes are read from the source at a time.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// XML reader over an [`io::Read`] source, for inputs too big to load into memory at once.
+///
+/// Only as much of the source as is needed to produce the next event is kept in memory,
+/// so the internal buffer grows to the size of the biggest event rather than the whole input.\
+/// Events are either borrowed from the buffer with [`IoReader::read_event`],
+/// or owned with the [`Iterator`] implementation.
+///
+/// Events are read with the settings of a [`ReaderBuilder`], see [`IoReader::settings`].\
+/// Error offsets are relative to the start of the source.
+///
+/// # Example
+/// ```rust
+/// use lazyxml::{Event, IoReader};
+///
+/// let file: &[u8] = b"<Frames><Frame/><Frame/></Frames>";
+/// let mut reader = IoReader::new(file);
+/// let mut frames = 0;
+/// while let Some(event) = reader.read_event() {
+///     if let Event::Empty(tag) = event.unwrap() {
+///         frames += (tag.name() == b"Frame") as usize;
+///     }
+/// }
+/// assert_eq!(frames, 2);
+/// ```
+pub struct IoReader<R> {
+    inner: R,
+    buf: Buffer,
+}
+
+/// Reader over XML that arrives in arbitrary pieces, such as from a protocol stream.
+///
+/// Data is handed over with [`FeedReader::feed`] as it comes in, and every event is yielded
+/// as soon as it's complete. Until then, [`FeedReader::next_event`] yields
+/// [`Error::NeedMoreData`], after which reading can carry on once more has been fed.\
+/// Text is only known to be complete at the next `<`, so at the end of the input,
+/// [`FeedReader::finish`] has to be called to get the last of it.
+///
+/// Error offsets are relative to the start of the input.
+///
+/// # Example
+/// ```rust
+/// use lazyxml::{Error, Event, FeedReader};
+///
+/// let mut reader = FeedReader::new();
+/// reader.feed(b"<policy-file-req");
+/// assert!(matches!(reader.next_event(), Some(Err(Error::NeedMoreData))));
+/// reader.feed(b"/>");
+/// assert!(matches!(reader.next_event(), Some(Ok(Event::Empty(_)))));
+/// reader.finish();
+/// assert!(reader.next_event().is_none());
+/// ```
+pub struct FeedReader {
+    buf: Buffer,
+}
+
+/// Buffer holding what's been read from the source of an [`IoReader`], async reader or [`FeedReader`],
+/// from the start of the next event on.
+pub(crate) struct Buffer {
+    buf: Vec<u8>,
+    start: usize,
+    drained: usize,
+    eof: bool,
+    /// Whether an error's been returned, after which nothing more is read.
+    failed: bool,
+    pub(crate) settings: ReaderBuilder,
+    carry: Carry,
+}
+
+/// What the [`Reader`] of a [`Buffer`] keeps track of from one event to the next,
+/// with offsets into the buffer.
+struct Carry {
+    depth: usize,
+    open: Vec<OpenElement>,
+    raw_text: Option<Range<usize>>,
+    xml_space: Vec<(usize, bool)>,
+    events: usize,
+    /// Whether the reader stopped before the end of the source, such as after the root element.
+    ended: bool,
+}
+
+/// What [`Buffer::next_event`] found in the buffer.
+pub(crate) enum Found<'b> {
+    /// Whole event, which the buffer has moved past.
+    Event(Event<'b, [u8]>),
+    /// Nothing, as the source has been read to the end.
+    Done,
+    /// Part of an event at most, so more has to be read from the source.
+    More,
+}
+
+impl<R: Read> IoReader<R> {
+    /// Constructs a new [`IoReader`] over `inner`.
+    ///
+    /// The source is read in chunks, so it doesn't need to be buffered itself.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            buf: Buffer::new(),
+        }
+    }
+
+    /// Enables or disables trimming whitespace in [`Text`](crate::Text) events.
+    ///
+    /// Defaults to enabled (`true`).
+    pub fn trim_whitespace(&mut self, trim: bool) -> &mut Self {
+        self.buf.settings = self.buf.settings.trim_whitespace(trim);
+        self
+    }
+
+    /// Sets how events are read, with every setting of a [`Reader`] at once.
+    ///
+    /// State is kept from one event to the next like a [`Reader`] does,
+    /// so structure checks and limits apply to the whole source.\
+    /// This applies to the events read afterwards.
+    ///
+    /// Defaults to the default settings of a [`ReaderBuilder`].
+    pub fn settings(&mut self, settings: ReaderBuilder) -> &mut Self {
+        self.buf.settings = settings;
+        self
+    }
+
+    /// Gets the underlying source.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Reads the next event, borrowing it from the internal buffer.
+    ///
+    /// I/O errors are returned as [`Error::Io`].
+    /// After any error, reading is over and `None` is returned from then on.
+    pub fn read_event(&mut self) -> Option<Result<Event<'_, [u8]>, Error>> {
+        loop {
+            // SAFETY: The buffer stays borrowed only when the event is returned,
+            // which the borrow checker can't tell apart from carrying on yet.
+            let buf = unsafe { &mut *(&mut self.buf as *mut Buffer) };
+            match buf.next_event() {
+                Ok(Found::Event(event)) => return Some(Ok(event)),
+                Ok(Found::Done) => return None,
+                Ok(Found::More) => (),
+                Err(error) => return Some(Err(error)),
+            }
+            let len = self.buf.reserve();
+            let read = loop {
+                match self.inner.read(self.buf.spare(len)) {
+                    Ok(read) => break Ok(read),
+                    Err(error) if error.kind() == io::ErrorKind::Interrupted => (),
+                    Err(error) => break Err(error),
+                }
+            };
+            if let Err(error) = self.buf.filled(len, read) {
+                return Some(Err(error));
+            }
+        }
+    }
+}
+
+impl FeedReader {
+    /// Constructs a new [`FeedReader`] without any data yet.
+    pub const fn new() -> Self {
+        Self { buf: Buffer::new() }
+    }
+
+    /// Enables or disables trimming whitespace in [`Text`](crate::Text) events.
+    ///
+    /// Defaults to enabled (`true`).
+    pub fn trim_whitespace(&mut self, trim: bool) -> &mut Self {
+        self.buf.settings = self.buf.settings.trim_whitespace(trim);
+        self
+    }
+
+    /// Sets how events are read, with every setting of a [`Reader`] at once.
+    ///
+    /// State is kept from one event to the next like a [`Reader`] does,
+    /// so structure checks and limits apply to the whole source.\
+    /// This applies to the events read afterwards.
+    ///
+    /// Defaults to the default settings of a [`ReaderBuilder`].
+    pub fn settings(&mut self, settings: ReaderBuilder) -> &mut Self {
+        self.buf.settings = settings;
+        self
+    }
+
+    /// Appends the next piece of the input.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called after [`FeedReader::finish`].
+    pub fn feed(&mut self, data: &[u8]) {
+        assert!(!self.buf.eof, "data fed after the end of the input");
+        self.buf.compact();
+        self.buf.buf.extend_from_slice(data);
+    }
+
+    /// Marks the end of the input, so that whatever is left gets yielded (or reported as an error).
+    pub fn finish(&mut self) {
+        self.buf.eof = true;
+    }
+
+    /// Reads the next event, borrowing it from the internal buffer.
+    ///
+    /// Yields [`Error::NeedMoreData`] if the event isn't complete yet,
+    /// and `None` once everything has been read after [`FeedReader::finish`],
+    /// or after any other error.
+    pub fn next_event(&mut self) -> Option<Result<Event<'_, [u8]>, Error>> {
+        match self.buf.next_event() {
+            Ok(Found::Event(event)) => Some(Ok(event)),
+            Ok(Found::Done) => None,
+            Ok(Found::More) => Some(Err(Error::NeedMoreData)),
+            Err(error) => Some(Err(error)),
+        }
+    }
+}
+
+impl Default for FeedReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Buffer {
+    pub(crate) const fn new() -> Self {
+        Self {
+            buf: Vec::new(),
+            start: 0,
+            drained: 0,
+            eof: false,
+            failed: false,
+            settings: ReaderBuilder::new(),
+            carry: Carry {
+                depth: 0,
+                open: Vec::new(),
+                raw_text: None,
+                xml_space: Vec::new(),
+                events: 0,
+                ended: false,
+            },
+        }
+    }
+
+    /// Reads the next event if the buffer holds the whole of it, moving past it.
+    ///
+    /// Errors are only returned once, and [`Found::Done`] from then on.
+    pub(crate) fn next_event(&mut self) -> Result<Found<'_>, Error> {
+        if self.failed {
+            return Ok(Found::Done);
+        }
+        let buf = &self.buf[..];
+        let mut reader = match Reader::<[u8]>::from_parts(buf, self.start, true) {
+            Some(reader) => reader,
+            None => {
+                self.failed = true;
+                let error = io::Error::new(io::ErrorKind::InvalidData, "buffer out of sync");
+                return Err(Error::Io(error));
+            }
+        };
+        self.settings.apply(&mut reader);
+        // The carried state is cloned, as the reader may throw it away when it runs out of data.
+        reader.depth = self.carry.depth;
+        reader.open.clone_from(&self.carry.open);
+        reader.raw_text.clone_from(&self.carry.raw_text);
+        reader.xml_space.clone_from(&self.carry.xml_space);
+        reader.events = self.carry.events;
+        if self.carry.ended {
+            reader.state = ReaderState::End;
+        }
+        // Text only ends at the next `<`, and tags might just be cut off by the chunking.
+        let event = reader.next();
+        let ended = matches!(reader.state, ReaderState::End);
+        match event {
+            None if self.eof || self.carry.ended => return Ok(Found::Done),
+            None => return Ok(Found::More),
+            Some(Err(Error::UnexpectedEof | Error::UnclosedTags(_))) if !self.eof => {
+                return Ok(Found::More)
+            }
+            Some(Ok(Event::Text(_))) if !self.eof && ended => return Ok(Found::More),
+            _ => (),
+        }
+        self.start = match ended {
+            true => buf.len(),
+            false => reader.offset,
+        };
+        self.carry = Carry {
+            depth: reader.depth,
+            open: mem::take(&mut reader.open),
+            raw_text: reader.raw_text.take(),
+            xml_space: mem::take(&mut reader.xml_space),
+            events: reader.events,
+            ended: ended && !self.eof,
+        };
+        match event {
+            Some(Ok(event)) => Ok(Found::Event(event)),
+            Some(Err(error)) => {
+                self.failed = true;
+                Err(rebase(error, self.drained))
+            }
+            None => Ok(Found::Done),
+        }
+    }
+
+    /// Drops what's already been read from the buffer, once that's most of it.
+    fn compact(&mut self) {
+        if self.start <= 1 || self.start * 2 < self.buf.len() {
+            return;
+        }
+        // Keep the names the reader still needs to check end tags against,
+        // and the byte before the start, which tells `Reader::from_parts` if it's inside a tag.
+        let mut kept = Vec::new();
+        // The name of a raw text element is also the name of the last open one, if checking structure.
+        let raw_text_open = self.carry.raw_text.is_some()
+            && self.carry.open.last().map(|x| &x.name) == self.carry.raw_text.as_ref();
+        for element in &mut self.carry.open {
+            let start = kept.len();
+            kept.extend_from_slice(&self.buf[element.start..element.name.end]);
+            element.name = start + element.name.start - element.start..kept.len();
+            element.start = start;
+        }
+        if let (true, Some(element)) = (raw_text_open, self.carry.open.last()) {
+            self.carry.raw_text = Some(element.name.clone());
+        } else if let Some(name) = &mut self.carry.raw_text {
+            let start = kept.len();
+            kept.extend_from_slice(&self.buf[name.clone()]);
+            *name = start..kept.len();
+        }
+        self.drained += self.start - 1 - kept.len();
+        let start = mem::replace(&mut self.start, kept.len() + 1);
+        self.buf.splice(..start - 1, kept);
+    }
+
+    /// Drops what's already been read from the buffer, and makes room for the next chunk
+    /// of the source, returning where it goes.
+    pub(crate) fn reserve(&mut self) -> usize {
+        self.compact();
+        // Grow with the pending event, so rescanning a huge one stays linear overall.
+        let len = self.buf.len();
+        self.buf.resize(len + CHUNK_SIZE.max(len - self.start), 0);
+        len
+    }
+
+    /// Gets the room made by [`Buffer::reserve`] to read into.
+    pub(crate) fn spare(&mut self, len: usize) -> &mut [u8] {
+        &mut self.buf[len..]
+    }
+
+    /// Keeps what was read into the room made by [`Buffer::reserve`].
+    pub(crate) fn filled(&mut self, len: usize, read: io::Result<usize>) -> Result<(), Error> {
+        match read {
+            Ok(read) => {
+                self.buf.truncate(len + read);
+                self.eof = read == 0;
+                Ok(())
+            }
+            Err(error) => {
+                self.buf.truncate(len);
+                self.failed = true;
+                Err(Error::Io(error))
+            }
+        }
+    }
+}
+
+/// Makes the offset of an error from the reader relative to the start of the source
+/// rather than the buffer.
+fn rebase(error: Error, drained: usize) -> Error {
+    match error {
+        Error::InvalidName(offset) => Error::InvalidName(drained + offset),
+        Error::LimitExceeded { limit, offset } => Error::LimitExceeded {
+            limit,
+            offset: drained + offset,
+        },
+        Error::UnexpectedEndTag { name, offset } => Error::UnexpectedEndTag {
+            name,
+            offset: drained + offset,
+        },
+        Error::MismatchedEnd {
+            expected,
+            found,
+            offset,
+        } => Error::MismatchedEnd {
+            expected,
+            found,
+            offset: drained + offset,
+        },
+        error => error,
+    }
+}
+
+impl<R: Read> Iterator for IoReader<R> {
+    type Item = Result<OwnedEvent<[u8]>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_event().map(|event| event.map(Event::into_owned))
+    }
+}
+
+impl<R: Read> FusedIterator for IoReader<R> {}
@@ -32,10 +32,28 @@
 //! If you're looking for the opposite, a standards-compliant low-level XML parser,
 //! I highly recommend [`xmlparser`](https://crates.io/crates/xmlparser).
 //!
+//! # `no_std`
+//! Disabling the default `std` feature makes the crate `no_std`, though it still needs `alloc`.
+//! The [`Reader`] and everything borrowing from the input keep working the same,
+//! while [`IoReader`], [`Writer`] and the `serde` integration are left out.
+//! Without `std`, `memchr` also can't detect vector instructions at runtime,
+//! so it only uses what the target is compiled for.
+//!
+//! There's no `alloc`-free core: the reader keeps its open elements and `xml:space` scopes in
+//! a [`Vec`], and errors such as [`Error::UnclosedTags`] carry owned names.
+//!
+//! # `safe`
+//! The reader slices the input without bounds checks wherever it has already made sure
+//! the indices are in range. Enabling the `safe` feature checks them anyway,
+//! so that a bug there panics rather than reading out of bounds, at a small cost in speed.
+//!
 //! # Note
 //! This is rather early in development,
-//! and bangs (!) and processing instructions (?) aren't supported yet.\
-//! So probably don't use this *at all* until it hits 1.0.
+//! so probably don't use this *at all* until it hits 1.0.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
 #[cfg(feature = "use-memchr")]
 use memchr::memchr;
@@ -43,22 +61,95 @@ use memchr::memchr;
 fn memchr(needle: u8, haystack: &[u8]) -> Option<usize> {
     haystack.iter().position(|&x| x == needle)
 }
+// Quotes are usually right after `=`, where a plain loop beats setting up a vectorized search.
+#[cfg(feature = "simd")]
+use memchr::memchr2;
+#[cfg(not(feature = "simd"))]
+fn memchr2(needle1: u8, needle2: u8, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|&x| x == needle1 || x == needle2)
+}
 
-use std::mem;
+use alloc::{
+    borrow::{Cow, ToOwned},
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use core::{
+    iter::FusedIterator,
+    marker::PhantomData,
+    mem,
+    num::NonZeroU32,
+    ops::Range,
+    slice, str,
+    sync::atomic::{AtomicBool, Ordering},
+};
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+#[cfg(feature = "async")]
+mod async_io;
+mod attributes;
+mod builder;
+mod chunks;
+#[cfg(feature = "serde")]
+pub mod de;
+mod decl;
+pub mod decode;
+pub mod diff;
+pub mod e4x;
+pub mod encoding;
+pub mod escape;
+pub mod fmt;
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+mod intern;
+#[cfg(feature = "std")]
+mod io;
+mod namespace;
+mod owned;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+mod path;
+pub mod policy;
+pub mod rewrite;
+#[cfg(feature = "serde")]
+pub mod ser;
+#[cfg(feature = "stats")]
+mod stats;
+pub mod tree;
+mod validate;
+#[cfg(feature = "std")]
+mod writer;
+pub mod xpath;
+#[cfg(feature = "async")]
+pub use async_io::AsyncReader;
+pub use attributes::{Attributes, DuplicatePolicy};
+pub use builder::ReaderBuilder;
+pub use chunks::ChunkedReader;
+pub use decl::XmlDecl;
+use decode::{eq_decoded, next_decode_chunk, Chunk, EntityTable};
+pub use intern::Interner;
+#[cfg(feature = "std")]
+pub use io::{FeedReader, IoReader};
+pub use namespace::{NamespaceResolver, XML_NAMESPACE};
+pub use owned::{OwnedAttribute, OwnedEvent, OwnedTag, OwnedText};
+pub use path::PathMatches;
+#[cfg(feature = "stats")]
+pub use stats::Stats;
+pub use validate::validate;
+#[cfg(feature = "std")]
+pub use writer::Writer;
 
 static IS_VALID_NAME_START: [bool; 256] = lut_name_start_chars();
 const fn lut_name_start_chars() -> [bool; 256] {
     let mut arr = [true; 256];
     let mut i = 0;
     while i < 256 {
-        arr[i] = match i as u8 {
-            0x00..=b' ' => false,
-            b'!'..=b'9' => false,
-            b':'..=b'@' => false,
-            b'['..=b'`' => false,
-            b'{'..=0x7F => false,
-            _ => true,
-        };
+        arr[i] = !matches!(
+            i as u8,
+            0x00..=b' ' | b'!'..=b'9' | b':'..=b'@' | b'['..=b'`' | b'{'..=0x7F
+        );
         i += 1;
     }
     arr
@@ -74,21 +165,227 @@ fn is_valid_tag_name(name: &[u8]) -> bool {
 
 // SAFETY: We only put trusted indices returned by the STL / `memchr` (crate) in here.
 // These only exist as mini functions to improve code readability.
+#[cfg(not(feature = "safe"))]
 #[inline]
 fn sl(s: &[u8], x: usize) -> &[u8] {
+    debug_assert!(x <= s.len(), "slicing {} bytes from {}", s.len(), x);
     unsafe { s.get_unchecked(x..) }
 }
+#[cfg(not(feature = "safe"))]
 #[inline]
 fn sl_to(s: &[u8], x: usize) -> &[u8] {
+    debug_assert!(x <= s.len(), "slicing {} bytes up to {}", s.len(), x);
     unsafe { s.get_unchecked(..x) }
 }
+// With `safe`, a bug in the above would panic instead.
+#[cfg(feature = "safe")]
+#[inline]
+fn sl(s: &[u8], x: usize) -> &[u8] {
+    &s[x..]
+}
+#[cfg(feature = "safe")]
+#[inline]
+fn sl_to(s: &[u8], x: usize) -> &[u8] {
+    &s[..x]
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    let (&first, rest) = needle.split_first()?;
+    let mut offset = 0;
+    loop {
+        offset += memchr(first, sl(haystack, offset))?;
+        if sl(haystack, offset + 1).starts_with(rest) {
+            return Some(offset);
+        }
+        offset += 1;
+    }
+}
+
+/// Finds the `<` of the first `</name` end tag in `haystack`, comparing names case-insensitively.
+fn find_end_tag(haystack: &[u8], name: &[u8]) -> Option<usize> {
+    let mut offset = 0;
+    loop {
+        offset += find(sl(haystack, offset), b"</")?;
+        let rest = sl(haystack, offset + 2);
+        if rest.len() > name.len()
+            && sl_to(rest, name.len()).eq_ignore_ascii_case(name)
+            && matches!(rest[name.len()], 0..=b' ' | b'>' | b'/')
+        {
+            return Some(offset);
+        }
+        offset += 2;
+    }
+}
+
+/// Splits a qualified name like `prefix:local` on the first `:`.
+fn split_qname(name: &[u8]) -> (Option<&[u8]>, &[u8]) {
+    match memchr(b':', name) {
+        Some(colon) => (Some(sl_to(name, colon)), sl(name, colon + 1)),
+        None => (None, name),
+    }
+}
 
 fn trim_whitespace(text: &[u8]) -> &[u8] {
     text.iter()
         .position(|&ch| ch > b' ')
         .and_then(|l| text.iter().rposition(|&ch| ch > b' ').map(|r| (l, r)))
         .and_then(|(l, r)| text.get(l..=r))
-        .unwrap_or(b"")
+        // Empty text stays in place so its offset in the input can be taken.
+        .unwrap_or_else(|| sl(text, text.len()))
+}
+
+/// Checks whether the end of a tag's content is inside an unterminated attribute value,
+/// following the same rules as [`AttributeIter`] for where values start and end.
+fn ends_in_value(content: &[u8]) -> bool {
+    let mut rest = content;
+    loop {
+        let sep = match memchr(b'=', rest) {
+            Some(sep) => sep,
+            None => return false,
+        };
+        rest = sl(rest, sep + 1);
+        let (open, quote_char) = match memchr2(b'"', b'\'', rest) {
+            Some(open) => (open, rest[open]),
+            None => return false,
+        };
+        rest = sl(rest, open + 1);
+        match memchr(quote_char, rest) {
+            Some(close) => rest = sl(rest, close + 1),
+            None => return true,
+        }
+    }
+}
+
+fn skip_bom(text: &[u8]) -> &[u8] {
+    let start = text.iter().position(|&ch| ch > b' ').unwrap_or(0);
+    match sl(text, start) {
+        [0xEF, 0xBB, 0xBF, rest @ ..] => rest,
+        _ => text,
+    }
+}
+
+/// Checks in debug builds that slices about to be handed out as `str` are valid UTF-8.
+///
+/// The `str` reader and attribute iterator are transmutes over the byte ones, which is only
+/// sound because those split the input exclusively around ASCII bytes, never inside a character.
+#[inline]
+fn debug_assert_utf8(slices: &[&[u8]]) {
+    if cfg!(debug_assertions) {
+        for slice in slices {
+            assert!(
+                str::from_utf8(slice).is_ok(),
+                "slice splits a UTF-8 sequence"
+            );
+        }
+    }
+}
+
+/// Checks the slices of an event with [`debug_assert_utf8`].
+#[inline]
+fn debug_assert_event_utf8(event: &Result<Event<'_, [u8]>, Error>) {
+    match event {
+        Ok(
+            Event::Start(tag)
+            | Event::End(tag)
+            | Event::Empty(tag)
+            | Event::ProcessingInstruction(tag),
+        ) => debug_assert_utf8(&[tag.name, tag.content, tag.raw()]),
+        Ok(
+            Event::Text(text) | Event::Comment(text) | Event::CData(text) | Event::DocType(text),
+        ) => debug_assert_utf8(&[text.content, text.raw()]),
+        Err(_) => (),
+    }
+}
+
+fn normalize_eol(text: &[u8]) -> Cow<'_, [u8]> {
+    let mut cr = match memchr(b'\r', text) {
+        Some(cr) => cr,
+        None => return Cow::Borrowed(text),
+    };
+    let mut out = Vec::with_capacity(text.len());
+    let mut rest = text;
+    loop {
+        out.extend_from_slice(sl_to(rest, cr));
+        out.push(b'\n');
+        // `\r\n` collapses into the single `\n` pushed above.
+        rest = match rest.get(cr + 1) {
+            Some(b'\n') => sl(rest, cr + 2),
+            _ => sl(rest, cr + 1),
+        };
+        cr = match memchr(b'\r', rest) {
+            Some(cr) => cr,
+            None => break,
+        };
+    }
+    out.extend_from_slice(rest);
+    Cow::Owned(out)
+}
+
+/// Collapses every run of whitespace into a single space, and trims it off both ends.
+fn collapse_whitespace(text: &[u8]) -> Cow<'_, [u8]> {
+    let text = trim_whitespace(text);
+    let next = text.iter().skip(1).chain(Some(&b'_'));
+    if !text
+        .iter()
+        .zip(next)
+        .any(|(&ch, &next)| ch <= b' ' && (ch != b' ' || next <= b' '))
+    {
+        return Cow::Borrowed(text);
+    }
+    let mut out = Vec::with_capacity(text.len());
+    for &ch in text {
+        if ch > b' ' {
+            out.push(ch);
+        } else if out.last() != Some(&b' ') {
+            out.push(b' ');
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Gets the name of the root element, without parsing any further than its start tag.
+///
+/// The prolog (XML declaration, comments, DOCTYPE, whitespace) is skipped over.\
+/// Returns `None` if the input has no elements, or the first tag is broken or an end tag.
+///
+/// # Example
+/// ```rust
+/// let xml = "<?xml version=\"1.0\"?>\n<!-- config -->\n<Config version=\"2\"/>";
+/// assert_eq!(lazyxml::root_element_name(xml), Some("Config"));
+/// ```
+pub fn root_element_name(xml: &str) -> Option<&str> {
+    for event in Reader::from_str(xml) {
+        match event.ok()? {
+            Event::Start(tag) | Event::Empty(tag) => return Some(tag.name()),
+            Event::End(_) => return None,
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Parses the whole document once, collecting the start tag spans of every element by name.
+///
+/// Each span covers an element's start tag (or empty tag) from `<` to `>` inclusive,
+/// in document order. Parsing stops at the first error.
+///
+/// # Example
+/// ```rust
+/// let xml = "<List><Item/><Other/><Item id=\"2\"/></List>";
+/// let index = lazyxml::index_by_name(xml).unwrap();
+/// assert_eq!(index["Item"], [6..13, 21..35]);
+/// ```
+#[cfg(feature = "std")]
+pub fn index_by_name(xml: &str) -> Result<HashMap<&str, Vec<Range<usize>>>, Error> {
+    let mut index: HashMap<&str, Vec<Range<usize>>> = HashMap::new();
+    let mut reader = Reader::from_str(xml);
+    while let Some(event) = reader.next() {
+        if let Event::Start(tag) | Event::Empty(tag) = event? {
+            let span = reader.event_start..reader.event_end;
+            index.entry(tag.name()).or_default().push(span);
+        }
+    }
+    Ok(index)
 }
 
 /// Generic XML parsing errors emitted by [`AttributeIter`] and [`Reader`].
@@ -112,6 +409,266 @@ pub enum Error {
     ///
     /// Examples: `<`, `<Name`, `<Name a`, `<Name a=`, `<Name a="1`, `<Name a="1"`.
     UnexpectedEof,
+
+    /// Parsing was cancelled through the flag given to [`Reader::with_cancel`].
+    Cancelled,
+
+    /// Attribute at (offset) has a value longer than the configured limit.
+    ///
+    /// Only emitted by [`AttributeIter`] when [`Reader::max_attribute_value_len`] is set.\
+    /// Offset is relative to the [`Tag`]'s content chunk if created with [`Tag::attributes`].
+    ValueTooLong { offset: usize },
+
+    /// Something at (offset) went over one of the configured limits in [`Limit`].
+    ///
+    /// Offset is relative to the [`Tag`]'s content chunk for [`Limit::Attributes`],
+    /// if created with [`Tag::attributes`].
+    LimitExceeded { limit: Limit, offset: usize },
+
+    /// End tag at (offset) doesn't close any element.
+    ///
+    /// Only emitted when [`Reader::check_structure`] is enabled.
+    ///
+    /// Examples: `</Name>`, `<Name></Name></Name>`.
+    UnexpectedEndTag { name: String, offset: usize },
+
+    /// End tag at (offset) named `found` doesn't match the innermost open element `expected`.
+    ///
+    /// Only emitted when [`Reader::check_structure`] is enabled.
+    ///
+    /// Examples: `<a></b>`, `<a><b></a>`.
+    MismatchedEnd {
+        expected: String,
+        found: String,
+        offset: usize,
+    },
+
+    /// Elements were still open at the end of the input, outermost first.
+    ///
+    /// Only emitted when [`Reader::check_structure`] is enabled.
+    ///
+    /// Examples: `<a>`, `<a><b></b>`.
+    UnclosedTags(Vec<String>),
+
+    /// Entity reference at (offset) is unknown or malformed.
+    ///
+    /// Only emitted by the strict functions in [`decode`].
+    ///
+    /// Examples: `&nbsp;`, `&#;`, `&#xZZ;`, `& `.
+    UnknownEntity(usize),
+
+    /// Attribute at (offset) has the same key as an earlier one in its tag.
+    ///
+    /// Only emitted when collecting [`Attributes`] with [`DuplicatePolicy::Error`].
+    ///
+    /// Examples: `<Name a="1" a="2">`.
+    DuplicateAttribute { offset: usize },
+
+    /// The next event isn't complete yet, so more input has to be fed first.
+    ///
+    /// Only emitted by [`FeedReader`].
+    NeedMoreData,
+
+    /// Token at (offset) doesn't end before the end of its chunk.
+    ///
+    /// Only emitted by [`ChunkedReader`], with an offset relative to the whole input.
+    SplitToken { offset: usize },
+
+    /// Reading from the source of an [`IoReader`] failed.
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+}
+
+impl Error {
+    /// Gets the byte offset the error occurred at, if it has one.
+    ///
+    /// This is relative to the input of the [`Reader`], except for [`Error::InvalidAttribute`],
+    /// [`Error::ValueTooLong`] and [`Limit::Attributes`], which are relative to the content of their [`Tag`].\
+    /// Use [`Reader::position_of`] to turn it into a line and column.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Error::InvalidName(offset)
+            | Error::InvalidAttribute(offset)
+            | Error::UnknownEntity(offset)
+            | Error::ValueTooLong { offset }
+            | Error::LimitExceeded { offset, .. }
+            | Error::DuplicateAttribute { offset }
+            | Error::SplitToken { offset }
+            | Error::UnexpectedEndTag { offset, .. }
+            | Error::MismatchedEnd { offset, .. } => Some(*offset),
+            Error::UnexpectedEof
+            | Error::NeedMoreData
+            | Error::Cancelled
+            | Error::UnclosedTags(_) => None,
+            #[cfg(feature = "std")]
+            Error::Io(_) => None,
+        }
+    }
+
+    /// Gets the broad category of the error, for handling errors without matching every variant.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::InvalidName(_)
+            | Error::InvalidAttribute(_)
+            | Error::UnexpectedEof
+            | Error::NeedMoreData
+            | Error::DuplicateAttribute { .. }
+            | Error::SplitToken { .. } => ErrorKind::Syntax,
+            Error::UnexpectedEndTag { .. }
+            | Error::MismatchedEnd { .. }
+            | Error::UnclosedTags(_) => ErrorKind::Structure,
+            Error::ValueTooLong { .. } | Error::LimitExceeded { .. } => ErrorKind::Limit,
+            Error::UnknownEntity(_) => ErrorKind::Entity,
+            Error::Cancelled => ErrorKind::Cancelled,
+            #[cfg(feature = "std")]
+            Error::Io(_) => ErrorKind::Io,
+        }
+    }
+
+    /// Gets up to [`Error::SNIPPET_LEN`] bytes of `input` starting at the error's offset,
+    /// to show what was found there.
+    ///
+    /// `input` has to be what the offset is relative to, see [`Error::offset`].
+    /// The snippet is cut short at the end of the line, and is `None` without an offset.
+    pub fn snippet<'a>(&self, input: &'a [u8]) -> Option<&'a [u8]> {
+        let rest = input.get(self.offset()?..)?;
+        let rest = &rest[..rest.len().min(Self::SNIPPET_LEN)];
+        Some(match memchr(b'\n', rest) {
+            Some(eol) => &rest[..eol],
+            None => rest,
+        })
+    }
+
+    /// Maximum length of [`Error::snippet`] in bytes.
+    pub const SNIPPET_LEN: usize = 40;
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::InvalidName(offset) => write!(f, "invalid tag name at byte {}", offset),
+            Error::InvalidAttribute(offset) => write!(f, "malformed attribute at byte {}", offset),
+            Error::UnexpectedEof => f.write_str("unexpected end of input"),
+            Error::NeedMoreData => f.write_str("more input is needed"),
+            Error::Cancelled => f.write_str("parsing was cancelled"),
+            Error::ValueTooLong { offset } => {
+                write!(f, "attribute value at byte {} is too long", offset)
+            }
+            Error::LimitExceeded { limit, offset } => match limit {
+                Limit::TagLen => write!(f, "tag at byte {} is too long", offset),
+                Limit::Depth => write!(f, "element at byte {} is nested too deep", offset),
+                Limit::TextLen => write!(f, "text at byte {} is too long", offset),
+                Limit::Attributes => write!(f, "tag has too many attributes at byte {}", offset),
+                Limit::Events => write!(f, "too many events by byte {}", offset),
+            },
+            Error::UnexpectedEndTag { name, offset } => {
+                write!(f, "end tag `</{}>` at byte {} closes nothing", name, offset)
+            }
+            Error::MismatchedEnd {
+                expected,
+                found,
+                offset,
+            } => write!(
+                f,
+                "expected `</{}>` but found `</{}>` at byte {}",
+                expected, found, offset
+            ),
+            Error::UnclosedTags(names) => {
+                write!(f, "unclosed elements at end of input: {}", names.join(", "))
+            }
+            Error::UnknownEntity(offset) => write!(f, "unknown entity at byte {}", offset),
+            Error::DuplicateAttribute { offset } => {
+                write!(f, "duplicate attribute at byte {}", offset)
+            }
+            Error::SplitToken { offset } => {
+                write!(f, "token at byte {} crosses the end of its chunk", offset)
+            }
+            #[cfg(feature = "std")]
+            Error::Io(error) => write!(f, "I/O error: {}", error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Which limit was exceeded, as given by [`Error::LimitExceeded`].
+///
+/// The length of attribute values has an error of its own, see [`ErrorKind::Limit`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Limit {
+    /// A tag was longer than [`Reader::max_tag_len`].
+    TagLen,
+    /// A start tag opened an element deeper than [`Reader::max_depth`].
+    Depth,
+    /// Text was longer than [`Reader::max_text_len`].
+    TextLen,
+    /// A tag had more attributes than [`Reader::max_attributes`].
+    Attributes,
+    /// The document had more events than [`Reader::max_events`].
+    Events,
+}
+
+/// Broad category of an [`Error`], as given by [`Error::kind`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The input isn't even lenient XML, such as a tag with an invalid name.
+    Syntax,
+    /// Tags aren't nested properly, only when [`Reader::check_structure`] is enabled.
+    Structure,
+    /// A configured limit was exceeded.
+    Limit,
+    /// An entity couldn't be decoded.
+    Entity,
+    /// Parsing was cancelled on purpose.
+    Cancelled,
+    /// Reading the input failed.
+    Io,
+}
+
+/// Error from parsing an [`Attribute`] value or [`Text`] into another type,
+/// as with [`Attribute::parse`] and [`Text::parse`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValueError<E> {
+    /// The value isn't valid UTF-8 once decoded, so it can't be parsed as a string.
+    NotUtf8,
+    /// The value didn't parse, with the error of the [`FromStr`](str::FromStr) implementation.
+    Invalid(E),
+}
+
+impl<E: core::fmt::Display> core::fmt::Display for ValueError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ValueError::NotUtf8 => f.write_str("value is not valid UTF-8"),
+            ValueError::Invalid(error) => write!(f, "invalid value: {}", error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: std::error::Error + 'static> std::error::Error for ValueError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ValueError::NotUtf8 => None,
+            ValueError::Invalid(error) => Some(error),
+        }
+    }
+}
+
+/// Parses raw text into `F`, decoding its entities and trimming surrounding whitespace first.
+fn parse_value<F: str::FromStr>(raw: &[u8]) -> Result<F, ValueError<F::Err>> {
+    let value = decode::decode_text(raw);
+    let value = str::from_utf8(trim_whitespace(&value)).map_err(|_| ValueError::NotUtf8)?;
+    value.parse().map_err(ValueError::Invalid)
 }
 
 /// Processed XML data, produced by a [`Reader`].
@@ -129,6 +686,25 @@ pub enum Event<'xml, T: ?Sized> {
     /// If the text is empty after trimming,
     /// it is not emitted as that occurs between all non-adjacent tags.
     Text(Text<'xml, T>),
+    /// XML `<!-- Comment -->`.
+    ///
+    /// The text is everything between `<!--` and `-->`, and is never trimmed.
+    Comment(Text<'xml, T>),
+    /// XML `<?Target content?>` processing instruction, such as the `<?xml ...?>` declaration.
+    ///
+    /// The tag's name is the target, and its content is everything after it up to `?>`,
+    /// so pseudo-attributes like `version` can be read with [`Tag::attributes`].
+    ProcessingInstruction(Tag<'xml, T>),
+    /// XML `<![CDATA[ ... ]]>` section.
+    ///
+    /// The text is everything between `<![CDATA[` and `]]>`, untouched: it's never trimmed,
+    /// and there are no entities to decode inside of it.
+    CData(Text<'xml, T>),
+    /// XML `<!DOCTYPE ...>` declaration.
+    ///
+    /// The text is everything between `<!DOCTYPE` and the closing `>`, untouched,
+    /// including the internal subset in `[` brackets `]` if there is one.
+    DocType(Text<'xml, T>),
 }
 
 /// Represents an XML tag.
@@ -136,39 +712,286 @@ pub enum Event<'xml, T: ?Sized> {
 pub struct Tag<'xml, T: ?Sized> {
     content: &'xml T,
     name: &'xml T,
+    options: AttributeOptions,
+    start: usize,
+    end: usize,
+    content_start: usize,
+    /// Symbol of the name plus one, looked up once by the reader if it has an [`Interner`].
+    symbol: Option<NonZeroU32>,
 }
 
 /// Iterator over XML attributes.
 pub struct AttributeIter<'xml, T: ?Sized> {
     content: &'xml T,
     offset: usize,
+    options: AttributeOptions,
+    /// Offset of the content in the input, for attribute spans.
+    base: usize,
+    /// Number of attributes yielded so far.
+    count: usize,
+}
+
+/// Settings handed down from the [`Reader`] to the [`AttributeIter`] of every [`Tag`].
+///
+/// Limits are `usize::MAX` when unset, which keeps every [`Tag`] smaller than with `Option`s.
+#[derive(Clone, Copy, Debug)]
+struct AttributeOptions {
+    strict: bool,
+    max_value_len: usize,
+    max_count: usize,
+    recover: bool,
+    unquoted: bool,
+    bare: bool,
+}
+
+impl AttributeOptions {
+    const DEFAULT: Self = Self {
+        strict: false,
+        max_value_len: usize::MAX,
+        max_count: usize::MAX,
+        recover: false,
+        unquoted: false,
+        bare: false,
+    };
 }
 
 /// Represents an XML attribute.
 pub struct Attribute<'xml, T: ?Sized> {
     key: &'xml T,
     value: &'xml T,
+    key_start: usize,
+    value_start: usize,
+    quote: Option<u8>,
+}
+
+/// Iterator over the chunks of text as its entities get decoded.
+///
+/// Created with [`Text::decode_chunks`].
+pub struct DecodeChunks<'xml, T: ?Sized> {
+    rest: &'xml [u8],
+    marker: PhantomData<&'xml T>,
 }
 
 /// Represents arbitrary text inside or outside of elements.
 #[derive(Debug)]
 pub struct Text<'xml, T: ?Sized> {
     content: &'xml T,
+    start: usize,
+    /// Bytes of the raw text before and after the content, see [`Text::trim_info`].
+    leading: usize,
+    trailing: usize,
 }
 
 /// Low level XML reader implemented as an [`Iterator`] producing events.
 ///
 /// See [`Event`] for more information.
+///
+/// # Errors
+/// What the reader does after yielding an error depends on the error:
+///
+/// - [`Error::InvalidName`], [`Error::UnexpectedEof`] and [`Limit::TagLen`] come from
+///   a tag that can't be parsed. There's no telling where to carry on,
+///   so the reader ends and only yields `None` from then on, like after the last event.\
+///   With [`Reader::recover`] enabled, these tags are yielded as text instead.
+/// - [`Limit::Depth`] ends the reader too, regardless of [`Reader::recover`].
+/// - [`Error::UnexpectedEndTag`] and [`Error::MismatchedEnd`] come from an end tag that
+///   doesn't fit, which the reader has moved past as if it wasn't there,
+///   so the next call carries on after it.
+/// - [`Error::UnclosedTags`] and [`Error::Cancelled`] are always the last item.
+///
+/// Attribute errors are reported by [`AttributeIter`] instead, which ends after any error.
 pub struct Reader<'xml, T: ?Sized> {
     // State
     state: ReaderState,
     source: &'xml T,
     offset: usize,
+    event_start: usize,
+    event_end: usize,
+    depth: usize,
+    open: Vec<OpenElement>,
+    match_start: Option<usize>,
+    /// Span of the name of the raw text element that was just started, in the HTML dialect.
+    raw_text: Option<Range<usize>>,
+    /// Depths at which `xml:space` was set, and whether it was set to `preserve`.
+    xml_space: Vec<(usize, bool)>,
+    /// Number of events yielded so far, for [`Reader::max_events`].
+    events: usize,
+    /// Whether the reader was ended by an error, for [`Reader::is_finished`].
+    failed: bool,
 
     // Settings
     trim: bool,
+    skip_bom: bool,
+    recover: bool,
+    check_structure: bool,
+    stop_after_root: bool,
+    comments: bool,
+    empty_text: bool,
+    strict_names: bool,
+    ignore_name_case: bool,
+    honor_xml_space: bool,
+    dialect: Dialect,
+    /// Unquoted attributes, bare attributes and ignoring name case from before
+    /// selecting [`Dialect::Html`], restored when going back to [`Dialect::Xml`].
+    xml_leniency: (bool, bool, bool),
+    max_tag_len: Option<usize>,
+    max_depth: Option<usize>,
+    max_text_len: Option<usize>,
+    max_events: Option<usize>,
+    attribute_options: AttributeOptions,
+    cancel: Option<Arc<AtomicBool>>,
+    interner: Option<&'xml Interner>,
+    #[cfg(feature = "stats")]
+    stats: Stats,
+}
+
+/// Handler receiving events from [`Reader::drive`], as an alternative to matching on [`Event`].
+///
+/// Every method defaults to doing nothing, so only the relevant ones need implementing.
+pub trait EventSink<'xml, T: ?Sized> {
+    /// Called for every [`Event::Start`].
+    fn start(&mut self, _tag: Tag<'xml, T>) {}
+
+    /// Called for every [`Event::End`].
+    fn end(&mut self, _tag: Tag<'xml, T>) {}
+
+    /// Called for every [`Event::Empty`].
+    fn empty(&mut self, _tag: Tag<'xml, T>) {}
+
+    /// Called for every [`Event::Text`].
+    fn text(&mut self, _text: Text<'xml, T>) {}
+
+    /// Called for every [`Event::Comment`].
+    fn comment(&mut self, _text: Text<'xml, T>) {}
+
+    /// Called for every [`Event::ProcessingInstruction`].
+    fn processing_instruction(&mut self, _tag: Tag<'xml, T>) {}
+
+    /// Called for every [`Event::CData`].
+    fn cdata(&mut self, _text: Text<'xml, T>) {}
+
+    /// Called for every [`Event::DocType`].
+    fn doctype(&mut self, _text: Text<'xml, T>) {}
+}
+
+/// Iterator pairing each event of a [`Reader`] with the byte offset it starts at.
+///
+/// Created with [`Reader::indexed`].
+pub struct Indexed<'xml, T: ?Sized> {
+    reader: Reader<'xml, T>,
+}
+
+/// Iterator over the direct children of an element, created with [`Reader::children`].
+///
+/// Yields every event directly inside the element except for end tags,
+/// and ends once the element's own end tag has been consumed.\
+/// Everything inside a child element is skipped, unless [`ChildIter::children`] is used
+/// right after its [`Event::Start`] to descend into it.
+pub struct ChildIter<'r, 'xml, T: ?Sized> {
+    reader: &'r mut Reader<'xml, T>,
+    depth: usize,
+    done: bool,
+}
+
+/// Line and column of a byte offset in the input, as computed by [`Reader::position_of`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Position {
+    /// Line number, starting at 1.
+    pub line: usize,
+    /// Column in characters from the start of the line, starting at 1.
+    pub column: usize,
+    /// Byte offset from the start of the input.
+    pub byte: usize,
+}
+
+/// Saved position of a [`Reader`], created with [`Reader::checkpoint`].
+#[derive(Clone, Debug)]
+pub struct Checkpoint<'xml> {
+    /// Address and length of the input, to make sure it's restored into the same reader.
+    source: (usize, usize),
+    marker: PhantomData<&'xml [u8]>,
+    state: ReaderState,
+    offset: usize,
+    event_start: usize,
+    event_end: usize,
+    depth: usize,
+    open: Vec<OpenElement>,
+    match_start: Option<usize>,
+    raw_text: Option<Range<usize>>,
+    xml_space: Vec<(usize, bool)>,
+    events: usize,
+    failed: bool,
+    #[cfg(feature = "stats")]
+    stats: Stats,
+}
+
+/// How much of a [`Text`] was cut off around its content, as returned by [`Text::trim_info`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TrimInfo {
+    /// Number of bytes cut off before the content.
+    pub leading: usize,
+    /// Number of bytes cut off after the content.
+    pub trailing: usize,
+}
+
+/// What an element directly contains, as determined by [`Reader::classify_element`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContentModel {
+    /// Nothing at all, like `<Name></Name>`.
+    Empty,
+    /// Only text, like `<Name>text</Name>`.
+    Text,
+    /// Only child elements, like `<Name><Child/></Name>`.
+    Elements,
+    /// Both text and child elements, like `<Name>text<Child/></Name>`.
+    Mixed,
+}
+
+/// Flavour of markup a [`Reader`] expects, as set with [`Reader::dialect`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dialect {
+    /// Plain XML, where every element has to be closed.
+    Xml,
+    /// XHTML-ish HTML, where void elements like `<br>` don't have to be closed,
+    /// and `<script>` and `<style>` contain raw text.
+    Html,
+}
+
+impl Dialect {
+    /// Elements that never have content in HTML, and so have no end tag.
+    const VOID_ELEMENTS: &'static [&'static [u8]] = &[
+        b"area", b"base", b"br", b"col", b"embed", b"hr", b"img", b"input", b"link", b"meta",
+        b"param", b"source", b"track", b"wbr",
+    ];
+
+    /// Elements whose content is raw text in HTML, up to their end tag.
+    const RAW_TEXT_ELEMENTS: &'static [&'static [u8]] = &[b"script", b"style"];
+
+    fn is_void(self, name: &[u8]) -> bool {
+        self == Self::Html
+            && Self::VOID_ELEMENTS
+                .iter()
+                .any(|void| void.eq_ignore_ascii_case(name))
+    }
+
+    fn is_raw_text(self, name: &[u8]) -> bool {
+        self == Self::Html
+            && Self::RAW_TEXT_ELEMENTS
+                .iter()
+                .any(|raw| raw.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Element that was started but not ended yet, tracked when checking structure.
+#[derive(Clone, Debug)]
+struct OpenElement {
+    /// Offset of the `<` of the element's start tag.
+    start: usize,
+    /// Span of the element's name.
+    name: Range<usize>,
 }
 
+#[derive(Clone, Copy, Debug)]
 enum ReaderState {
     /// The reader isn't particularly on anything. It's looking for text or tags.
     Searching,
@@ -182,7 +1005,15 @@ enum ReaderState {
 
 impl<'xml, T: ?Sized> Tag<'xml, T> {
     pub(crate) const fn new(name: &'xml T, content: &'xml T) -> Self {
-        Self { content, name }
+        Self {
+            content,
+            name,
+            options: AttributeOptions::DEFAULT,
+            start: 0,
+            end: 0,
+            content_start: 0,
+            symbol: None,
+        }
     }
 
     /// Gets the content of the tag this instance represents.
@@ -191,7 +1022,7 @@ impl<'xml, T: ?Sized> Tag<'xml, T> {
     /// up to but not including the end of the tag.\
     /// This does **not** include the `/` in `<Empty />` tags.
     pub const fn content(&self) -> &'xml T {
-        self.name
+        self.content
     }
 
     /// Gets the name of the tag this instance represents.
@@ -202,312 +1033,4932 @@ impl<'xml, T: ?Sized> Tag<'xml, T> {
     }
 
     /// Returns an iterator over the tag's attributes, if any.
+    ///
+    /// The iterator inherits the attribute settings of the [`Reader`] that produced the tag.
     pub const fn attributes(&self) -> AttributeIter<'xml, T> {
-        AttributeIter::new(self.content)
-    }
-}
-
-impl<'xml, T: ?Sized> Clone for Tag<'xml, T> {
-    fn clone(&self) -> Self {
-        Self {
+        AttributeIter {
             content: self.content,
-            name: self.name,
+            offset: 0,
+            options: self.options,
+            base: self.content_start,
+            count: 0,
         }
     }
+
+    /// Gets the byte span of the whole tag in the input, from `<` to `>` inclusive.
+    ///
+    /// This is `0..0` for tags that weren't produced by a [`Reader`].
+    pub const fn span(&self) -> Range<usize> {
+        self.start..self.end
+    }
 }
 
-impl<'xml, T: ?Sized> AttributeIter<'xml, T> {
-    /// Constructs an attribute iterator over the given content.
+impl<'xml, T: ?Sized + AsRef<[u8]>> Tag<'xml, T> {
+    /// Checks whether the tag is named `name`, comparing ASCII letters case-insensitively.
     ///
-    /// Usually instanced with [`Tag::attributes`], but can be constructed with arbitrary data.
-    pub const fn new(content: &'xml T) -> Self {
-        Self { content, offset: 0 }
+    /// # Example
+    /// ```rust
+    /// use lazyxml::{Event, Reader};
+    ///
+    /// let mut reader = Reader::from_bytes(b"<frame/>");
+    /// let Some(Ok(Event::Empty(tag))) = reader.next() else { unreachable!() };
+    /// assert!(tag.name_eq_ignore_case(b"Frame"));
+    /// ```
+    pub fn name_eq_ignore_case(&self, name: &T) -> bool {
+        self.name.as_ref().eq_ignore_ascii_case(name.as_ref())
+    }
+
+    /// Gets the symbol of the tag's name in the [`Interner`] set with [`Reader::with_interner`].
+    ///
+    /// Returns `None` if the name isn't in the dictionary, or if there's no dictionary.\
+    /// The reader looks the name up once when it reads the tag, so this is just a field access.
+    pub fn symbol(&self) -> Option<u32> {
+        self.symbol.map(|symbol| symbol.get() - 1)
     }
 }
 
-impl<'xml, T: ?Sized> Clone for AttributeIter<'xml, T> {
-    fn clone(&self) -> Self {
-        Self {
-            content: self.content,
-            offset: self.offset,
+impl<'xml> Tag<'xml, [u8]> {
+    /// Gets the whole tag as it appears in the input, from `<` to `>` inclusive.
+    ///
+    /// Unlike the name and content, nothing is left out, so it can be copied out byte for byte.\
+    /// This is just the content for tags that weren't produced by a [`Reader`].
+    pub fn raw(&self) -> &'xml [u8] {
+        if self.end == 0 {
+            return self.content;
+        }
+        // SAFETY: The reader took the content from `content_start` in the input,
+        // within the tag spanning `start..end` of it.
+        unsafe {
+            let start = self.content.as_ptr().sub(self.content_start - self.start);
+            slice::from_raw_parts(start, self.end - self.start)
         }
     }
-}
 
-impl<'xml> Iterator for AttributeIter<'xml, [u8]> {
-    type Item = Result<Attribute<'xml, [u8]>, Error>;
+    /// Gets the name of the tag as a string, if it's valid UTF-8.
+    pub fn name_str(&self) -> Result<&'xml str, str::Utf8Error> {
+        str::from_utf8(self.name)
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let mut source = sl(self.content, self.offset);
+    /// Gets the name of the tag as a string, replacing invalid UTF-8 with `U+FFFD`.
+    ///
+    /// This only allocates if the name isn't valid UTF-8.
+    pub fn name_str_lossy(&self) -> Cow<'xml, str> {
+        String::from_utf8_lossy(self.name)
+    }
 
-        // Ignore preceding whitespace (happens between attributes too, sometimes*).
-        // * The standard actually requires it but we don't care.
-        self.offset += source.iter().position(|&ch| ch > b' ')?;
-        source = sl(self.content, self.offset);
+    /// Gets the namespace prefix of the tag's name, which is everything before the first `:`.
+    pub fn prefix(&self) -> Option<&'xml [u8]> {
+        split_qname(self.name).0
+    }
 
-        // Store position for error messages on top of the attribute.
-        let initial_offset = self.offset;
+    /// Gets the local part of the tag's name, which is everything after the first `:` if any.
+    pub fn local_name(&self) -> &'xml [u8] {
+        split_qname(self.name).1
+    }
 
-        // Find `=` key/value separator
-        let sep_offset = match memchr(b'=', source) {
-            Some(sep) => sep,
-            None => return Some(Err(Error::UnexpectedEof)),
-        };
-        self.offset += sep_offset;
+    /// Gets the length of the tag's name in bytes.
+    pub const fn name_len(&self) -> usize {
+        self.name.len()
+    }
 
-        // Trim whitespace around key so a="1" and a = "1" behave the same
-        let key = trim_whitespace(sl_to(source, sep_offset));
-        if key.is_empty() {
-            return Some(Err(Error::InvalidAttribute(initial_offset)));
-        }
-        self.offset += 1; // move past `=`
+    /// Gets the length of the tag's content in bytes.
+    pub const fn content_len(&self) -> usize {
+        self.content.len()
+    }
 
-        // Find starting quote, either `'` or `"`.
-        // Memchr is not used here because 99.999% it'll be offset 0 (a="1") or 1 (a = "1").
-        source = sl(self.content, self.offset);
-        let (offset, quote_char) = match source
-            .iter()
-            .enumerate()
-            .find(|&(_ix, ch)| *ch == b'"' || *ch == b'\'')
-        {
-            Some((ix, ch)) => (ix, *ch),
-            None => return Some(Err(Error::InvalidAttribute(initial_offset))),
-        };
-        self.offset += offset + 1; // past the quote
-        source = sl(self.content, self.offset);
+    /// Finds the tag's first attribute with the given key.
+    ///
+    /// Malformed attributes end the search, as there's no telling where the next one starts.
+    pub fn find_attribute(&self, key: &[u8]) -> Option<Attribute<'xml, [u8]>> {
+        self.attributes()
+            .map_while(Result::ok)
+            .find(|attr| attr.key() == key)
+    }
 
-        // Yield key & value if available.
-        match memchr(quote_char, source) {
-            Some(end) => {
-                let value = sl_to(source, end);
-                self.offset += end + 1; // past the closing quote
-                Some(Ok(Attribute::new(key, value)))
-            }
-            None => Some(Err(Error::InvalidAttribute(initial_offset))),
-        }
+    /// Checks whether the tag has an attribute with the given key, see [`Tag::find_attribute`].
+    pub fn has_attribute(&self, key: &[u8]) -> bool {
+        self.find_attribute(key).is_some()
+    }
+
+    /// Returns an iterator over the tag's attributes whose key starts with `prefix`.
+    ///
+    /// Errors are passed through, as the key of a malformed attribute can't be checked.
+    pub fn attributes_with_prefix<'p>(
+        &self,
+        prefix: &'p [u8],
+    ) -> impl Iterator<Item = Result<Attribute<'xml, [u8]>, Error>> + 'p
+    where
+        'xml: 'p,
+    {
+        self.attributes().filter(move |attr| {
+            attr.as_ref()
+                .map_or(true, |attr| attr.key().starts_with(prefix))
+        })
     }
 }
 
-impl<'xml> Iterator for AttributeIter<'xml, str> {
-    type Item = Result<Attribute<'xml, str>, Error>;
+impl<'xml> Tag<'xml, str> {
+    /// Gets the whole tag as it appears in the input, from `<` to `>` inclusive.
+    ///
+    /// See [`Tag::<[u8]>::raw`](Tag::raw) for details.
+    pub fn raw(&self) -> &'xml str {
+        // SAFETY: Identical layout, and tags start and end on ASCII `<` and `>`.
+        unsafe {
+            let tag = mem::transmute::<&Self, &Tag<'xml, [u8]>>(self);
+            str::from_utf8_unchecked(tag.raw())
+        }
+    }
 
-    #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        // SAFETY: Identical layout, contents, and that's how the standard library does it too.
-        unsafe { mem::transmute(mem::transmute::<_, &mut AttributeIter<'xml, [u8]>>(self).next()) }
+    /// Gets the namespace prefix of the tag's name, which is everything before the first `:`.
+    pub fn prefix(&self) -> Option<&'xml str> {
+        self.name.split_once(':').map(|(prefix, _)| prefix)
     }
-}
 
-impl<'xml, T: ?Sized> Attribute<'xml, T> {
-    pub(crate) const fn new(key: &'xml T, value: &'xml T) -> Self {
-        Self { key, value }
+    /// Gets the local part of the tag's name, which is everything after the first `:` if any.
+    pub fn local_name(&self) -> &'xml str {
+        self.name
+            .split_once(':')
+            .map_or(self.name, |(_, local)| local)
     }
 
-    /// Gets the key of the attribute this instance represents.
-    pub const fn key(&self) -> &'xml T {
-        self.key
+    /// Gets the length of the tag's name in bytes.
+    pub const fn name_len(&self) -> usize {
+        self.name.len()
     }
 
-    /// Gets the raw and potentially escaped value of the attribute this instance represents.
-    pub const fn value(&self) -> &'xml T {
-        self.value
+    /// Gets the length of the tag's content in bytes.
+    pub const fn content_len(&self) -> usize {
+        self.content.len()
     }
-}
 
-impl<'xml, T: ?Sized> Clone for Attribute<'xml, T> {
-    fn clone(&self) -> Self {
-        Self {
-            key: self.key,
-            value: self.value,
-        }
+    /// Finds the tag's first attribute with the given key.
+    ///
+    /// Malformed attributes end the search, as there's no telling where the next one starts.
+    pub fn find_attribute(&self, key: &str) -> Option<Attribute<'xml, str>> {
+        self.attributes()
+            .map_while(Result::ok)
+            .find(|attr| attr.key() == key)
     }
-}
 
-impl<'xml, T: ?Sized> Text<'xml, T> {
-    #[inline]
-    pub(crate) const fn new(content: &'xml T) -> Self {
-        Self { content }
+    /// Checks whether the tag has an attribute with the given key, see [`Tag::find_attribute`].
+    pub fn has_attribute(&self, key: &str) -> bool {
+        self.find_attribute(key).is_some()
+    }
+
+    /// Returns an iterator over the tag's attributes whose key starts with `prefix`.
+    ///
+    /// Errors are passed through, as the key of a malformed attribute can't be checked.
+    pub fn attributes_with_prefix<'p>(
+        &self,
+        prefix: &'p str,
+    ) -> impl Iterator<Item = Result<Attribute<'xml, str>, Error>> + 'p
+    where
+        'xml: 'p,
+    {
+        self.attributes().filter(move |attr| {
+            attr.as_ref()
+                .map_or(true, |attr| attr.key().starts_with(prefix))
+        })
     }
 }
 
-impl<'xml, T: ?Sized> Clone for Text<'xml, T> {
+impl<'xml, T: ?Sized> Clone for Tag<'xml, T> {
     fn clone(&self) -> Self {
         Self {
             content: self.content,
+            name: self.name,
+            options: self.options,
+            start: self.start,
+            end: self.end,
+            content_start: self.content_start,
+            symbol: self.symbol,
         }
     }
 }
 
-impl<'xml, T: ?Sized> Reader<'xml, T> {
-    /// Enables or disables trimming whitespace in [`Text`] events.
-    ///
-    /// This property is dynamic and can be turned on and off while parsing.
+impl<'xml, T: ?Sized> AttributeIter<'xml, T> {
+    /// Constructs an attribute iterator over the given content.
     ///
-    /// Defaults to enabled (`true`).
-    pub fn trim_whitespace(&mut self, trim: bool) -> &mut Self {
-        self.trim = trim;
+    /// Usually instanced with [`Tag::attributes`], but can be constructed with arbitrary data.
+    pub const fn new(content: &'xml T) -> Self {
+        Self {
+            content,
+            offset: 0,
+            options: AttributeOptions::DEFAULT,
+            base: 0,
+            count: 0,
+        }
+    }
+
+    /// Enables or disables strict quoting, see [`Reader::strict_attributes`].
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.options.strict = strict;
         self
     }
 
-    /// Gets the byte offset from the start of the input.
-    pub const fn offset(&self) -> usize {
-        self.offset
+    /// Sets the maximum length of values, see [`Reader::max_attribute_value_len`].
+    pub fn max_value_len(mut self, limit: Option<usize>) -> Self {
+        self.options.max_value_len = limit.unwrap_or(usize::MAX);
+        self
     }
-}
 
-impl<'xml> Reader<'xml, [u8]> {
-    /// Constructs a new [`Reader`] from ASCII-compatible XML bytes.
-    pub const fn from_bytes(xml: &'xml [u8]) -> Reader<'xml, [u8]> {
-        Reader {
-            state: ReaderState::Searching,
-            source: xml,
-            offset: 0,
+    /// Sets the maximum number of attributes, see [`Reader::max_attributes`].
+    pub fn max_count(mut self, limit: Option<usize>) -> Self {
+        self.options.max_count = limit.unwrap_or(usize::MAX);
+        self
+    }
 
-            trim: true,
+    /// Enables or disables skipping malformed attributes, see [`Reader::recover`].
+    pub fn recover(mut self, recover: bool) -> Self {
+        self.options.recover = recover;
+        self
+    }
+
+    /// Enables or disables unquoted values, see [`Reader::allow_unquoted_attributes`].
+    pub fn unquoted(mut self, allow: bool) -> Self {
+        self.options.unquoted = allow;
+        self
+    }
+
+    /// Enables or disables keys without values, see [`Reader::allow_bare_attributes`].
+    pub fn bare(mut self, allow: bool) -> Self {
+        self.options.bare = allow;
+        self
+    }
+}
+
+impl<'xml, T: ?Sized> Clone for AttributeIter<'xml, T> {
+    fn clone(&self) -> Self {
+        Self {
+            content: self.content,
+            offset: self.offset,
+            options: self.options,
+            base: self.base,
+            count: self.count,
         }
     }
+}
 
-    fn next_search(&mut self) -> Option<Result<Event<'xml, [u8]>, Error>> {
-        let source = sl(self.source, self.offset);
-        let mut text = match memchr(b'<', source) {
-            Some(idx) => {
-                // We move 1 byte past '<' as we know that's what it is.
-                // This makes next access be worst-case &[] (safe).
-                self.offset += idx + 1;
-                self.state = ReaderState::LocatedTag;
-                sl_to(source, idx)
-            }
-            None => {
-                self.state = ReaderState::End;
-                source
+impl<'xml> AttributeIter<'xml, [u8]> {
+    fn next_attribute(&mut self) -> Option<Result<Attribute<'xml, [u8]>, Error>> {
+        let mut source = sl(self.content, self.offset);
+
+        // Ignore preceding whitespace (happens between attributes too, sometimes*).
+        // * The standard actually requires it but we don't care.
+        self.offset += source.iter().position(|&ch| ch > b' ')?;
+        source = sl(self.content, self.offset);
+
+        // Store position for error messages on top of the attribute.
+        let initial_offset = self.offset;
+
+        if self.options.bare {
+            // The key ends at whitespace or `=`, and a bare one isn't followed by `=` at all.
+            let len = source
+                .iter()
+                .position(|&ch| ch <= b' ' || ch == b'=')
+                .unwrap_or(source.len());
+            let rest = sl(source, len);
+            if len > 0 && rest.iter().find(|&&ch| ch > b' ') != Some(&b'=') {
+                let attr = Attribute {
+                    key_start: self.base + self.offset,
+                    value_start: self.base + self.offset + len,
+                    ..Attribute::new(sl_to(source, len), sl_to(rest, 0))
+                };
+                self.offset += len;
+                return Some(Ok(attr));
             }
-        };
-        if self.trim {
-            text = trim_whitespace(text);
-        }
-        if !text.is_empty() {
-            Some(Ok(Event::Text(Text::new(text))))
-        } else {
-            self.next()
         }
-    }
 
-    fn next_tag(&mut self) -> Option<Result<Event<'xml, [u8]>, Error>> {
-        let source = sl(self.source, self.offset);
-        let first_char = match source.get(0) {
-            Some(ch) => ch,
+        // Find `=` key/value separator
+        let sep_offset = match memchr(b'=', source) {
+            Some(sep) => sep,
             None => return Some(Err(Error::UnexpectedEof)),
         };
-        match first_char {
-            b'!' => todo!("bang"),
-            b'?' => todo!("pi"),
+        self.offset += sep_offset;
 
-            // Standard Tags - Start / Empty / End
-            first @ _ => {
-                let is_end_tag = *first == b'/';
-                match memchr(b'>', source) {
-                    Some(idx) => {
-                        // The inner content is the entire slice <[between]> the angle brackets.
-                        let inner = sl_to(source, idx);
+        // Trim whitespace around key so a="1" and a = "1" behave the same
+        let key = trim_whitespace(sl_to(source, sep_offset));
+        if key.is_empty() {
+            return Some(Err(Error::InvalidAttribute(initial_offset)));
+        }
+        let key_start = self.base + (key.as_ptr() as usize - self.content.as_ptr() as usize);
+        self.offset += 1; // move past `=`
 
-                        // Separate head & tail (tag name, attributes chunk).
-                        // (head, tail) of `<Name a="1"/>` is <[Name] [a="1"]/>
-                        // (head, tail) of `<Name />` is <[Name] []/>
-                        // (head, tail) of `<Name/>` is <[Name/][]>
-                        let (mut head, mut tail) = match inner.iter().position(|&ch| ch <= b' ') {
-                            Some(space) => (sl_to(inner, space), sl(inner, space + 1)),
-                            None => (inner, &[][..]),
-                        };
+        // Find starting quote, either `'` or `"`.
+        // Memchr is only used with `simd`, as 99.999% it'll be offset 0 (a="1") or 1 (a = "1").
+        source = sl(self.content, self.offset);
+        if self.options.unquoted {
+            // Without a quote, the value runs up to the next whitespace or the end of the tag.
+            let start = match source.iter().position(|&ch| ch > b' ') {
+                Some(start) => start,
+                None => return Some(Err(Error::UnexpectedEof)),
+            };
+            let value = sl(source, start);
+            if value[0] != b'"' && value[0] != b'\'' {
+                let len = value
+                    .iter()
+                    .position(|&ch| ch <= b' ')
+                    .unwrap_or(value.len());
+                if len > self.options.max_value_len {
+                    return Some(Err(Error::ValueTooLong {
+                        offset: initial_offset,
+                    }));
+                }
+                let attr = Attribute {
+                    key_start,
+                    value_start: self.base + self.offset + start,
+                    ..Attribute::new(key, sl_to(value, len))
+                };
+                self.offset += start + len;
+                return Some(Ok(attr));
+            }
+        }
+        if self.options.strict {
+            // Only whitespace may come before the quote, so point at whatever else is there.
+            match source.iter().position(|&ch| ch > b' ') {
+                Some(ix) if source[ix] == b'"' || source[ix] == b'\'' => (),
+                Some(ix) => return Some(Err(Error::InvalidAttribute(self.offset + ix))),
+                None => return Some(Err(Error::UnexpectedEof)),
+            }
+        }
+        let (offset, quote_char) = match memchr2(b'"', b'\'', source) {
+            Some(ix) => (ix, source[ix]),
+            None => return Some(Err(Error::InvalidAttribute(initial_offset))),
+        };
+        self.offset += offset + 1; // past the quote
+        source = sl(self.content, self.offset);
 
-                        // Trim `/` of `/>` in empty tags.
-                        let is_empty_tag = inner.last().map(|&ch| ch == b'/').unwrap_or(false);
-                        if is_empty_tag {
-                            // Note: Yes, this permits `</Name/>` on purpose as an end tag.
-                            // You *could* fix that with checking `is_end_tag`.
-                            if tail.is_empty() {
-                                head = sl_to(head, head.len() - 1);
-                            } else {
-                                tail = sl_to(tail, tail.len() - 1);
-                            }
-                        }
+        // Don't look for the closing quote any further than the value is allowed to go.
+        let limit = self.options.max_value_len;
+        if source.len() > limit && memchr(quote_char, sl_to(source, limit + 1)).is_none() {
+            return Some(Err(Error::ValueTooLong {
+                offset: initial_offset,
+            }));
+        }
 
-                        // Trim `/` of `</` in end tags.
-                        if is_end_tag {
-                            if head.is_empty() {
-                                // A strange case of `</>` would lead here.
-                                return Some(Err(Error::InvalidName(self.offset - 1)));
-                            } else {
-                                head = sl(head, 1);
-                            }
-                        }
+        // Yield key & value if available.
+        match memchr(quote_char, source) {
+            Some(end) => {
+                let value = sl_to(source, end);
+                let attr = Attribute {
+                    key_start,
+                    value_start: self.base + self.offset,
+                    quote: Some(quote_char),
+                    ..Attribute::new(key, value)
+                };
+                self.offset += end + 1; // past the closing quote
+                Some(Ok(attr))
+            }
+            None => Some(Err(Error::InvalidAttribute(initial_offset))),
+        }
+    }
+}
 
-                        // Yield tag if name is valid.
-                        if is_valid_tag_name(head) {
-                            self.offset += idx + 1;
-                            self.state = ReaderState::Searching;
-                            if is_end_tag {
-                                Some(Ok(Event::End(Tag::new(head, tail))))
-                            } else if is_empty_tag {
-                                Some(Ok(Event::Empty(Tag::new(head, tail))))
-                            } else {
-                                Some(Ok(Event::Start(Tag::new(head, tail))))
-                            }
-                        } else {
-                            Some(Err(Error::InvalidName(self.offset - 1)))
-                        }
+impl<'xml> Iterator for AttributeIter<'xml, [u8]> {
+    type Item = Result<Attribute<'xml, [u8]>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let attr = self.next_attribute();
+            if let Some(Ok(found)) = &attr {
+                if self.count >= self.options.max_count {
+                    // Going over the limit ends the iterator even when recovering.
+                    self.offset = self.content.len();
+                    return Some(Err(Error::LimitExceeded {
+                        limit: Limit::Attributes,
+                        offset: found.key_start - self.base,
+                    }));
+                }
+                self.count += 1;
+            }
+            match attr {
+                Some(Err(Error::InvalidAttribute(offset))) if self.options.recover => {
+                    // Skip the offending token, as it doesn't contain any whitespace.
+                    let token = sl(self.content, offset);
+                    if let Some(len) = token.iter().position(|&ch| ch <= b' ') {
+                        self.offset = offset + len;
+                        continue;
                     }
-                    None => Some(Err(Error::UnexpectedEof)),
+                    self.offset = self.content.len();
+                    return None;
                 }
+                Some(Err(ref err)) => {
+                    // There's no telling where the next attribute starts, so this is the last one.
+                    // Going over the length limit is still reported, like going over the count.
+                    self.offset = self.content.len();
+                    if self.options.recover && !matches!(err, Error::ValueTooLong { .. }) {
+                        return None;
+                    }
+                }
+                _ => (),
             }
+            return attr;
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // The shortest attribute is `a=""`, or `a` and a space when leniency allows it,
+        // and an error is always the last item.
+        let remaining = self.content.len() - self.offset;
+        let shortest = match self.options.bare || self.options.unquoted {
+            true => 2,
+            false => 4,
+        };
+        (0, Some(remaining.div_ceil(shortest)))
+    }
 }
 
-impl<'xml> Reader<'xml, str> {
-    /// Constructs a new [`Reader`] from a UTF-8 string.
-    pub const fn from_str(xml: &'xml str) -> Reader<'xml, str> {
-        Reader {
-            state: ReaderState::Searching,
-            source: xml,
-            offset: 0,
+impl<'xml> FusedIterator for AttributeIter<'xml, [u8]> {}
 
-            trim: true,
+impl<'xml> Iterator for AttributeIter<'xml, str> {
+    type Item = Result<Attribute<'xml, str>, Error>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        // SAFETY: Identical layout, contents, and that's how the standard library does it too.
+        unsafe {
+            let iter = mem::transmute::<&mut Self, &mut AttributeIter<'xml, [u8]>>(self);
+            let attr = iter.next();
+            if let Some(Ok(attr)) = &attr {
+                debug_assert_utf8(&[attr.key, attr.value]);
+            }
+            mem::transmute::<Option<Result<Attribute<'xml, [u8]>, Error>>, Option<Self::Item>>(attr)
         }
     }
 
-    /// Constructs a new [`Reader`] from a UTF-8 string, stripping the BOM if it's present.
     #[inline]
-    pub fn from_str_bom(xml: &'xml str) -> Reader<'xml, str> {
-        Self::from_str(xml.trim_start_matches('\u{feff}'))
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // SAFETY: Identical layout, contents, and that's how the standard library does it too.
+        unsafe { mem::transmute::<&Self, &AttributeIter<'xml, [u8]>>(self) }.size_hint()
     }
 }
 
-impl<'xml> Iterator for Reader<'xml, [u8]> {
-    type Item = Result<Event<'xml, [u8]>, Error>;
+impl<'xml> FusedIterator for AttributeIter<'xml, str> {}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        match self.state {
-            ReaderState::Searching => self.next_search(),
-            ReaderState::LocatedTag => self.next_tag(),
-            ReaderState::End => None,
+impl<'xml, T: ?Sized> Attribute<'xml, T> {
+    pub(crate) const fn new(key: &'xml T, value: &'xml T) -> Self {
+        Self {
+            key,
+            value,
+            key_start: 0,
+            value_start: 0,
+            quote: None,
         }
     }
-}
 
-impl<'xml> Iterator for Reader<'xml, str> {
-    type Item = Result<Event<'xml, str>, Error>;
+    /// Gets the key of the attribute this instance represents.
+    pub const fn key(&self) -> &'xml T {
+        self.key
+    }
 
-    #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        // SAFETY: Identical layout, contents, and that's how the standard library does it too.
-        unsafe { mem::transmute(mem::transmute::<_, &mut Reader<'xml, [u8]>>(self).next()) }
+    /// Gets the raw and potentially escaped value of the attribute this instance represents.
+    pub const fn value(&self) -> &'xml T {
+        self.value
+    }
+
+    /// Gets the quote the value was written in, either `'` or `"`,
+    /// or `None` if it wasn't quoted at all.
+    ///
+    /// Values are only ever unquoted with [`Reader::allow_unquoted_attributes`]
+    /// or [`Reader::allow_bare_attributes`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::AttributeIter;
+    ///
+    /// let mut attrs = AttributeIter::new("a='1' b=\"2\"").map(Result::unwrap);
+    /// assert_eq!(attrs.next().unwrap().quote_char(), Some(b'\''));
+    /// assert_eq!(attrs.next().unwrap().quote_char(), Some(b'"'));
+    /// ```
+    pub const fn quote_char(&self) -> Option<u8> {
+        self.quote
     }
 }
 
-#[cfg(test)]
-mod tests {
-    #[test]
+impl<'xml> Attribute<'xml, [u8]> {
+    /// Compares the value with its entities decoded against `other`, without allocating.
+    ///
+    /// The predefined entities (`&amp;`, `&lt;`, `&gt;`, `&quot;`, `&apos;`) and numeric
+    /// character references (`&#38;`, `&#x26;`) are decoded, and anything else is compared as-is.
+    pub fn value_eq_decoded(&self, other: &[u8]) -> bool {
+        eq_decoded(self.value, other)
+    }
+
+    /// Gets the value with its entities decoded, leaving unknown ones untouched.
+    ///
+    /// See [`decode::decode_text`] for details.
+    pub fn decoded_value(&self) -> Cow<'xml, [u8]> {
+        decode::decode_text(self.value)
+    }
+
+    /// Gets the value with its entities decoded, including the ones in `entities`.
+    pub fn decoded_value_with(&self, entities: &EntityTable) -> Cow<'xml, [u8]> {
+        entities.decode(self.value)
+    }
+
+    /// Gets the namespace prefix of the attribute's key, which is everything before the first `:`.
+    pub fn prefix(&self) -> Option<&'xml [u8]> {
+        split_qname(self.key).0
+    }
+
+    /// Gets the local part of the attribute's key, which is everything after the first `:` if any.
+    pub fn local_name(&self) -> &'xml [u8] {
+        split_qname(self.key).1
+    }
+
+    /// Gets the length of the attribute's key in bytes.
+    pub const fn key_len(&self) -> usize {
+        self.key.len()
+    }
+
+    /// Gets the length of the attribute's raw value in bytes.
+    pub const fn value_len(&self) -> usize {
+        self.value.len()
+    }
+
+    /// Gets the byte span of the key in the input.
+    ///
+    /// This is relative to the content if the [`AttributeIter`] was made with
+    /// [`AttributeIter::new`] rather than [`Tag::attributes`].
+    pub const fn key_span(&self) -> Range<usize> {
+        self.key_start..self.key_start + self.key.len()
+    }
+
+    /// Gets the byte span of the raw value in the input, not including the quotes.
+    ///
+    /// This is relative to the content if the [`AttributeIter`] was made with
+    /// [`AttributeIter::new`] rather than [`Tag::attributes`].
+    pub const fn value_span(&self) -> Range<usize> {
+        self.value_start..self.value_start + self.value.len()
+    }
+
+    /// Gets the byte span of the whole attribute in the input, from the start of the key
+    /// up to and including the closing quote.
+    ///
+    /// This is relative to the content if the [`AttributeIter`] was made with
+    /// [`AttributeIter::new`] rather than [`Tag::attributes`].
+    pub const fn span(&self) -> Range<usize> {
+        let end = self.value_start + self.value.len();
+        match self.quote {
+            Some(_) => self.key_start..end + 1,
+            None => self.key_start..end,
+        }
+    }
+}
+
+impl<'xml> Attribute<'xml, str> {
+    /// Compares the value with its entities decoded against `other`, without allocating.
+    ///
+    /// The predefined entities (`&amp;`, `&lt;`, `&gt;`, `&quot;`, `&apos;`) and numeric
+    /// character references (`&#38;`, `&#x26;`) are decoded, and anything else is compared as-is.
+    pub fn value_eq_decoded(&self, other: &str) -> bool {
+        eq_decoded(self.value.as_bytes(), other.as_bytes())
+    }
+
+    /// Gets the value with its entities decoded, leaving unknown ones untouched.
+    ///
+    /// See [`decode::decode_text`] for details.
+    pub fn decoded_value(&self) -> Cow<'xml, str> {
+        decode::decode_text_str(self.value)
+    }
+
+    /// Gets the value with its entities decoded, including the ones in `entities`.
+    pub fn decoded_value_with(&self, entities: &EntityTable) -> Cow<'xml, str> {
+        entities.decode_str(self.value)
+    }
+
+    /// Gets the namespace prefix of the attribute's key, which is everything before the first `:`.
+    pub fn prefix(&self) -> Option<&'xml str> {
+        self.key.split_once(':').map(|(prefix, _)| prefix)
+    }
+
+    /// Gets the local part of the attribute's key, which is everything after the first `:` if any.
+    pub fn local_name(&self) -> &'xml str {
+        self.key
+            .split_once(':')
+            .map_or(self.key, |(_, local)| local)
+    }
+
+    /// Gets the length of the attribute's key in bytes.
+    pub const fn key_len(&self) -> usize {
+        self.key.len()
+    }
+
+    /// Gets the length of the attribute's raw value in bytes.
+    pub const fn value_len(&self) -> usize {
+        self.value.len()
+    }
+
+    /// Gets the byte span of the key in the input.
+    ///
+    /// This is relative to the content if the [`AttributeIter`] was made with
+    /// [`AttributeIter::new`] rather than [`Tag::attributes`].
+    pub const fn key_span(&self) -> Range<usize> {
+        self.key_start..self.key_start + self.key.len()
+    }
+
+    /// Gets the byte span of the raw value in the input, not including the quotes.
+    ///
+    /// This is relative to the content if the [`AttributeIter`] was made with
+    /// [`AttributeIter::new`] rather than [`Tag::attributes`].
+    pub const fn value_span(&self) -> Range<usize> {
+        self.value_start..self.value_start + self.value.len()
+    }
+
+    /// Gets the byte span of the whole attribute in the input, from the start of the key
+    /// up to and including the closing quote.
+    ///
+    /// This is relative to the content if the [`AttributeIter`] was made with
+    /// [`AttributeIter::new`] rather than [`Tag::attributes`].
+    pub const fn span(&self) -> Range<usize> {
+        let end = self.value_start + self.value.len();
+        match self.quote {
+            Some(_) => self.key_start..end + 1,
+            None => self.key_start..end,
+        }
+    }
+}
+
+impl<'xml, T: ?Sized + AsRef<[u8]>> Attribute<'xml, T> {
+    /// Checks whether the key is `key`, comparing ASCII letters case-insensitively.
+    pub fn key_eq_ignore_case(&self, key: &T) -> bool {
+        self.key.as_ref().eq_ignore_ascii_case(key.as_ref())
+    }
+
+    /// Parses the value into any [`FromStr`](str::FromStr) type,
+    /// after decoding its entities and trimming surrounding whitespace.
+    ///
+    /// This only allocates if the value contains entities.
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::AttributeIter;
+    ///
+    /// let mut attrs = AttributeIter::new("index=' 3 ' rate='x'").map(Result::unwrap);
+    /// assert_eq!(attrs.next().unwrap().parse::<u32>(), Ok(3));
+    /// assert!(attrs.next().unwrap().parse::<f32>().is_err());
+    /// ```
+    pub fn parse<F: str::FromStr>(&self) -> Result<F, ValueError<F::Err>> {
+        parse_value(self.value.as_ref())
+    }
+
+    /// Parses the value as an integer, ignoring surrounding whitespace.
+    ///
+    /// Unlike [`Attribute::parse`], entities aren't decoded, as they never make up a number.
+    pub fn as_i64(&self) -> Option<i64> {
+        str::from_utf8(trim_whitespace(self.value.as_ref()))
+            .ok()?
+            .parse()
+            .ok()
+    }
+
+    /// Parses the value as a floating point number, ignoring surrounding whitespace.
+    ///
+    /// Unlike [`Attribute::parse`], entities aren't decoded, as they never make up a number.
+    pub fn as_f64(&self) -> Option<f64> {
+        str::from_utf8(trim_whitespace(self.value.as_ref()))
+            .ok()?
+            .parse()
+            .ok()
+    }
+
+    /// Parses the value as a boolean the way Flash configs spell them,
+    /// which is any of `true`, `false`, `yes`, `no` (ignoring case), `1` or `0`.
+    pub fn as_bool(&self) -> Option<bool> {
+        let value = trim_whitespace(self.value.as_ref());
+        let is = |word: &[u8]| value.eq_ignore_ascii_case(word);
+        if is(b"true") || is(b"yes") || is(b"1") {
+            Some(true)
+        } else if is(b"false") || is(b"no") || is(b"0") {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'xml, T: ?Sized> Clone for Attribute<'xml, T> {
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key,
+            value: self.value,
+            key_start: self.key_start,
+            value_start: self.value_start,
+            quote: self.quote,
+        }
+    }
+}
+
+impl<'xml, T: ?Sized> Text<'xml, T> {
+    #[inline]
+    pub(crate) const fn new(content: &'xml T) -> Self {
+        Self {
+            content,
+            start: 0,
+            leading: 0,
+            trailing: 0,
+        }
+    }
+
+    /// Gets the raw and potentially escaped content of the text this instance represents.
+    pub const fn content(&self) -> &'xml T {
+        self.content
+    }
+}
+
+impl<'xml> Text<'xml, [u8]> {
+    /// Gets the text as it appears in the input.
+    ///
+    /// For [`Event::Text`], this is the content before any trimming (or BOM skipping).\
+    /// For comments, CDATA sections and DOCTYPEs, this includes the surrounding markup.
+    pub fn raw(&self) -> &'xml [u8] {
+        // SAFETY: The reader took the content from the raw text, `leading` bytes in,
+        // and text that wasn't produced by one has nothing around its content.
+        unsafe {
+            let start = self.content.as_ptr().sub(self.leading);
+            slice::from_raw_parts(start, self.leading + self.content.len() + self.trailing)
+        }
+    }
+
+    /// Returns an iterator decoding the content piece by piece, without one big allocation.
+    ///
+    /// Chunks alternate between borrowed runs of literal text and owned expansions of single
+    /// entities, so concatenating them gives the fully decoded text.\
+    /// Only the predefined entities and numeric character references are decoded,
+    /// and unknown ones are left as-is inside the literal runs.
+    pub fn decode_chunks(&self) -> DecodeChunks<'xml, [u8]> {
+        DecodeChunks {
+            rest: self.content,
+            marker: PhantomData,
+        }
+    }
+
+    /// Gets the content with its entities decoded, leaving unknown ones untouched.
+    ///
+    /// See [`decode::decode_text`] for details.
+    pub fn decoded(&self) -> Cow<'xml, [u8]> {
+        decode::decode_text(self.content)
+    }
+
+    /// Gets the content with its entities decoded, including the ones in `entities`.
+    pub fn decoded_with(&self, entities: &EntityTable) -> Cow<'xml, [u8]> {
+        entities.decode(self.content)
+    }
+
+    /// Gets the length of the text's content in bytes.
+    pub const fn content_len(&self) -> usize {
+        self.content.len()
+    }
+
+    /// Gets the byte span of the content in the input.
+    ///
+    /// This is `0..0` for text that wasn't produced by a [`Reader`].
+    pub const fn span(&self) -> Range<usize> {
+        self.start..self.start + self.content.len()
+    }
+
+    /// Gets the content with line endings normalized as per the XML spec.
+    ///
+    /// Both `\r\n` and lone `\r` are translated to `\n`.\
+    /// This is independent of whitespace trimming, and borrows if there's no `\r` in the content.
+    pub fn content_normalized_eol(&self) -> Cow<'xml, [u8]> {
+        normalize_eol(self.content)
+    }
+
+    /// Gets the content as a string, if it's valid UTF-8.
+    pub fn as_str(&self) -> Result<&'xml str, str::Utf8Error> {
+        str::from_utf8(self.content)
+    }
+
+    /// Gets the content with every run of whitespace collapsed into a single space,
+    /// and without any at either end, as for `xml:space="default"`.
+    ///
+    /// This borrows if there's nothing to collapse.
+    pub fn normalized(&self) -> Cow<'xml, [u8]> {
+        collapse_whitespace(self.content)
+    }
+}
+
+impl<'xml> Text<'xml, str> {
+    /// Gets the text as it appears in the input.
+    ///
+    /// See [`Text::<[u8]>::raw`](Text::raw) for details.
+    pub fn raw(&self) -> &'xml str {
+        // SAFETY: Identical layout, and the content is only ever trimmed of ASCII whitespace,
+        // a BOM, or markup.
+        unsafe {
+            let text = mem::transmute::<&Self, &Text<'xml, [u8]>>(self);
+            str::from_utf8_unchecked(text.raw())
+        }
+    }
+
+    /// Returns an iterator decoding the content piece by piece, without one big allocation.
+    ///
+    /// Chunks alternate between borrowed runs of literal text and owned expansions of single
+    /// entities, so concatenating them gives the fully decoded text.\
+    /// Only the predefined entities and numeric character references are decoded,
+    /// and unknown ones are left as-is inside the literal runs.
+    pub fn decode_chunks(&self) -> DecodeChunks<'xml, str> {
+        DecodeChunks {
+            rest: self.content.as_bytes(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Gets the content with its entities decoded, leaving unknown ones untouched.
+    ///
+    /// See [`decode::decode_text`] for details.
+    pub fn decoded(&self) -> Cow<'xml, str> {
+        decode::decode_text_str(self.content)
+    }
+
+    /// Gets the content with its entities decoded, including the ones in `entities`.
+    pub fn decoded_with(&self, entities: &EntityTable) -> Cow<'xml, str> {
+        entities.decode_str(self.content)
+    }
+
+    /// Gets the length of the text's content in bytes.
+    pub const fn content_len(&self) -> usize {
+        self.content.len()
+    }
+
+    /// Gets the byte span of the content in the input.
+    ///
+    /// This is `0..0` for text that wasn't produced by a [`Reader`].
+    pub const fn span(&self) -> Range<usize> {
+        self.start..self.start + self.content.len()
+    }
+
+    /// Gets the content with line endings normalized as per the XML spec.
+    ///
+    /// Both `\r\n` and lone `\r` are translated to `\n`.\
+    /// This is independent of whitespace trimming, and borrows if there's no `\r` in the content.
+    pub fn content_normalized_eol(&self) -> Cow<'xml, str> {
+        match normalize_eol(self.content.as_bytes()) {
+            // SAFETY: Only ASCII bytes were replaced, so the output is still valid UTF-8.
+            Cow::Borrowed(x) => Cow::Borrowed(unsafe { str::from_utf8_unchecked(x) }),
+            Cow::Owned(x) => Cow::Owned(unsafe { String::from_utf8_unchecked(x) }),
+        }
+    }
+
+    /// Gets the content as a string, which it already is.
+    ///
+    /// This mirrors the checked conversion for byte [`Text`].
+    pub const fn as_str(&self) -> &'xml str {
+        self.content
+    }
+
+    /// Gets the content with every run of whitespace collapsed into a single space,
+    /// and without any at either end, as for `xml:space="default"`.
+    ///
+    /// This borrows if there's nothing to collapse.
+    pub fn normalized(&self) -> Cow<'xml, str> {
+        match collapse_whitespace(self.content.as_bytes()) {
+            // SAFETY: Only ASCII bytes were replaced or removed, so the output is still valid UTF-8.
+            Cow::Borrowed(x) => Cow::Borrowed(unsafe { str::from_utf8_unchecked(x) }),
+            Cow::Owned(x) => Cow::Owned(unsafe { String::from_utf8_unchecked(x) }),
+        }
+    }
+}
+
+impl<'xml, T: ?Sized + AsRef<[u8]>> Text<'xml, T> {
+    /// Parses the content into any [`FromStr`](str::FromStr) type,
+    /// after decoding its entities and trimming surrounding whitespace.
+    ///
+    /// This only allocates if the content contains entities.
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::{Event, Reader};
+    ///
+    /// let mut reader = Reader::from_str("<Value> 42 </Value>");
+    /// reader.trim_whitespace(false);
+    /// reader.next();
+    /// let Some(Ok(Event::Text(text))) = reader.next() else { unreachable!() };
+    /// assert_eq!(text.parse::<i32>(), Ok(42));
+    /// ```
+    pub fn parse<F: str::FromStr>(&self) -> Result<F, ValueError<F::Err>> {
+        parse_value(self.content.as_ref())
+    }
+
+    /// Gets how many bytes of [`Text::raw`] come before and after the content.
+    ///
+    /// For [`Event::Text`], this is the whitespace removed by [`Reader::trim_whitespace`]
+    /// (along with a skipped BOM), so the event can still be handled as if it wasn't trimmed.
+    /// For comments, CDATA sections and DOCTYPEs, this is the surrounding markup.
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::{Event, Reader, TrimInfo};
+    ///
+    /// let mut reader = Reader::from_str("<Text>\n  Hello  </Text>");
+    /// reader.next();
+    /// let Some(Ok(Event::Text(text))) = reader.next() else { unreachable!() };
+    /// assert_eq!(text.content(), "Hello");
+    /// assert_eq!(text.trim_info(), TrimInfo { leading: 3, trailing: 2 });
+    /// ```
+    pub fn trim_info(&self) -> TrimInfo {
+        TrimInfo {
+            leading: self.leading,
+            trailing: self.trailing,
+        }
+    }
+}
+
+impl<'xml> Iterator for DecodeChunks<'xml, [u8]> {
+    type Item = Cow<'xml, [u8]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(match next_decode_chunk(&mut self.rest)? {
+            Chunk::Literal(literal) => Cow::Borrowed(literal),
+            Chunk::Entity(ch) => Cow::Owned(ch.to_string().into_bytes()),
+        })
+    }
+}
+
+impl<'xml> Iterator for DecodeChunks<'xml, str> {
+    type Item = Cow<'xml, str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(match next_decode_chunk(&mut self.rest)? {
+            // SAFETY: Chunks are only ever split around ASCII `&` and `;`.
+            Chunk::Literal(literal) => Cow::Borrowed(unsafe { str::from_utf8_unchecked(literal) }),
+            Chunk::Entity(ch) => Cow::Owned(ch.to_string()),
+        })
+    }
+}
+
+impl<'xml, T: ?Sized> Clone for Text<'xml, T> {
+    fn clone(&self) -> Self {
+        Self {
+            content: self.content,
+            start: self.start,
+            leading: self.leading,
+            trailing: self.trailing,
+        }
+    }
+}
+
+impl<'xml, T: ?Sized> Reader<'xml, T> {
+    /// Enables or disables trimming whitespace in [`Text`] events.
+    ///
+    /// This property is dynamic and can be turned on and off while parsing.
+    ///
+    /// Defaults to enabled (`true`).
+    pub fn trim_whitespace(&mut self, trim: bool) -> &mut Self {
+        self.trim = trim;
+        self
+    }
+
+    /// Enables or disables skipping a UTF-8 BOM at the start of each concatenated document.
+    ///
+    /// When enabled, a BOM leading a text run (optionally preceded by whitespace) is removed
+    /// along with that whitespace, which is where the next document's BOM lands
+    /// when BOM-prefixed files are concatenated into one stream.
+    ///
+    /// Defaults to disabled (`false`).
+    pub fn skip_bom_per_document(&mut self, skip: bool) -> &mut Self {
+        self.skip_bom = skip;
+        self
+    }
+
+    /// Enables or disables checking the structure of the document.
+    ///
+    /// When enabled, an end tag that doesn't close any open element
+    /// yields [`Error::UnexpectedEndTag`] instead of an [`Event::End`],
+    /// and one that doesn't match the innermost open element yields [`Error::MismatchedEnd`].\
+    /// The reader moves past the offending tag without closing anything,
+    /// so parsing can carry on afterwards.\
+    /// Elements left open at the end of the input are reported with [`Error::UnclosedTags`].
+    ///
+    /// Defaults to disabled (`false`).
+    pub fn check_structure(&mut self, check: bool) -> &mut Self {
+        self.check_structure = check;
+        self
+    }
+
+    /// Enables or disables stopping once the root element has been closed.
+    ///
+    /// When enabled, the reader ends after the end tag bringing the depth back to zero
+    /// (or after an empty tag at the top level), ignoring any trailing content such as
+    /// garbage or further concatenated documents.
+    ///
+    /// Defaults to disabled (`false`).
+    pub fn stop_after_root(&mut self, stop: bool) -> &mut Self {
+        self.stop_after_root = stop;
+        self
+    }
+
+    /// Enables or disables yielding [`Event::Comment`]s.
+    ///
+    /// When disabled, comments are skipped over as if they weren't there.
+    ///
+    /// Defaults to enabled (`true`).
+    pub fn emit_comments(&mut self, emit: bool) -> &mut Self {
+        self.comments = emit;
+        self
+    }
+
+    /// Enables or disables yielding [`Event::Text`]s that are empty.
+    ///
+    /// When enabled, every run of text before a tag is yielded, even if it's empty
+    /// (such as between `<a><b>`) or only whitespace that got trimmed.
+    /// Text at the very end of the input is still only yielded if it's not empty.
+    ///
+    /// Defaults to disabled (`false`).
+    pub fn emit_empty_text(&mut self, emit: bool) -> &mut Self {
+        self.empty_text = emit;
+        self
+    }
+
+    /// Enables or disables checking every character of tag names.
+    ///
+    /// By default, only the first character of a name is checked, so `<a$b>` is accepted.\
+    /// When enabled, the rest of the name may only contain ASCII letters and digits,
+    /// `-`, `.`, `_`, `:` and non-ASCII characters, and anything else yields [`Error::InvalidName`].
+    ///
+    /// Defaults to disabled (`false`).
+    pub fn strict_names(&mut self, strict: bool) -> &mut Self {
+        self.strict_names = strict;
+        self
+    }
+
+    /// Enables or disables comparing tag names case-insensitively, for input such as
+    /// `<Frame></frame>` from tools that weren't consistent about it.
+    ///
+    /// This affects how end tags are matched with [`Reader::check_structure`] and
+    /// [`Reader::read_text`]. Only ASCII letters are folded, and nothing gets allocated.\
+    /// Names are still yielded as they appear in the input.
+    ///
+    /// Defaults to disabled (`false`).
+    pub fn ignore_name_case(&mut self, ignore: bool) -> &mut Self {
+        self.ignore_name_case = ignore;
+        self
+    }
+
+    /// Enables or disables honoring `xml:space` when trimming whitespace.
+    ///
+    /// When enabled, text inside an element with `xml:space="preserve"` isn't trimmed,
+    /// even if [`Reader::trim_whitespace`] is, and whitespace-only text is yielded there.
+    /// The setting is inherited by descendants until one of them sets `xml:space="default"`.
+    ///
+    /// Defaults to disabled (`false`).
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::{Event, Reader};
+    ///
+    /// let xml = "<Text xml:space='preserve'><Run> a </Run></Text><Run> b </Run>";
+    /// let mut reader = Reader::from_str(xml);
+    /// reader.honor_xml_space(true);
+    /// let texts: Vec<_> = reader
+    ///     .filter_map(|event| match event.unwrap() {
+    ///         Event::Text(text) => Some(text.content()),
+    ///         _ => None,
+    ///     })
+    ///     .collect();
+    /// assert_eq!(texts, [" a ", "b"]);
+    /// ```
+    pub fn honor_xml_space(&mut self, honor: bool) -> &mut Self {
+        self.honor_xml_space = honor;
+        self
+    }
+
+    /// Sets the maximum length in bytes of start, end and empty tags, or `None` for no limit.
+    ///
+    /// The length counts everything between the angle brackets. Longer tags yield
+    /// [`Error::LimitExceeded`] with [`Limit::TagLen`], without scanning any further than the limit for the closing `>`.
+    ///
+    /// Defaults to no limit (`None`).
+    pub fn max_tag_len(&mut self, limit: Option<usize>) -> &mut Self {
+        self.max_tag_len = limit;
+        self
+    }
+
+    /// Sets the maximum nesting depth of elements, or `None` for no limit.
+    ///
+    /// A start tag that would open an element deeper than that yields [`Error::LimitExceeded`]
+    /// with [`Limit::Depth`],
+    /// so `Some(1)` only allows the root element to have (empty) children.
+    ///
+    /// Defaults to no limit (`None`).
+    pub fn max_depth(&mut self, limit: Option<usize>) -> &mut Self {
+        self.max_depth = limit;
+        self
+    }
+
+    /// Sets the maximum length in bytes of text between tags, or `None` for no limit.
+    ///
+    /// The length is counted before any trimming. Longer text yields
+    /// [`Error::LimitExceeded`] with [`Limit::TextLen`], without scanning any further
+    /// than the limit for the next tag, and the reader ends there.
+    ///
+    /// Defaults to no limit (`None`).
+    pub fn max_text_len(&mut self, limit: Option<usize>) -> &mut Self {
+        self.max_text_len = limit;
+        self
+    }
+
+    /// Sets the maximum number of events to read, or `None` for no limit.
+    ///
+    /// Reading past that yields [`Error::LimitExceeded`] with [`Limit::Events`],
+    /// and the reader ends there. Errors count as events too.
+    ///
+    /// Defaults to no limit (`None`).
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::{Error, Limit, Reader};
+    ///
+    /// let mut reader = Reader::from_str("<a><b/><b/><b/></a>");
+    /// reader.max_events(Some(3));
+    /// assert_eq!(reader.by_ref().take(3).filter(Result::is_ok).count(), 3);
+    /// assert!(matches!(
+    ///     reader.next(),
+    ///     Some(Err(Error::LimitExceeded { limit: Limit::Events, offset: 11 }))
+    /// ));
+    /// assert!(reader.next().is_none());
+    /// ```
+    pub fn max_events(&mut self, limit: Option<usize>) -> &mut Self {
+        self.max_events = limit;
+        self
+    }
+
+    /// Enables or disables strict quoting of attribute values.
+    ///
+    /// By default, the value of an attribute is whatever comes after the next quote following `=`,
+    /// so `<Name a=1 b="2">` leniently parses as a single attribute `a` with the value `2`.\
+    /// When enabled, anything other than whitespace between `=` and the opening quote makes
+    /// [`AttributeIter`] yield [`Error::InvalidAttribute`] with the offset of the offending
+    /// token (`1` in the example above) instead.
+    ///
+    /// This applies to the attributes of every [`Tag`] produced afterwards.
+    ///
+    /// Defaults to disabled (`false`).
+    pub fn strict_attributes(&mut self, strict: bool) -> &mut Self {
+        self.attribute_options.strict = strict;
+        self
+    }
+
+    /// Enables or disables attribute values without quotes, as in `<Frame index=3>`.
+    ///
+    /// When enabled, a value that doesn't start with a quote runs up to the next whitespace
+    /// or the end of the tag, rather than being looked for after the next quote.
+    /// This takes precedence over [`strict_attributes`](Self::strict_attributes).
+    ///
+    /// This applies to the attributes of every [`Tag`] produced afterwards.
+    ///
+    /// Defaults to disabled (`false`).
+    pub fn allow_unquoted_attributes(&mut self, allow: bool) -> &mut Self {
+        self.attribute_options.unquoted = allow;
+        self
+    }
+
+    /// Enables or disables attributes without a value, as in `<Option enabled>`.
+    ///
+    /// When enabled, a key that isn't followed by `=` yields an [`Attribute`] with an empty value.
+    /// Keys then end at the first whitespace, where they would otherwise run up to the next `=`.
+    ///
+    /// This applies to the attributes of every [`Tag`] produced afterwards.
+    ///
+    /// Defaults to disabled (`false`).
+    pub fn allow_bare_attributes(&mut self, allow: bool) -> &mut Self {
+        self.attribute_options.bare = allow;
+        self
+    }
+
+    /// Sets the flavour of markup to expect.
+    ///
+    /// With [`Dialect::Html`], void elements such as `<br>` and `<img>` are yielded as
+    /// [`Event::Empty`] whether or not they're self-closing, and their stray end tags are skipped.
+    /// The content of `<script>` and `<style>` is yielded as a single [`Event::Text`]
+    /// running up to their end tag, markup and all.\
+    /// Selecting it also enables [`Reader::allow_unquoted_attributes`],
+    /// [`Reader::allow_bare_attributes`] and [`Reader::ignore_name_case`],
+    /// which can be disabled again afterwards.
+    /// Going back to [`Dialect::Xml`] restores them to what they were before.
+    ///
+    /// Defaults to [`Dialect::Xml`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::{Dialect, Event, Reader};
+    ///
+    /// let mut reader = Reader::from_str("<p>a<br>b<script>if (a<b) {}</script></p>");
+    /// reader.dialect(Dialect::Html).check_structure(true);
+    /// let events: Vec<_> = reader.map(Result::unwrap).collect();
+    /// assert!(matches!(events[2], Event::Empty(_)));
+    /// assert!(matches!(&events[5], Event::Text(text) if text.content() == "if (a<b) {}"));
+    /// assert_eq!(events.len(), 8);
+    /// ```
+    pub fn dialect(&mut self, dialect: Dialect) -> &mut Self {
+        match (self.dialect, dialect) {
+            (Dialect::Xml, Dialect::Html) => {
+                let options = &self.attribute_options;
+                self.xml_leniency = (options.unquoted, options.bare, self.ignore_name_case);
+                self.allow_unquoted_attributes(true)
+                    .allow_bare_attributes(true)
+                    .ignore_name_case(true);
+            }
+            (Dialect::Html, Dialect::Xml) => {
+                let (unquoted, bare, ignore_case) = self.xml_leniency;
+                self.allow_unquoted_attributes(unquoted)
+                    .allow_bare_attributes(bare)
+                    .ignore_name_case(ignore_case);
+            }
+            _ => (),
+        }
+        self.dialect = dialect;
+        self
+    }
+
+    /// Sets the maximum length in bytes of attribute values, or `None` for no limit.
+    ///
+    /// Values longer than that make [`AttributeIter`] yield [`Error::ValueTooLong`],
+    /// without scanning any further than the limit for the closing quote.
+    ///
+    /// This applies to the attributes of every [`Tag`] produced afterwards.
+    ///
+    /// Defaults to no limit (`None`).
+    pub fn max_attribute_value_len(&mut self, limit: Option<usize>) -> &mut Self {
+        self.attribute_options.max_value_len = limit.unwrap_or(usize::MAX);
+        self
+    }
+
+    /// Sets the maximum number of attributes per tag, or `None` for no limit.
+    ///
+    /// Once a tag has had that many attributes, the next one makes [`AttributeIter`] yield
+    /// [`Error::LimitExceeded`] with [`Limit::Attributes`], as its last item.
+    ///
+    /// This applies to the attributes of every [`Tag`] produced afterwards.
+    ///
+    /// Defaults to no limit (`None`).
+    pub fn max_attributes(&mut self, limit: Option<usize>) -> &mut Self {
+        self.attribute_options.max_count = limit.unwrap_or(usize::MAX);
+        self
+    }
+
+    /// Enables or disables recovering from malformed tags.
+    ///
+    /// When enabled, a tag that can't be parsed as one (such as `<0Name>`, `</>`,
+    /// or an unterminated `<Name` at the end of the input) is not reported as an error.\
+    /// Instead, its raw bytes from `<` up to and including `>` (or the end of the input) are
+    /// yielded untrimmed as an [`Event::Text`], so no input is silently lost, and parsing carries on.
+    ///
+    /// Malformed attributes don't make a tag unparseable: `<a =bad b="1">` is still
+    /// an [`Event::Start`] whose content keeps the raw `=bad b="1"`.
+    /// When enabled, its [`AttributeIter`] skips `=bad` up to the next whitespace
+    /// instead of reporting [`Error::InvalidAttribute`], and carries on with `b`.
+    /// Going over [`Reader::max_attribute_value_len`] or [`Reader::max_attributes`]
+    /// is still reported as the last item, and any other attribute error just ends the iterator.
+    ///
+    /// This applies to the attributes of every [`Tag`] produced afterwards.
+    ///
+    /// Defaults to disabled (`false`).
+    pub fn recover(&mut self, recover: bool) -> &mut Self {
+        self.recover = recover;
+        self.attribute_options.recover = recover;
+        self
+    }
+
+    /// Attaches a cancellation flag, checked once every time an event is requested.
+    ///
+    /// When the flag is set, the reader yields [`Error::Cancelled`] and then stops.
+    pub fn with_cancel(&mut self, cancel: Arc<AtomicBool>) -> &mut Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Attaches a dictionary of names, which the name of every [`Tag`] produced afterwards
+    /// is looked up in, for [`Tag::symbol`].
+    pub fn with_interner(&mut self, interner: &'xml Interner) -> &mut Self {
+        self.interner = Some(interner);
+        self
+    }
+
+    /// Gets how many elements are open after the last event, which is 1 inside the root element.
+    ///
+    /// This counts start tags against end tags, even if they don't match.
+    pub const fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Gets the byte offset from the start of the input.
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Checks whether the reader is done and ended cleanly,
+    /// so that [`Iterator::next`] only returns `None` from now on.
+    ///
+    /// A reader that was ended by an error isn't finished, such as after [`Error::UnexpectedEof`]
+    /// from a document that ends inside a tag, or [`Error::UnclosedTags`] from one with elements
+    /// left open with [`Reader::check_structure`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::Reader;
+    ///
+    /// let mut reader = Reader::from_str("<a></a><b");
+    /// reader.next(); // <a>
+    /// reader.next(); // </a>
+    /// assert!(!reader.is_finished());
+    /// assert!(reader.next().unwrap().is_err());
+    /// assert!(reader.next().is_none());
+    /// assert!(!reader.is_finished());
+    ///
+    /// let mut reader = Reader::from_str("<a></a>");
+    /// reader.by_ref().for_each(drop);
+    /// assert!(reader.is_finished());
+    /// ```
+    pub fn is_finished(&self) -> bool {
+        matches!(self.state, ReaderState::End) && self.open.is_empty() && !self.failed
+    }
+
+    /// Gets the byte span `start..end` of the last yielded event in the input.
+    ///
+    /// Before any event was yielded, this is `0..0`.
+    pub const fn last_event_span(&self) -> Range<usize> {
+        self.event_start..self.event_end
+    }
+
+    /// Gets the byte offset one past the end of the last yielded event.
+    ///
+    /// Together with the start offsets from [`Reader::indexed`], this gives the exact span
+    /// `source[start..end]` of an event. Before any event was yielded, this is `0`.
+    pub const fn last_event_end(&self) -> usize {
+        self.event_end
+    }
+
+    /// Gets the offset of the start tag matching the end tag that was just yielded.
+    ///
+    /// Only available when [`Reader::check_structure`] was enabled from the start,
+    /// and only right after an [`Event::End`] that closed an element.\
+    /// Together with [`Reader::last_event_end`], this gives the span of the whole element.
+    pub fn current_match_start(&self) -> Option<usize> {
+        self.match_start
+    }
+
+    /// Turns the reader into an iterator yielding each event alongside its starting byte offset.
+    ///
+    /// Tags start at their opening angle bracket `<`, and text starts at its first character
+    /// after trimming (if enabled).
+    pub fn indexed(self) -> Indexed<'xml, T> {
+        Indexed { reader: self }
+    }
+}
+
+impl<'xml, T: ?Sized> Reader<'xml, T> {
+    /// Gets the address and length of the input, which tell readers apart.
+    fn source_id(&self) -> (usize, usize) {
+        (
+            self.source as *const T as *const u8 as usize,
+            mem::size_of_val(self.source),
+        )
+    }
+
+    /// Gets where the unread rest of the input starts, including the `<` of a located tag.
+    fn remaining_start(&self) -> usize {
+        match self.state {
+            ReaderState::Searching => self.offset,
+            ReaderState::LocatedTag => self.offset - 1,
+            ReaderState::End => mem::size_of_val(self.source),
+        }
+    }
+
+    /// Ends the reader, handing its settings over to a fresh reader over `rest`.
+    fn split_off_into(&mut self, rest: &'xml T) -> Self {
+        let reader = Self {
+            state: ReaderState::Searching,
+            source: rest,
+            offset: 0,
+            event_start: 0,
+            event_end: 0,
+            depth: 0,
+            open: Vec::new(),
+            match_start: None,
+            raw_text: None,
+            xml_space: Vec::new(),
+            events: 0,
+            failed: false,
+            #[cfg(feature = "stats")]
+            stats: Stats::EMPTY,
+            ..self.fork()
+        };
+        self.state = ReaderState::End;
+        self.offset = mem::size_of_val(self.source);
+        self.open.clear();
+        reader
+    }
+
+    /// Copies the reader to look ahead without affecting it.
+    fn fork(&self) -> Self {
+        Self {
+            state: self.state,
+            source: self.source,
+            offset: self.offset,
+            event_start: self.event_start,
+            event_end: self.event_end,
+            depth: self.depth,
+            open: self.open.clone(),
+            match_start: self.match_start,
+            raw_text: self.raw_text.clone(),
+            xml_space: self.xml_space.clone(),
+            events: self.events,
+            failed: self.failed,
+
+            trim: self.trim,
+            skip_bom: self.skip_bom,
+            recover: self.recover,
+            check_structure: self.check_structure,
+            stop_after_root: self.stop_after_root,
+            comments: self.comments,
+            empty_text: self.empty_text,
+            strict_names: self.strict_names,
+            ignore_name_case: self.ignore_name_case,
+            honor_xml_space: self.honor_xml_space,
+            dialect: self.dialect,
+            xml_leniency: self.xml_leniency,
+            max_tag_len: self.max_tag_len,
+            max_depth: self.max_depth,
+            max_text_len: self.max_text_len,
+            max_events: self.max_events,
+            attribute_options: self.attribute_options,
+            cancel: self.cancel.clone(),
+            interner: self.interner,
+            #[cfg(feature = "stats")]
+            stats: self.stats,
+        }
+    }
+}
+
+impl<'r, 'xml, T: ?Sized> ChildIter<'r, 'xml, T> {
+    /// Returns an iterator over the direct children of the child element that was just yielded.
+    ///
+    /// This must be called right after this iterator yields an [`Event::Start`].
+    pub fn children(&mut self) -> ChildIter<'_, 'xml, T> {
+        ChildIter {
+            depth: self.reader.depth,
+            reader: self.reader,
+            done: false,
+        }
+    }
+}
+
+impl<'r, 'xml, T: ?Sized> Iterator for ChildIter<'r, 'xml, T>
+where
+    Reader<'xml, T>: Iterator<Item = Result<Event<'xml, T>, Error>>,
+{
+    type Item = Result<Event<'xml, T>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let depth = self.reader.depth;
+            let event = match self.reader.next() {
+                Some(Ok(event)) => event,
+                Some(Err(error)) => {
+                    self.done = true;
+                    return Some(Err(error));
+                }
+                None => {
+                    self.done = true;
+                    return Some(Err(Error::UnexpectedEof));
+                }
+            };
+            // Anything read while inside a child (including its end tag) is skipped.
+            if depth > self.depth {
+                continue;
+            }
+            if let Event::End(_) = event {
+                self.done = true;
+                return None;
+            }
+            return Some(Ok(event));
+        }
+        None
+    }
+}
+
+impl<'xml, T: ?Sized> Indexed<'xml, T> {
+    /// Gets a reference to the underlying [`Reader`].
+    pub const fn reader(&self) -> &Reader<'xml, T> {
+        &self.reader
+    }
+}
+
+impl<'xml, T: ?Sized> Iterator for Indexed<'xml, T>
+where
+    Reader<'xml, T>: Iterator<Item = Result<Event<'xml, T>, Error>>,
+{
+    type Item = Result<(usize, Event<'xml, T>), Error>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = self.reader.next()?;
+        Some(event.map(|event| (self.reader.event_start, event)))
+    }
+}
+
+impl<'xml, T: ?Sized> Reader<'xml, T>
+where
+    Self: Iterator<Item = Result<Event<'xml, T>, Error>>,
+{
+    /// Returns an iterator over the direct children of the element that was just started.
+    ///
+    /// This must be called right after an [`Event::Start`].
+    /// Once the iterator ends, the reader is positioned right after the element's end tag,
+    /// even if the iterator was dropped early.
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::{Event, Reader};
+    ///
+    /// let mut reader = Reader::from_str("<List><A><Nested/></A>text<B/></List><After/>");
+    /// reader.next(); // <List>
+    /// assert_eq!(reader.children().count(), 3);
+    /// assert!(matches!(reader.next(), Some(Ok(Event::Empty(_)))));
+    /// ```
+    pub fn children(&mut self) -> ChildIter<'_, 'xml, T> {
+        ChildIter {
+            depth: self.depth,
+            reader: self,
+            done: false,
+        }
+    }
+
+    /// Saves where the reader is, to go back there later with [`Reader::restore`].
+    ///
+    /// This captures the position and everything tracked along the way,
+    /// such as the open elements with [`Reader::check_structure`], but not the settings.
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::{Event, Reader};
+    ///
+    /// let mut reader = Reader::from_str("<a><b/><c/></a>");
+    /// reader.next(); // <a>
+    /// let checkpoint = reader.checkpoint();
+    /// assert_eq!(reader.by_ref().count(), 3);
+    /// reader.restore(checkpoint);
+    /// assert_eq!(reader.depth(), 1);
+    /// assert!(matches!(reader.next(), Some(Ok(Event::Empty(tag))) if tag.name() == "b"));
+    /// ```
+    pub fn checkpoint(&self) -> Checkpoint<'xml> {
+        Checkpoint {
+            source: self.source_id(),
+            marker: PhantomData,
+            state: self.state,
+            offset: self.offset,
+            event_start: self.event_start,
+            event_end: self.event_end,
+            depth: self.depth,
+            open: self.open.clone(),
+            match_start: self.match_start,
+            raw_text: self.raw_text.clone(),
+            xml_space: self.xml_space.clone(),
+            events: self.events,
+            failed: self.failed,
+            #[cfg(feature = "stats")]
+            stats: self.stats,
+        }
+    }
+
+    /// Goes back to where the reader was when `checkpoint` was created,
+    /// so the events since then are read again.
+    ///
+    /// Settings changed since then are kept.
+    ///
+    /// # Panics
+    /// Panics if `checkpoint` was created by a reader over different input.
+    pub fn restore(&mut self, checkpoint: Checkpoint<'xml>) {
+        assert!(
+            checkpoint.source == self.source_id(),
+            "checkpoint is from a reader over different input"
+        );
+        self.state = checkpoint.state;
+        self.offset = checkpoint.offset;
+        self.event_start = checkpoint.event_start;
+        self.event_end = checkpoint.event_end;
+        self.depth = checkpoint.depth;
+        self.open = checkpoint.open;
+        self.match_start = checkpoint.match_start;
+        self.raw_text = checkpoint.raw_text;
+        self.xml_space = checkpoint.xml_space;
+        self.events = checkpoint.events;
+        self.failed = checkpoint.failed;
+        #[cfg(feature = "stats")]
+        {
+            self.stats = checkpoint.stats;
+        }
+    }
+
+    /// Gets the next event without consuming it.
+    ///
+    /// The reader isn't affected at all, so [`Reader::offset`] and [`Reader::depth`]
+    /// still describe the last event that was consumed.
+    /// Nothing is cached either: the event is read from a copy of the reader,
+    /// which is cheap as events borrow from the input.
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::{Event, Reader};
+    ///
+    /// let mut reader = Reader::from_str("<a><b/></a>");
+    /// reader.next(); // <a>
+    /// assert!(matches!(reader.peek(), Some(Ok(Event::Empty(_)))));
+    /// assert_eq!(reader.depth(), 1);
+    /// assert!(matches!(reader.peek_nth(1), Some(Ok(Event::End(_)))));
+    /// assert!(matches!(reader.next(), Some(Ok(Event::Empty(_)))));
+    /// ```
+    pub fn peek(&self) -> Option<Result<Event<'xml, T>, Error>> {
+        self.peek_nth(0)
+    }
+
+    /// Gets the event `n` places ahead without consuming anything, where `0` is the next one.
+    ///
+    /// This reads `n + 1` events ahead on a copy of the reader, see [`Reader::peek`].
+    /// If an error comes up before the `n`th event, it's returned instead.
+    pub fn peek_nth(&self, n: usize) -> Option<Result<Event<'xml, T>, Error>> {
+        let mut lookahead = self.fork();
+        for _ in 0..n {
+            if let Err(error) = lookahead.next()? {
+                return Some(Err(error));
+            }
+        }
+        lookahead.next()
+    }
+
+    /// Determines what the element that was just started directly contains, without consuming it.
+    ///
+    /// This must be called right after an [`Event::Start`]. The children are scanned ahead
+    /// up to the matching end tag, and the reader stays where it was.\
+    /// Text only counts if it would be emitted as an [`Event::Text`], so whitespace between
+    /// children doesn't make an element mixed as long as trimming is enabled.
+    pub fn classify_element(&mut self) -> Result<ContentModel, Error> {
+        let (mut text, mut elements) = (false, false);
+        let mut depth = 0usize;
+        let mut lookahead = self.fork();
+        loop {
+            match lookahead.next().ok_or(Error::UnexpectedEof)?? {
+                Event::Text(_) | Event::CData(_) if depth == 0 => text = true,
+                Event::Empty(_) if depth == 0 => elements = true,
+                Event::Start(_) => {
+                    elements = true;
+                    depth += 1;
+                }
+                Event::End(_) if depth == 0 => break,
+                Event::End(_) => depth -= 1,
+                _ => (),
+            }
+        }
+        Ok(match (text, elements) {
+            (false, false) => ContentModel::Empty,
+            (true, false) => ContentModel::Text,
+            (false, true) => ContentModel::Elements,
+            (true, true) => ContentModel::Mixed,
+        })
+    }
+
+    /// Feeds all remaining events into `sink`, stopping at the first error.
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::{EventSink, Reader, Tag};
+    ///
+    /// struct CountFrames(usize);
+    /// impl<'xml> EventSink<'xml, str> for CountFrames {
+    ///     fn start(&mut self, tag: Tag<'xml, str>) {
+    ///         self.0 += (tag.name() == "Frame") as usize;
+    ///     }
+    /// }
+    ///
+    /// let mut sink = CountFrames(0);
+    /// Reader::from_str("<Frame><Frame></Frame></Frame>").drive(&mut sink).unwrap();
+    /// assert_eq!(sink.0, 2);
+    /// ```
+    pub fn drive<S: EventSink<'xml, T>>(self, sink: &mut S) -> Result<(), Error> {
+        for event in self {
+            match event? {
+                Event::Start(tag) => sink.start(tag),
+                Event::End(tag) => sink.end(tag),
+                Event::Empty(tag) => sink.empty(tag),
+                Event::Text(text) => sink.text(text),
+                Event::Comment(text) => sink.comment(text),
+                Event::ProcessingInstruction(tag) => sink.processing_instruction(tag),
+                Event::CData(text) => sink.cdata(text),
+                Event::DocType(text) => sink.doctype(text),
+            }
+        }
+        Ok(())
+    }
+
+    /// Clears `buf` and fills it with the remaining events, converted to [`OwnedEvent`]s.
+    ///
+    /// Reusing the same `buf` across many small documents means the [`Vec`]
+    /// only allocates when it needs to grow past its previous capacity,
+    /// though each event still owns a copy of its data.
+    ///
+    /// On error, `buf` holds the events read up to that point.
+    pub fn drain_into(&mut self, buf: &mut Vec<OwnedEvent<T>>) -> Result<(), Error>
+    where
+        T: ToOwned,
+    {
+        buf.clear();
+        for event in self {
+            buf.push(event?.into_owned());
+        }
+        Ok(())
+    }
+}
+
+impl<'xml, T: ?Sized + AsRef<[u8]>> Reader<'xml, T> {
+    /// Gets the line and column the reader is at.
+    ///
+    /// See [`Reader::position_of`] for details.
+    pub fn position(&self) -> Position {
+        self.position_of(self.offset)
+    }
+
+    /// Gets the line and column of a byte offset in the input, such as from [`Error::offset`].
+    ///
+    /// Lines are counted on demand so that reading stays fast, which is linear in `offset`.\
+    /// Offsets past the end of the input are clamped to it.
+    pub fn position_of(&self, offset: usize) -> Position {
+        let bytes = self.source.as_ref();
+        let before = sl_to(bytes, offset.min(bytes.len()));
+        let line_start = before
+            .iter()
+            .rposition(|&ch| ch == b'\n')
+            .map_or(0, |x| x + 1);
+        // Continuation bytes don't start a new character.
+        let column = sl(before, line_start)
+            .iter()
+            .filter(|&&ch| !(0x80..0xC0).contains(&ch))
+            .count();
+        Position {
+            line: before.iter().filter(|&&ch| ch == b'\n').count() + 1,
+            column: column + 1,
+            byte: before.len(),
+        }
+    }
+
+    /// Skips over the rest of the element that was just started, including its end tag.
+    ///
+    /// This must be called right after an [`Event::Start`].
+    /// Unlike draining [`Reader::children`], no events are produced for the content:
+    /// it's only scanned for `<` and `>` to keep track of nesting,
+    /// which makes cherry-picking a few elements out of a large document cheap.\
+    /// As such, the skipped content isn't checked for errors, even when checking structure.
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::{Event, Reader};
+    ///
+    /// let mut reader = Reader::from_str("<Big><A><!-- </Big> --></A></Big><After/>");
+    /// reader.next(); // <Big>
+    /// reader.skip_current_element().unwrap();
+    /// assert!(matches!(reader.next(), Some(Ok(Event::Empty(_)))));
+    /// ```
+    pub fn skip_current_element(&mut self) -> Result<(), Error> {
+        self.skip_element(false).map(|_| ())
+    }
+
+    fn names_eq(&self, a: &[u8], b: &[u8]) -> bool {
+        match self.ignore_name_case {
+            true => a.eq_ignore_ascii_case(b),
+            false => a == b,
+        }
+    }
+
+    /// Moves past the end tag matching the element that was just started,
+    /// returning the offset of its `<`.
+    ///
+    /// With `by_name`, only tags named like the element count towards nesting,
+    /// so that stray markup in raw content can't unbalance it.
+    fn skip_element(&mut self, by_name: bool) -> Result<usize, Error> {
+        let bytes = self.source.as_ref();
+        fn tag_name(tag: &[u8]) -> &[u8] {
+            match tag
+                .iter()
+                .position(|&ch| matches!(ch, 0..=b' ' | b'>' | b'/'))
+            {
+                Some(idx) => sl_to(tag, idx),
+                None => tag,
+            }
+        }
+        let name = tag_name(sl(bytes, self.event_start + 1));
+        let is_name = |tag: &[u8]| self.names_eq(tag_name(tag), name);
+        let mut depth = 1usize;
+        let mut offset = self.offset;
+        let end_tag = loop {
+            let lt = match memchr(b'<', sl(bytes, offset)) {
+                Some(idx) => offset + idx,
+                None => break None,
+            };
+            let rest = sl(bytes, lt + 1);
+            // Markup that may contain `<` and `>` has to be skipped as a whole.
+            let (terminator, skip) = if rest.starts_with(b"!--") {
+                (&b"-->"[..], 3)
+            } else if rest.starts_with(b"![CDATA[") {
+                (&b"]]>"[..], 8)
+            } else if rest.starts_with(b"?") {
+                (&b"?>"[..], 1)
+            } else {
+                (&b">"[..], 0)
+            };
+            let gt = match find(sl(rest, skip), terminator) {
+                Some(idx) => lt + 1 + skip + idx + terminator.len(),
+                None => break None,
+            };
+            offset = gt;
+            match rest.first() {
+                Some(b'!' | b'?') => (),
+                Some(b'/') => {
+                    if !by_name || is_name(sl(rest, 1)) {
+                        depth -= 1;
+                        if depth == 0 {
+                            break Some((lt, gt));
+                        }
+                    }
+                }
+                _ if by_name && !is_name(rest) => (),
+                _ if bytes[gt - 2] == b'/' => (),
+                _ => depth += 1,
+            }
+        };
+        match end_tag {
+            Some((lt, gt)) => {
+                self.event_start = lt;
+                self.event_end = gt;
+                self.offset = gt;
+                self.state = ReaderState::Searching;
+                self.depth = self.depth.saturating_sub(1);
+                self.match_start = self.open.pop().map(|x| x.start);
+                if self.depth == 0 && self.stop_after_root {
+                    self.state = ReaderState::End;
+                }
+                Ok(lt)
+            }
+            None => {
+                self.offset = bytes.len();
+                self.state = ReaderState::End;
+                self.open.clear();
+                self.failed = true;
+                Err(Error::UnexpectedEof)
+            }
+        }
+    }
+
+    /// Constructs a new [`Reader`] over `xml`, resuming at `offset` like `from_parts`,
+    /// but without checking that it's on a char boundary, which is up to the caller for `str`.
+    ///
+    /// Returns `None` if `offset` is past the end of the input.
+    pub(crate) fn resume(xml: &'xml T, offset: usize, trim: bool) -> Option<Self> {
+        let bytes = xml.as_ref();
+        if offset > bytes.len() {
+            return None;
+        }
+        let state = match offset.checked_sub(1).map(|x| bytes[x]) {
+            Some(b'<') => ReaderState::LocatedTag,
+            _ => ReaderState::Searching,
+        };
+        Some(Reader {
+            state,
+            source: xml,
+            offset,
+            event_start: 0,
+            event_end: 0,
+            depth: 0,
+            open: Vec::new(),
+            match_start: None,
+            raw_text: None,
+            xml_space: Vec::new(),
+            events: 0,
+            failed: false,
+
+            trim,
+            skip_bom: false,
+            recover: false,
+            check_structure: false,
+            stop_after_root: false,
+            comments: true,
+            empty_text: false,
+            strict_names: false,
+            ignore_name_case: false,
+            honor_xml_space: false,
+            dialect: Dialect::Xml,
+            xml_leniency: (false, false, false),
+            max_tag_len: None,
+            max_depth: None,
+            max_text_len: None,
+            max_events: None,
+            attribute_options: AttributeOptions::DEFAULT,
+            cancel: None,
+            interner: None,
+            #[cfg(feature = "stats")]
+            stats: Stats::EMPTY,
+        })
+    }
+}
+
+impl<'xml> Reader<'xml, [u8]> {
+    /// Constructs a new [`Reader`] over `xml`, resuming at `offset`.
+    ///
+    /// This is meant to continue from a previous [`Reader::offset`] with the same source.\
+    /// If `offset` directly follows a `<`, parsing resumes inside that tag,
+    /// which is where [`Reader::offset`] points after a [`Text`] event.
+    ///
+    /// Returns `None` if `offset` is past the end of the input.
+    pub fn from_parts(xml: &'xml [u8], offset: usize, trim: bool) -> Option<Self> {
+        Reader::resume(xml, offset, trim)
+    }
+
+    /// Constructs a new [`Reader`] from ASCII-compatible XML bytes.
+    pub const fn from_bytes(xml: &'xml [u8]) -> Reader<'xml, [u8]> {
+        Reader {
+            state: ReaderState::Searching,
+            source: xml,
+            offset: 0,
+            event_start: 0,
+            event_end: 0,
+            depth: 0,
+            open: Vec::new(),
+            match_start: None,
+            raw_text: None,
+            xml_space: Vec::new(),
+            events: 0,
+            failed: false,
+
+            trim: true,
+            skip_bom: false,
+            recover: false,
+            check_structure: false,
+            stop_after_root: false,
+            comments: true,
+            empty_text: false,
+            strict_names: false,
+            ignore_name_case: false,
+            honor_xml_space: false,
+            dialect: Dialect::Xml,
+            xml_leniency: (false, false, false),
+            max_tag_len: None,
+            max_depth: None,
+            max_text_len: None,
+            max_events: None,
+            attribute_options: AttributeOptions::DEFAULT,
+            cancel: None,
+            interner: None,
+            #[cfg(feature = "stats")]
+            stats: Stats::EMPTY,
+        }
+    }
+
+    /// Reads up to the next [`Text`] event, returning its decoded content as an owned [`String`].
+    ///
+    /// Any other events before it are skipped, and trimming follows the reader's settings.\
+    /// Entities are decoded as with [`Text::decoded`], and invalid UTF-8 is replaced
+    /// with `U+FFFD` (see [`String::from_utf8_lossy`]).
+    ///
+    /// Returns `Ok(None)` once the end of the input is reached.
+    pub fn take_text(&mut self) -> Result<Option<String>, Error> {
+        for event in self {
+            if let Event::Text(text) = event? {
+                return Ok(Some(String::from_utf8_lossy(&text.decoded()).into_owned()));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Reads the raw content of the element that was just started, up to its end tag.
+    ///
+    /// This must be called right after an [`Event::Start`]. Nested markup is kept as-is
+    /// and nothing is trimmed or decoded, which suits elements such as `<Script>` that hold code.
+    /// Only tags with the same name as the element are matched up,
+    /// so that the content doesn't need to be well-formed.\
+    /// The reader ends up past the end tag, like with [`Reader::skip_current_element`].
+    pub fn read_text(&mut self) -> Result<&'xml [u8], Error> {
+        let start = self.offset;
+        let end = self.skip_element(true)?;
+        Ok(sl_to(sl(self.source, start), end - start))
+    }
+
+    /// Gets the rest of the input that hasn't been read yet.
+    ///
+    /// This includes the `<` of the next tag if the reader already found it,
+    /// as it does when yielding a [`Text`] event.
+    pub fn remaining(&self) -> &'xml [u8] {
+        sl(self.source, self.remaining_start())
+    }
+
+    /// Returns an iterator over the names of the open elements, from the root inwards.
+    ///
+    /// Open elements are only tracked with [`Reader::check_structure`] enabled,
+    /// and there are none otherwise. After an [`Event::Start`], its own name comes last.
+    pub fn current_path(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &'xml [u8]> + ExactSizeIterator + '_ {
+        let source = self.source;
+        self.open.iter().map(move |x| &source[x.name.clone()])
+    }
+
+    /// Hands the rest of the input over to a new reader with the same settings,
+    /// ending this one.
+    ///
+    /// The new reader starts from scratch over [`Reader::remaining`], so its offsets are
+    /// relative to that, and it doesn't know about the elements that are still open.
+    /// This suits sending part of a document to another thread,
+    /// or handling binary data embedded in it by hand before carrying on.
+    pub fn split_off(&mut self) -> Reader<'xml, [u8]> {
+        let rest = self.remaining();
+        self.split_off_into(rest)
+    }
+
+    /// Reads the next event, skipping whatever the settings leave out.
+    ///
+    /// Before the end, `None` from a step means it skipped something, such as a comment
+    /// with [`Reader::emit_comments`] disabled. That's looped over rather than recursed into,
+    /// so that long runs of skipped markup can't overflow the stack.
+    fn next_event(&mut self) -> Option<Result<Event<'xml, [u8]>, Error>> {
+        loop {
+            let item = match self.state {
+                ReaderState::Searching => self.next_search(),
+                ReaderState::LocatedTag => self.next_tag(),
+                ReaderState::End => return self.next_end(),
+            };
+            if item.is_some() {
+                return item;
+            }
+        }
+    }
+
+    fn next_search(&mut self) -> Option<Result<Event<'xml, [u8]>, Error>> {
+        let source = sl(self.source, self.offset);
+        // With a limit, the next tag has to start at most right after the limit.
+        let haystack = match self.max_text_len {
+            Some(limit) if source.len() > limit => sl_to(source, limit + 1),
+            _ => source,
+        };
+        let lt = match self.raw_text.take() {
+            Some(name) => find_end_tag(source, &self.source[name]),
+            None => memchr(b'<', haystack),
+        };
+        if self
+            .max_text_len
+            .is_some_and(|limit| lt.unwrap_or(source.len()) > limit)
+        {
+            self.state = ReaderState::End;
+            self.open.clear();
+            return Some(Err(Error::LimitExceeded {
+                limit: Limit::TextLen,
+                offset: self.offset,
+            }));
+        }
+        let mut text = match lt {
+            Some(idx) => {
+                // We move 1 byte past '<' as we know that's what it is.
+                // This makes next access be worst-case &[] (safe).
+                self.offset += idx + 1;
+                self.state = ReaderState::LocatedTag;
+                sl_to(source, idx)
+            }
+            None => {
+                self.state = ReaderState::End;
+                source
+            }
+        };
+        let untrimmed = text;
+        if self.skip_bom {
+            text = skip_bom(text);
+        }
+        if self.trim && !self.preserves_space() {
+            text = trim_whitespace(text);
+        }
+        let located = matches!(self.state, ReaderState::LocatedTag);
+        if !text.is_empty() || (self.empty_text && located) {
+            self.event_start = self.offset_of(text);
+            self.event_end = self.event_start + text.len();
+            self.match_start = None;
+            let leading = self.event_start - self.offset_of(untrimmed);
+            Some(Ok(Event::Text(Text {
+                leading,
+                trailing: untrimmed.len() - text.len() - leading,
+                ..self.make_text(text)
+            })))
+        } else {
+            None
+        }
+    }
+
+    fn next_tag(&mut self) -> Option<Result<Event<'xml, [u8]>, Error>> {
+        let source = sl(self.source, self.offset);
+        let first_char = match source.first() {
+            Some(ch) => ch,
+            None => return self.tag_error(Error::UnexpectedEof, self.source.len()),
+        };
+        match first_char {
+            b'!' if source.starts_with(b"!--") => self.next_comment(),
+            b'!' if source.starts_with(b"![CDATA[") => self.next_cdata(),
+            b'!' if source.starts_with(b"!DOCTYPE") => self.next_doctype(),
+            b'!' => {
+                // Declarations such as `<!ELEMENT>` are only valid inside of a DOCTYPE.
+                match memchr(b'>', source) {
+                    Some(idx) => {
+                        let end = self.offset + idx + 1;
+                        self.tag_error(Error::InvalidName(self.offset - 1), end)
+                    }
+                    None => self.tag_error(Error::UnexpectedEof, self.source.len()),
+                }
+            }
+            b'?' => self.next_processing_instruction(),
+
+            // Standard Tags - Start / Empty / End
+            first => {
+                let is_end_tag = *first == b'/';
+                // With a limit, the closing `>` is at most right after the limit.
+                let haystack = match self.max_tag_len {
+                    Some(limit) if source.len() > limit + 1 => sl_to(source, limit + 1),
+                    _ => source,
+                };
+                match memchr(b'>', haystack) {
+                    Some(idx) => {
+                        // The inner content is the entire slice <[between]> the angle brackets.
+                        let inner = sl_to(source, idx);
+
+                        // Separate head & tail (tag name, attributes chunk).
+                        // (head, tail) of `<Name a="1"/>` is <[Name] [a="1"]/>
+                        // (head, tail) of `<Name />` is <[Name] []/>
+                        // (head, tail) of `<Name/>` is <[Name/][]>
+                        let (mut head, mut tail) = match inner.iter().position(|&ch| ch <= b' ') {
+                            Some(space) => (sl_to(inner, space), sl(inner, space + 1)),
+                            None => (inner, sl(inner, inner.len())),
+                        };
+
+                        // Trim `/` of `/>` in empty tags, unless it belongs to an unterminated value.
+                        let is_empty_tag = inner.last() == Some(&b'/') && !ends_in_value(tail);
+                        if is_empty_tag {
+                            // Note: Yes, this permits `</Name/>` on purpose as an end tag.
+                            // You *could* fix that with checking `is_end_tag`.
+                            if tail.is_empty() {
+                                head = sl_to(head, head.len() - 1);
+                            } else {
+                                tail = sl_to(tail, tail.len() - 1);
+                            }
+                        }
+
+                        // Trim `/` of `</` in end tags.
+                        if is_end_tag {
+                            if head.is_empty() {
+                                // A strange case of `</>` would lead here.
+                                let end = self.offset + idx + 1;
+                                return self.tag_error(Error::InvalidName(self.offset - 1), end);
+                            } else {
+                                head = sl(head, 1);
+                            }
+                        }
+
+                        // Yield tag if name is valid.
+                        if self.is_valid_name(head) {
+                            self.event_start = self.offset - 1;
+                            self.offset += idx + 1;
+                            self.event_end = self.offset;
+                            self.state = ReaderState::Searching;
+                            let tag = self.make_tag(head, tail);
+                            self.match_start = None;
+                            if is_end_tag {
+                                if self.dialect.is_void(head) {
+                                    return None;
+                                }
+                                if let (true, Some(open)) = (self.check_structure, self.open.last())
+                                {
+                                    let expected = &self.source[open.name.clone()];
+                                    if !self.names_eq(expected, head) {
+                                        return Some(Err(Error::MismatchedEnd {
+                                            expected: String::from_utf8_lossy(expected)
+                                                .into_owned(),
+                                            found: String::from_utf8_lossy(head).into_owned(),
+                                            offset: self.event_start,
+                                        }));
+                                    }
+                                }
+                                if self.depth != 0 {
+                                    self.depth -= 1;
+                                    if self.check_structure {
+                                        self.match_start = self.open.pop().map(|x| x.start);
+                                    }
+                                    if self.depth == 0 && self.stop_after_root {
+                                        self.state = ReaderState::End;
+                                    }
+                                } else if self.check_structure {
+                                    return Some(Err(Error::UnexpectedEndTag {
+                                        name: String::from_utf8_lossy(head).into_owned(),
+                                        offset: self.event_start,
+                                    }));
+                                }
+                                Some(Ok(Event::End(tag)))
+                            } else if is_empty_tag || self.dialect.is_void(head) {
+                                if self.depth == 0 && self.stop_after_root {
+                                    self.state = ReaderState::End;
+                                }
+                                Some(Ok(Event::Empty(tag)))
+                            } else {
+                                if self.max_depth.is_some_and(|max| self.depth >= max) {
+                                    self.state = ReaderState::End;
+                                    self.open.clear();
+                                    return Some(Err(Error::LimitExceeded {
+                                        limit: Limit::Depth,
+                                        offset: self.event_start,
+                                    }));
+                                }
+                                self.depth += 1;
+                                if self.honor_xml_space {
+                                    self.push_xml_space(&tag);
+                                }
+                                let name_start = self.event_start + 1;
+                                let name = name_start..name_start + head.len();
+                                if self.dialect.is_raw_text(head) {
+                                    self.raw_text = Some(name.clone());
+                                }
+                                if self.check_structure {
+                                    self.open.push(OpenElement {
+                                        start: self.event_start,
+                                        name,
+                                    });
+                                }
+                                Some(Ok(Event::Start(tag)))
+                            }
+                        } else {
+                            let end = self.offset + idx + 1;
+                            self.tag_error(Error::InvalidName(self.offset - 1), end)
+                        }
+                    }
+                    None if haystack.len() < source.len() => {
+                        let error = Error::LimitExceeded {
+                            limit: Limit::TagLen,
+                            offset: self.offset - 1,
+                        };
+                        self.tag_error(error, self.source.len())
+                    }
+                    None => self.tag_error(Error::UnexpectedEof, self.source.len()),
+                }
+            }
+        }
+    }
+
+    fn next_comment(&mut self) -> Option<Result<Event<'xml, [u8]>, Error>> {
+        // Skip the `!--` to not match `-->` in `<!-->`.
+        let source = sl(self.source, self.offset + 3);
+        match find(source, b"-->") {
+            Some(idx) => {
+                let content = sl_to(source, idx);
+                self.markup_end(3 + idx + 3);
+                if !self.comments {
+                    return None;
+                }
+                Some(Ok(Event::Comment(self.make_text(content))))
+            }
+            None => self.tag_error(Error::UnexpectedEof, self.source.len()),
+        }
+    }
+
+    fn next_cdata(&mut self) -> Option<Result<Event<'xml, [u8]>, Error>> {
+        let source = sl(self.source, self.offset + 8);
+        match find(source, b"]]>") {
+            Some(idx) => {
+                let content = sl_to(source, idx);
+                self.markup_end(8 + idx + 3);
+                Some(Ok(Event::CData(self.make_text(content))))
+            }
+            None => self.tag_error(Error::UnexpectedEof, self.source.len()),
+        }
+    }
+
+    fn next_doctype(&mut self) -> Option<Result<Event<'xml, [u8]>, Error>> {
+        let source = sl(self.source, self.offset + 8);
+        // The internal subset may contain `>` in declarations, comments and quoted strings.
+        let (mut depth, mut quote) = (0usize, None);
+        let mut idx = 0;
+        while let Some(&ch) = source.get(idx) {
+            match (quote, ch) {
+                (Some(q), ch) if q == ch => quote = None,
+                (Some(_), _) => (),
+                (None, b'"' | b'\'') => quote = Some(ch),
+                (None, b'[') => depth += 1,
+                (None, b']') => depth = depth.saturating_sub(1),
+                (None, b'>') if depth == 0 => {
+                    let content = sl_to(source, idx);
+                    self.markup_end(8 + idx + 1);
+                    return Some(Ok(Event::DocType(self.make_text(content))));
+                }
+                (None, b'<') if sl(source, idx).starts_with(b"<!--") => {
+                    idx += find(sl(source, idx + 4), b"-->").map_or(source.len(), |x| x + 6);
+                }
+                _ => (),
+            }
+            idx += 1;
+        }
+        self.tag_error(Error::UnexpectedEof, self.source.len())
+    }
+
+    fn next_processing_instruction(&mut self) -> Option<Result<Event<'xml, [u8]>, Error>> {
+        let source = sl(self.source, self.offset + 1);
+        match find(source, b"?>") {
+            Some(idx) => {
+                // (head, tail) of `<?xml version="1.0"?>` is <?[xml] [version="1.0"]?>
+                let inner = sl_to(source, idx);
+                let (head, tail) = match inner.iter().position(|&ch| ch <= b' ') {
+                    Some(space) => (sl_to(inner, space), sl(inner, space + 1)),
+                    None => (inner, sl(inner, inner.len())),
+                };
+                if self.is_valid_name(head) {
+                    self.markup_end(1 + idx + 2);
+                    let tag = self.make_tag(head, tail);
+                    Some(Ok(Event::ProcessingInstruction(tag)))
+                } else {
+                    let end = self.offset + 1 + idx + 2;
+                    self.tag_error(Error::InvalidName(self.offset - 1), end)
+                }
+            }
+            None => self.tag_error(Error::UnexpectedEof, self.source.len()),
+        }
+    }
+
+    /// Updates the state after reading markup other than a start/end/empty tag,
+    /// which is `len` bytes long not counting the opening angle bracket `<`.
+    fn markup_end(&mut self, len: usize) {
+        self.event_start = self.offset - 1;
+        self.offset += len;
+        self.event_end = self.offset;
+        self.match_start = None;
+        self.state = ReaderState::Searching;
+    }
+
+    fn is_valid_name(&self, name: &[u8]) -> bool {
+        is_valid_tag_name(name)
+            && (!self.strict_names
+                || sl(name, 1).iter().all(|&ch| {
+                    ch.is_ascii_alphanumeric() || matches!(ch, b'-' | b'.' | b'_' | b':' | 0x80..)
+                }))
+    }
+
+    fn offset_of(&self, slice: &[u8]) -> usize {
+        slice.as_ptr() as usize - self.source.as_ptr() as usize
+    }
+
+    /// Builds a tag for the event that was just read, which has to be spanned already.
+    fn make_tag(&self, name: &'xml [u8], content: &'xml [u8]) -> Tag<'xml, [u8]> {
+        Tag {
+            options: self.attribute_options,
+            start: self.event_start,
+            end: self.event_end,
+            content_start: self.offset_of(content),
+            symbol: self
+                .interner
+                .and_then(|interner| interner.get(name))
+                .and_then(|symbol| NonZeroU32::new(symbol + 1)),
+            ..Tag::new(name, content)
+        }
+    }
+
+    fn make_text(&self, content: &'xml [u8]) -> Text<'xml, [u8]> {
+        let start = self.offset_of(content);
+        Text {
+            start,
+            leading: start - self.event_start,
+            trailing: self.event_end - start - content.len(),
+            ..Text::new(content)
+        }
+    }
+
+    /// Checks whether whitespace is preserved at the current depth.
+    ///
+    /// Entries deeper than the reader are left over from elements that have been closed since.
+    fn preserves_space(&mut self) -> bool {
+        if !self.honor_xml_space {
+            return false;
+        }
+        while self.xml_space.last().is_some_and(|x| x.0 > self.depth) {
+            self.xml_space.pop();
+        }
+        self.xml_space.last().is_some_and(|x| x.1)
+    }
+
+    /// Records the `xml:space` attribute of the element that was just started, if any.
+    fn push_xml_space(&mut self, tag: &Tag<'xml, [u8]>) {
+        while self.xml_space.last().is_some_and(|x| x.0 >= self.depth) {
+            self.xml_space.pop();
+        }
+        let attr = tag
+            .attributes()
+            .map_while(Result::ok)
+            .find(|attr| attr.key() == b"xml:space");
+        match attr.map(|attr| attr.value()) {
+            Some(b"preserve") => self.xml_space.push((self.depth, true)),
+            Some(b"default") => self.xml_space.push((self.depth, false)),
+            _ => (),
+        }
+    }
+
+    /// Yields the elements left open once the end is reached, if checking structure.
+    fn next_end(&mut self) -> Option<Result<Event<'xml, [u8]>, Error>> {
+        if self.open.is_empty() {
+            return None;
+        }
+        let source = self.source;
+        let names = self
+            .open
+            .drain(..)
+            .map(|x| String::from_utf8_lossy(&source[x.name]).into_owned());
+        Some(Err(Error::UnclosedTags(names.collect())))
+    }
+
+    /// Yields an error for the tag being read, or salvages it as text in recover mode.
+    ///
+    /// `end` is the offset one past the last byte belonging to the broken tag.
+    fn tag_error(&mut self, error: Error, end: usize) -> Option<Result<Event<'xml, [u8]>, Error>> {
+        if !self.recover {
+            // The reader can't tell where to carry on, so it ends here.
+            self.state = ReaderState::End;
+            self.open.clear();
+            return Some(Err(error));
+        }
+        #[cfg(feature = "stats")]
+        {
+            self.stats.recovered += 1;
+        }
+        // The reader is one past the opening angle bracket `<`, which is salvaged too.
+        self.event_start = self.offset - 1;
+        self.event_end = end;
+        self.offset = end;
+        self.state = if end < self.source.len() {
+            ReaderState::Searching
+        } else {
+            ReaderState::End
+        };
+        let raw = sl_to(sl(self.source, self.event_start), end - self.event_start);
+        Some(Ok(Event::Text(self.make_text(raw))))
+    }
+}
+
+impl<'xml> Reader<'xml, str> {
+    /// Constructs a new [`Reader`] over `xml`, resuming at `offset`.
+    ///
+    /// This is meant to continue from a previous [`Reader::offset`] with the same source.\
+    /// If `offset` directly follows a `<`, parsing resumes inside that tag,
+    /// which is where [`Reader::offset`] points after a [`Text`] event.
+    ///
+    /// Returns `None` if `offset` is past the end of the input or inside a UTF-8 sequence.
+    pub fn from_parts(xml: &'xml str, offset: usize, trim: bool) -> Option<Self> {
+        match xml.is_char_boundary(offset) {
+            true => Reader::resume(xml, offset, trim),
+            false => None,
+        }
+    }
+
+    /// Constructs a new [`Reader`] from a UTF-8 string.
+    pub const fn from_str(xml: &'xml str) -> Reader<'xml, str> {
+        Reader {
+            state: ReaderState::Searching,
+            source: xml,
+            offset: 0,
+            event_start: 0,
+            event_end: 0,
+            depth: 0,
+            open: Vec::new(),
+            match_start: None,
+            raw_text: None,
+            xml_space: Vec::new(),
+            events: 0,
+            failed: false,
+
+            trim: true,
+            skip_bom: false,
+            recover: false,
+            check_structure: false,
+            stop_after_root: false,
+            comments: true,
+            empty_text: false,
+            strict_names: false,
+            ignore_name_case: false,
+            honor_xml_space: false,
+            dialect: Dialect::Xml,
+            xml_leniency: (false, false, false),
+            max_tag_len: None,
+            max_depth: None,
+            max_text_len: None,
+            max_events: None,
+            attribute_options: AttributeOptions::DEFAULT,
+            cancel: None,
+            interner: None,
+            #[cfg(feature = "stats")]
+            stats: Stats::EMPTY,
+        }
+    }
+
+    /// Constructs a new [`Reader`] over a fragment of XML, such as a snippet stored in a database.
+    ///
+    /// This is the same as [`Reader::from_str`], spelling out a guarantee that always holds:
+    /// any number of top-level elements is fine, and so is text before, between and after them.
+    /// Nothing is assumed about a root element unless [`Reader::stop_after_root`] is enabled,
+    /// and checking the structure only concerns how elements nest.
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::Reader;
+    ///
+    /// let mut reader = Reader::from_fragment("Hello <b>bold</b> and <i/> world");
+    /// reader.check_structure(true);
+    /// assert_eq!(reader.map(Result::unwrap).count(), 7);
+    /// ```
+    #[inline]
+    pub const fn from_fragment(xml: &'xml str) -> Reader<'xml, str> {
+        Self::from_str(xml)
+    }
+
+    /// Constructs a new [`Reader`] from a UTF-8 string, stripping the BOM if it's present.
+    #[inline]
+    pub fn from_str_bom(xml: &'xml str) -> Reader<'xml, str> {
+        Self::from_str(xml.trim_start_matches('\u{feff}'))
+    }
+
+    /// Reads up to the next [`Text`] event, returning its decoded content as an owned [`String`].
+    ///
+    /// See [`Reader::<[u8]>::take_text`](Reader::take_text) for details.
+    pub fn take_text(&mut self) -> Result<Option<String>, Error> {
+        for event in self {
+            if let Event::Text(text) = event? {
+                return Ok(Some(text.decoded().into_owned()));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Reads the raw content of the element that was just started, up to its end tag.
+    ///
+    /// See [`Reader::<[u8]>::read_text`](Reader::read_text) for details.
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::Reader;
+    ///
+    /// let mut reader = Reader::from_str("<Script> if (a <b> c) {} </Script>");
+    /// reader.next(); // <Script>
+    /// assert_eq!(reader.read_text().unwrap(), " if (a <b> c) {} ");
+    /// ```
+    pub fn read_text(&mut self) -> Result<&'xml str, Error> {
+        let start = self.offset;
+        let end = self.skip_element(true)?;
+        Ok(&self.source[start..end])
+    }
+
+    /// Gets the rest of the input that hasn't been read yet.
+    ///
+    /// See [`Reader::<[u8]>::remaining`](Reader::remaining) for details.
+    pub fn remaining(&self) -> &'xml str {
+        &self.source[self.remaining_start()..]
+    }
+
+    /// Returns an iterator over the names of the open elements, from the root inwards.
+    ///
+    /// See [`Reader::<[u8]>::current_path`](Reader::current_path) for details.
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::{Event, Reader};
+    ///
+    /// let mut reader = Reader::from_str("<Config><Graphics><Shadows/></Graphics></Config>");
+    /// reader.check_structure(true);
+    /// while let Some(event) = reader.next() {
+    ///     if let Event::Empty(tag) = event.unwrap() {
+    ///         assert_eq!(tag.name(), "Shadows");
+    ///         assert!(reader.current_path().eq(["Config", "Graphics"]));
+    ///     }
+    /// }
+    /// assert_eq!(reader.current_path().len(), 0);
+    /// ```
+    pub fn current_path(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &'xml str> + ExactSizeIterator + '_ {
+        let source = self.source;
+        self.open.iter().map(move |x| &source[x.name.clone()])
+    }
+
+    /// Hands the rest of the input over to a new reader with the same settings,
+    /// ending this one.
+    ///
+    /// See [`Reader::<[u8]>::split_off`](Reader::split_off) for details.
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::{Event, Reader};
+    ///
+    /// let mut reader = Reader::from_str("<Header/><Body><Item/></Body>");
+    /// reader.next(); // <Header/>
+    /// let rest = reader.split_off();
+    /// assert_eq!(rest.remaining(), "<Body><Item/></Body>");
+    /// assert!(reader.next().is_none());
+    /// assert_eq!(rest.count(), 3);
+    /// ```
+    pub fn split_off(&mut self) -> Reader<'xml, str> {
+        let rest = self.remaining();
+        self.split_off_into(rest)
+    }
+}
+
+impl<'xml> Iterator for Reader<'xml, [u8]> {
+    type Item = Result<Event<'xml, [u8]>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(cancel) = &self.cancel {
+            if cancel.load(Ordering::Relaxed) && !matches!(self.state, ReaderState::End) {
+                self.state = ReaderState::End;
+                self.open.clear();
+                self.failed = true;
+                return Some(Err(Error::Cancelled));
+            }
+        }
+        if let Some(max) = self.max_events {
+            if self.events >= max && !matches!(self.state, ReaderState::End) {
+                let offset = self.remaining_start();
+                self.state = ReaderState::End;
+                self.open.clear();
+                self.failed = true;
+                return Some(Err(Error::LimitExceeded {
+                    limit: Limit::Events,
+                    offset,
+                }));
+            }
+        }
+        let item = self.next_event();
+        #[cfg(feature = "stats")]
+        self.record(&item);
+        self.events += item.is_some() as usize;
+        if let Some(Err(_)) = item {
+            self.failed |= matches!(self.state, ReaderState::End);
+        }
+        item
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every event takes up at least a byte, except for the final error about unclosed tags,
+        // or the one after cancelling.
+        // Even a located tag might be skipped, such as a comment, so there's no lower bound.
+        match self.state {
+            ReaderState::End => {
+                let left = !self.open.is_empty() as usize;
+                (left, Some(left))
+            }
+            ReaderState::LocatedTag | ReaderState::Searching => {
+                (0, Some(self.source.len() - self.offset + 1))
+            }
+        }
+    }
+}
+
+impl<'xml> Iterator for Reader<'xml, str> {
+    type Item = Result<Event<'xml, str>, Error>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        // SAFETY: Identical layout, contents, and that's how the standard library does it too.
+        unsafe {
+            let reader = mem::transmute::<&mut Self, &mut Reader<'xml, [u8]>>(self);
+            let event = reader.next();
+            if let Some(event) = &event {
+                debug_assert_event_utf8(event);
+            }
+            mem::transmute::<Option<Result<Event<'xml, [u8]>, Error>>, Option<Self::Item>>(event)
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // SAFETY: Identical layout, contents, and that's how the standard library does it too.
+        unsafe { mem::transmute::<&Self, &Reader<'xml, [u8]>>(self) }.size_hint()
+    }
+}
+
+impl<'xml> FusedIterator for Reader<'xml, [u8]> {}
+impl<'xml> FusedIterator for Reader<'xml, str> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn text_normalized_eol() {
+        let text = Text::new("a\r\nb\rc\n\r\r\nd\r");
+        assert_eq!(text.content_normalized_eol(), "a\nb\nc\n\n\nd\n");
+        let text = Text::new(&b"no carriage returns\n"[..]);
+        assert!(matches!(text.content_normalized_eol(), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn reader_is_finished() {
+        let mut reader = Reader::from_str("<a>text");
+        reader.check_structure(true);
+        assert!(!reader.is_finished());
+        reader.next(); // <a>
+        reader.next(); // text
+        assert!(!reader.is_finished());
+        assert!(matches!(reader.next(), Some(Err(Error::UnclosedTags(_)))));
+        assert!(reader.next().is_none());
+        assert!(!reader.is_finished());
+
+        let mut reader = Reader::from_str("<a/>");
+        reader.by_ref().for_each(drop);
+        assert!(reader.is_finished());
+
+        let mut reader = Reader::from_str("<a>text</a");
+        reader.by_ref().for_each(drop);
+        assert!(reader.next().is_none());
+        assert!(!reader.is_finished());
+    }
+
+    #[test]
+    fn reader_take_text() {
+        let mut reader = Reader::from_str("<a> one &amp; </a><b/>two<c");
+        assert_eq!(reader.take_text().unwrap().as_deref(), Some("one &"));
+        assert_eq!(reader.take_text().unwrap().as_deref(), Some("two"));
+        assert!(matches!(reader.take_text(), Err(Error::UnexpectedEof)));
+
+        let mut reader = Reader::from_bytes(b"<a>\xFF&lt;</a>");
+        assert_eq!(reader.take_text().unwrap().as_deref(), Some("\u{FFFD}<"));
+        assert_eq!(reader.take_text().unwrap(), None);
+    }
+
+    #[test]
+    fn attribute_multiline_values() {
+        let tag =
+            "<Script time=\"0\"a\"'\"''\"'\"\"'''32'34fdhfjsklflsjeje2!!!!!=\"e\"what\n='\n   '/>";
+        let tag = match Reader::from_str(tag).next() {
+            Some(Ok(Event::Empty(tag))) => tag,
+            x => panic!("expected empty tag, got {:?}", x),
+        };
+        let attrs = tag
+            .attributes()
+            .map(|x| x.map(|a| (a.key(), a.value())))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            attrs,
+            [
+                ("time", "0"),
+                ("a\"'\"''\"'\"\"'''32'34fdhfjsklflsjeje2!!!!!", "e"),
+                ("what", "\n   "),
+            ]
+        );
+
+        // Values spanning several lines don't throw off the following attributes.
+        let mut iter = AttributeIter::new("a=\"1\n2\r\n3\"\nb='\n'\n c=\"4\" d=\"\n");
+        let attr = iter.next().unwrap().unwrap();
+        assert_eq!((attr.key(), attr.value()), ("a", "1\n2\r\n3"));
+        let attr = iter.next().unwrap().unwrap();
+        assert_eq!((attr.key(), attr.value()), ("b", "\n"));
+        let attr = iter.next().unwrap().unwrap();
+        assert_eq!((attr.key(), attr.value()), ("c", "4"));
+        assert!(matches!(
+            iter.next(),
+            Some(Err(Error::InvalidAttribute(24)))
+        ));
+    }
+
+    #[test]
+    fn reader_indexed() {
+        let offsets = Reader::from_str("  <a x=\"1\">\n text </a> <b/>")
+            .indexed()
+            .map(|x| x.map(|(offset, _)| offset))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(offsets, [2, 13, 18, 23]);
+    }
+
+    #[test]
+    fn reader_cancel() {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let mut reader = Reader::from_str("<a>text</a>");
+        reader.with_cancel(Arc::clone(&cancel));
+        assert!(matches!(reader.next(), Some(Ok(Event::Start(_)))));
+        cancel.store(true, Ordering::Relaxed);
+        assert!(matches!(reader.next(), Some(Err(Error::Cancelled))));
+        assert!(reader.next().is_none());
+
+        // Consuming adapters check the flag between events too.
+        let cancel = Arc::new(AtomicBool::new(false));
+        let mut reader = Reader::from_str("<a><b/><c/></a>");
+        reader.with_cancel(Arc::clone(&cancel));
+        let mut events = 0;
+        reader.for_each(|event| {
+            events += event.is_ok() as usize;
+            cancel.store(true, Ordering::Relaxed);
+        });
+        assert_eq!(events, 1);
+    }
+
+    #[test]
+    fn tag_attributes_with_prefix() {
+        let tag = Tag::new("div", "id=\"x\" data-a=\"1\" xlink:href=\"#\" data-b='2'");
+        let keys = tag
+            .attributes_with_prefix("data-")
+            .map(|x| x.map(|attr| attr.key()))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(keys, ["data-a", "data-b"]);
+        let mut iter = tag.attributes_with_prefix("missing");
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn reader_recover_salvages_tags() {
+        let mut reader = Reader::from_str("<a =bad><0bad x='1'> text </><b");
+        reader.recover(true);
+        match reader.next() {
+            Some(Ok(Event::Start(tag))) => {
+                assert_eq!((tag.name(), tag.content()), ("a", "=bad"));
+                assert!(tag.attributes().next().is_none());
+            }
+            x => panic!("expected start tag, got {:?}", x),
+        }
+        let texts = reader
+            .map(|x| match x {
+                Ok(Event::Text(text)) => text.content(),
+                x => panic!("expected text, got {:?}", x),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(texts, ["<0bad x='1'>", "text", "</>", "<b"]);
+    }
+
+    #[test]
+    fn reader_from_parts() {
+        let xml = "<a>text</a><b/>";
+        let mut reader = Reader::from_str(xml);
+        reader.nth(1);
+        let mut resumed = Reader::<str>::from_parts(xml, reader.offset(), true).unwrap();
+        assert!(matches!(resumed.next(), Some(Ok(Event::End(_)))));
+        assert!(matches!(resumed.next(), Some(Ok(Event::Empty(_)))));
+
+        assert!(Reader::<str>::from_parts(xml, xml.len() + 1, true).is_none());
+        assert!(Reader::<str>::from_parts("<é/>", 2, true).is_none());
+        assert!(Reader::<[u8]>::from_parts(&b"<a/>"[..], 4, true)
+            .unwrap()
+            .next()
+            .is_none());
+
+        // Bytes aren't UTF-8, so any offset in bounds is fine.
+        let xml = b"<a>\xA0caf\xC3\xA9</a><b/>";
+        let mut resumed = Reader::<[u8]>::from_parts(&xml[..], 3, true).unwrap();
+        assert!(matches!(resumed.next(), Some(Ok(Event::Text(_)))));
+        assert_eq!(resumed.count(), 2);
+    }
+
+    #[test]
+    fn reader_skip_bom_per_document() {
+        let xml = "\u{feff}<a>1</a>\r\n\u{feff}<b>2</b>";
+        let mut reader = Reader::from_str(xml);
+        reader.skip_bom_per_document(true).trim_whitespace(false);
+        let count = reader.filter(|x| matches!(x, Ok(Event::Text(_)))).count();
+        assert_eq!(count, 2);
+
+        let reader = Reader::from_str(xml);
+        let count = reader.filter(|x| matches!(x, Ok(Event::Text(_)))).count();
+        assert_eq!(count, 4);
+    }
+
+    #[test]
+    fn const_lengths() {
+        const TAG: Tag<'static, str> = Tag::new("Name", "a=\"1\"");
+        const LENGTHS: [usize; 4] = [
+            TAG.name_len(),
+            TAG.content_len(),
+            Attribute::<[u8]>::new(b"key", b"").key_len(),
+            Text::<[u8]>::new(b"text").content_len(),
+        ];
+        assert_eq!(LENGTHS, [4, 5, 3, 4]);
+    }
+
+    #[test]
+    fn attributes_strict_quoting() {
+        let attrs = |iter: AttributeIter<'static, str>| {
+            iter.map(|x| x.map(|attr| (attr.key(), attr.value())))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let lenient = AttributeIter::new("a=1 b=\"2\"");
+        assert_eq!(attrs(lenient.clone()).unwrap(), [("a", "2")]);
+        assert!(matches!(
+            attrs(lenient.strict(true)),
+            Err(Error::InvalidAttribute(2))
+        ));
+        assert!(attrs(AttributeIter::new("a = '1' b=\"2\"").strict(true)).is_ok());
+
+        let mut reader = Reader::from_str("<a x=1/>");
+        reader.strict_attributes(true);
+        match reader.next() {
+            Some(Ok(Event::Empty(tag))) => {
+                assert!(matches!(
+                    tag.attributes().next(),
+                    Some(Err(Error::InvalidAttribute(2)))
+                ))
+            }
+            x => panic!("expected empty tag, got {:?}", x),
+        }
+    }
+
+    #[test]
+    fn reader_drain_into() {
+        let mut buf = Vec::new();
+        Reader::from_str("<a x='1'>hi</a>")
+            .drain_into(&mut buf)
+            .unwrap();
+        assert_eq!(buf.len(), 3);
+        let capacity = buf.capacity();
+        Reader::from_str("<b/>").drain_into(&mut buf).unwrap();
+        assert_eq!(buf.capacity(), capacity);
+        match &buf[..] {
+            [OwnedEvent::Empty(tag)] => assert_eq!(tag.name(), "b"),
+            x => panic!("expected one empty tag, got {:?}", x),
+        }
+        assert!(Reader::from_str("<c>text<").drain_into(&mut buf).is_err());
+        assert_eq!(buf.len(), 2);
+    }
+
+    #[test]
+    fn reader_last_event_end() {
+        let xml = "<a> text </a>";
+        let mut reader = Reader::from_str(xml);
+        assert_eq!(reader.last_event_end(), 0);
+        let mut ends = Vec::new();
+        while let Some(Ok(_)) = reader.next() {
+            ends.push(reader.last_event_end());
+        }
+        assert_eq!(ends, [3, 8, 13]);
+        assert_eq!(&xml[4..ends[1]], "text");
+    }
+
+    #[test]
+    fn tag_name_str() {
+        let tag = Tag::<[u8]>::new(b"Frame", b"");
+        assert_eq!(tag.name_str(), Ok("Frame"));
+        assert!(matches!(tag.name_str_lossy(), Cow::Borrowed("Frame")));
+        let tag = Tag::<[u8]>::new(b"Fr\xFFme", b"");
+        assert!(tag.name_str().is_err());
+        assert_eq!(tag.name_str_lossy(), "Fr\u{FFFD}me");
+    }
+
+    #[test]
+    fn reader_empty_tag_quote_aware() {
+        let value = |event: Option<Result<Event<'static, str>, Error>>| match event {
+            Some(Ok(Event::Empty(tag))) => tag.attributes().next().unwrap().unwrap().value(),
+            x => panic!("expected empty tag, got {:?}", x),
+        };
+        assert_eq!(value(Reader::from_str("<a href=\"dir/\"/>").next()), "dir/");
+        assert_eq!(value(Reader::from_str("<a href=\"x\"/>").next()), "x");
+        assert_eq!(value(Reader::from_str("<a b=\"1\" c='/'/>").next()), "1");
+
+        // The trailing `/` is part of the value, so this isn't self-closing.
+        match Reader::from_str("<a href=\"dir/>").next() {
+            Some(Ok(Event::Start(tag))) => assert_eq!(tag.content(), "href=\"dir/"),
+            x => panic!("expected start tag, got {:?}", x),
+        }
+    }
+
+    #[test]
+    fn reader_unexpected_end_tag() {
+        let xml = "<a></a></b><c/>";
+        assert!(Reader::from_str(xml).all(|x| x.is_ok()));
+
+        let mut reader = Reader::from_str(xml);
+        reader.check_structure(true);
+        assert!(matches!(reader.next(), Some(Ok(Event::Start(_)))));
+        assert!(matches!(reader.next(), Some(Ok(Event::End(_)))));
+        match reader.next() {
+            Some(Err(Error::UnexpectedEndTag { name, offset })) => {
+                assert_eq!((name.as_str(), offset), ("b", 7))
+            }
+            x => panic!("expected unexpected end tag, got {:?}", x),
+        }
+        assert!(matches!(reader.next(), Some(Ok(Event::Empty(_)))));
+    }
+
+    #[test]
+    fn root_element_name_prolog() {
+        assert_eq!(root_element_name("<Root><Child/></Root>"), Some("Root"));
+        assert_eq!(
+            root_element_name("\n <?xml version='1.0'?><!----><Root a='>'>"),
+            Some("Root")
+        );
+        assert_eq!(
+            root_element_name("<!DOCTYPE x><!-- <Fake> --><Root/>"),
+            Some("Root")
+        );
+        assert_eq!(root_element_name("text only"), None);
+        assert_eq!(root_element_name("</End>"), None);
+        assert_eq!(root_element_name("<!-- unterminated <Root/>"), None);
+        assert_eq!(root_element_name(""), None);
+    }
+
+    #[test]
+    fn attribute_value_eq_decoded() {
+        let eq = |value, other| Attribute::new("a", value).value_eq_decoded(other);
+        assert!(eq("&amp;b", "&b"));
+        assert!(eq("&lt;&gt;&quot;&apos;", "<>\"'"));
+        assert!(eq("&#233;t&#xE9;", "été"));
+        assert!(eq("a &unknown; &amp b", "a &unknown; &amp b"));
+        assert!(eq("&#x110000;", "&#x110000;"));
+        assert!(!eq("&amp;b", "&amp;b"));
+        assert!(!eq("&amp;b", "&bc"));
+        assert!(!eq("abc", "ab"));
+        assert!(eq("", ""));
+    }
+
+    #[test]
+    fn reader_stop_after_root() {
+        let mut reader = Reader::from_str("<a><b></b></a>trailing</junk><c/>");
+        reader.stop_after_root(true);
+        assert_eq!(reader.count(), 4);
+
+        let mut reader = Reader::from_str("text <a/> more <b/>");
+        reader.stop_after_root(true);
+        assert_eq!(reader.count(), 2);
+    }
+
+    #[test]
+    fn attributes_max_value_len() {
+        let content = "a=\"1234\" b='12345'";
+        let mut iter = AttributeIter::new(content).max_value_len(Some(4));
+        assert_eq!(iter.next().unwrap().unwrap().value(), "1234");
+        assert!(matches!(
+            iter.next(),
+            Some(Err(Error::ValueTooLong { offset: 9 }))
+        ));
+
+        let mut reader = Reader::from_str("<a b='12345'/>");
+        reader.max_attribute_value_len(Some(5));
+        match reader.next() {
+            Some(Ok(Event::Empty(tag))) => assert!(tag.attributes().all(|x| x.is_ok())),
+            x => panic!("expected empty tag, got {:?}", x),
+        }
+    }
+
+    #[test]
+    fn text_decode_chunks() {
+        let text = Text::new("a &lt; b &unknown; &amp&amp;c&#33;");
+        let chunks = text.decode_chunks().collect::<Vec<_>>();
+        assert_eq!(chunks, ["a ", "<", " b &unknown; &amp", "&", "c", "!"]);
+        assert!(matches!(chunks[0], Cow::Borrowed(_)));
+        assert!(matches!(chunks[1], Cow::Owned(_)));
+
+        let text = Text::<[u8]>::new(b"&gt;&gt;");
+        assert_eq!(text.decode_chunks().collect::<Vec<_>>(), [&b">"[..], b">"]);
+        assert_eq!(Text::new("").decode_chunks().count(), 0);
+    }
+
+    #[test]
+    fn reader_classify_element() {
+        let xml = "<r><a></a><b> x </b><c>\n<d>y</d>\n</c><e>z<f/></e><g>";
+        let mut reader = Reader::from_str(xml);
+        let mut models = Vec::new();
+        while let Some(event) = reader.next() {
+            if let Event::Start(tag) = event.unwrap() {
+                models.push((tag.name(), reader.classify_element()));
+            }
+        }
+        let models = models
+            .into_iter()
+            .map(|(name, model)| (name, model.ok()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            models,
+            [
+                ("r", None),
+                ("a", Some(ContentModel::Empty)),
+                ("b", Some(ContentModel::Text)),
+                ("c", Some(ContentModel::Elements)),
+                ("d", Some(ContentModel::Text)),
+                ("e", Some(ContentModel::Mixed)),
+                ("g", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn reader_children_nested() {
+        let xml = "<r><a><x>1</x><y/></a> t <b><z/></b><c/></r><after/>";
+        let mut reader = Reader::from_str(xml);
+        reader.next();
+        let mut names = Vec::new();
+        let mut children = reader.children();
+        while let Some(event) = children.next() {
+            match event.unwrap() {
+                Event::Start(tag) if tag.name() == "a" => {
+                    let mut nested = children.children();
+                    names.push(tag.name());
+                    // Only look at the first grandchild, the rest is skipped by the parent.
+                    match nested.next() {
+                        Some(Ok(Event::Start(tag))) => names.push(tag.name()),
+                        x => panic!("expected start tag, got {:?}", x),
+                    }
+                }
+                Event::Start(tag) | Event::Empty(tag) => names.push(tag.name()),
+                Event::Text(text) => names.push(text.content()),
+                x => panic!("unexpected event {:?}", x),
+            }
+        }
+        assert_eq!(names, ["a", "x", "t", "b", "c"]);
+        assert!(matches!(reader.next(), Some(Ok(Event::Empty(_)))));
+        assert!(reader.next().is_none());
+
+        let mut reader = Reader::from_str("<r><a/>");
+        reader.next();
+        let mut children = reader.children();
+        assert!(matches!(children.next(), Some(Ok(Event::Empty(_)))));
+        assert!(matches!(children.next(), Some(Err(Error::UnexpectedEof))));
+        assert!(children.next().is_none());
+    }
+
+    #[test]
+    fn reader_current_match_start() {
+        let xml = "<a> <b>text</b> <c/> </a>";
+        let mut reader = Reader::from_str(xml);
+        reader.check_structure(true);
+        let mut spans = Vec::new();
+        while let Some(event) = reader.next() {
+            match event.unwrap() {
+                Event::End(_) => {
+                    let start = reader.current_match_start().unwrap();
+                    spans.push(&xml[start..reader.last_event_end()]);
+                }
+                _ => assert_eq!(reader.current_match_start(), None),
+            }
+        }
+        assert_eq!(spans, ["<b>text</b>", xml]);
+    }
+
+    #[test]
+    fn reader_comments() {
+        let xml = "<a><!-- <b> -> > --><!----></a><!--";
+        let mut reader = Reader::from_str(xml);
+        assert!(matches!(reader.next(), Some(Ok(Event::Start(_)))));
+        for content in &[" <b> -> > ", ""] {
+            match reader.next() {
+                Some(Ok(Event::Comment(text))) => assert_eq!(text.content(), *content),
+                x => panic!("expected comment, got {:?}", x),
+            }
+        }
+        assert_eq!(reader.last_event_end(), 27);
+        assert!(matches!(reader.next(), Some(Ok(Event::End(_)))));
+        assert!(matches!(reader.next(), Some(Err(Error::UnexpectedEof))));
+    }
+
+    #[test]
+    fn reader_processing_instructions() {
+        let xml = "<?xml version=\"1.0\" encoding='UTF-8'?>\n<?empty?><a/><? bad?>";
+        let mut reader = Reader::from_str(xml);
+        match reader.next() {
+            Some(Ok(Event::ProcessingInstruction(tag))) => {
+                assert_eq!(tag.name(), "xml");
+                let mut attrs = tag.attributes();
+                let version = attrs.next().unwrap().unwrap();
+                assert_eq!((version.key(), version.value()), ("version", "1.0"));
+                let encoding = attrs.next().unwrap().unwrap();
+                assert_eq!((encoding.key(), encoding.value()), ("encoding", "UTF-8"));
+                assert!(attrs.next().is_none());
+            }
+            x => panic!("expected processing instruction, got {:?}", x),
+        }
+        match reader.next() {
+            Some(Ok(Event::ProcessingInstruction(tag))) => {
+                assert_eq!((tag.name(), tag.content()), ("empty", ""));
+            }
+            x => panic!("expected processing instruction, got {:?}", x),
+        }
+        assert!(matches!(reader.next(), Some(Ok(Event::Empty(_)))));
+        assert!(matches!(reader.next(), Some(Err(Error::InvalidName(52)))));
+    }
+
+    #[test]
+    fn reader_cdata() {
+        let xml = "<Script><![CDATA[ if (a < b && c > d) x = ']]'; ]]></Script><![CDATA[";
+        let mut reader = Reader::from_str(xml);
+        assert!(matches!(reader.next(), Some(Ok(Event::Start(_)))));
+        match reader.next() {
+            Some(Ok(Event::CData(text))) => {
+                assert_eq!(text.content(), " if (a < b && c > d) x = ']]'; ");
+            }
+            x => panic!("expected cdata, got {:?}", x),
+        }
+        assert!(matches!(reader.next(), Some(Ok(Event::End(_)))));
+        assert!(matches!(reader.next(), Some(Err(Error::UnexpectedEof))));
+    }
+
+    #[test]
+    fn reader_doctype() {
+        let subset = " Config [\n  <!ENTITY gt \"]>\">\n  <!-- ] > -->\n]";
+        let xml = format!("<!DOCTYPE{}><Config/><!ELEMENT x>", subset);
+        let mut reader = Reader::from_str(&xml);
+        match reader.next() {
+            Some(Ok(Event::DocType(text))) => assert_eq!(text.content(), subset),
+            x => panic!("expected doctype, got {:?}", x),
+        }
+        assert!(matches!(reader.next(), Some(Ok(Event::Empty(_)))));
+        let offset = xml.len() - 12;
+        assert!(matches!(reader.next(), Some(Err(Error::InvalidName(x))) if x == offset));
+    }
+
+    #[test]
+    fn decode_text() {
+        let text = "&lt;a&gt; &amp;&#38;&#x26; &quot;&apos; &nbsp; &#xD800; &";
+        assert_eq!(
+            decode::decode_text_str(text),
+            "<a> &&& \"' &nbsp; &#xD800; &"
+        );
+        assert!(matches!(
+            decode::decode_text_str("plain"),
+            Cow::Borrowed("plain")
+        ));
+        let strict = decode::try_decode_text(text.as_bytes());
+        assert!(matches!(strict, Err(Error::UnknownEntity(40))));
+        assert_eq!(decode::try_decode_text(b"a&amp;b").unwrap(), &b"a&b"[..]);
+
+        let mut reader = Reader::from_str("<a b='&#65;'>x &amp; y</a>");
+        match reader.next() {
+            Some(Ok(Event::Start(tag))) => {
+                let attr = tag.attributes().next().unwrap().unwrap();
+                assert_eq!(attr.decoded_value(), "A");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        match reader.next() {
+            Some(Ok(Event::Text(text))) => assert_eq!(text.decoded(), "x & y"),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn io_reader_chunks() {
+        // Trickle the input in a few bytes at a time to cut events at every possible point.
+        struct Trickle<'a>(&'a [u8]);
+        impl std::io::Read for Trickle<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let len = self.0.len().min(buf.len()).min(3);
+                buf[..len].copy_from_slice(&self.0[..len]);
+                self.0 = &self.0[len..];
+                Ok(len)
+            }
+        }
+        let xml = b"<a x='1'> text <!-- c --><b/>tail</a> end ";
+        let expected = Reader::from_bytes(xml)
+            .map(|x| format!("{:?}", x.unwrap().into_owned()))
+            .collect::<Vec<_>>();
+        let events = IoReader::new(Trickle(xml))
+            .map(|x| format!("{:?}", x.unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(events, expected);
+
+        let mut reader = IoReader::new(Trickle(b"<a>text</a"));
+        assert!(matches!(reader.next(), Some(Ok(OwnedEvent::Start(_)))));
+        assert!(matches!(reader.next(), Some(Ok(OwnedEvent::Text(_)))));
+        assert!(matches!(reader.next(), Some(Err(Error::UnexpectedEof))));
+        assert!(reader.next().is_none());
+
+        // Iterating to completion stops after the error instead of repeating it.
+        let events = IoReader::new(Trickle(b"<a><b x='1'")).collect::<Vec<_>>();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[1], Err(Error::UnexpectedEof)));
+        let errors = IoReader::new(&b"<a><0/><b/></a>"[..]).filter(Result::is_err);
+        assert_eq!(errors.count(), 1);
+
+        // Text starting with what would be a UTF-8 continuation byte is fine in bytes.
+        let events = IoReader::new(Trickle(b"<a>\xA0caf\xC3\xA9</a><b/>"));
+        assert_eq!(events.map(Result::unwrap).count(), 4);
+
+        // Settings apply, and state is kept from one event to the next even once compacted away.
+        let xml = format!(
+            "<root><list>{}</list><script>a<b</script></wrong>",
+            "<item/>".repeat(5000)
+        );
+        let settings = ReaderBuilder::new()
+            .check_structure(true)
+            .dialect(Dialect::Html);
+        let expected = settings
+            .from_bytes(xml.as_bytes())
+            .map(|x| format!("{:?}", x.map(Event::into_owned)))
+            .collect::<Vec<_>>();
+        let mut reader = IoReader::new(Trickle(xml.as_bytes()));
+        reader.settings(settings);
+        let events = reader.map(|x| format!("{:?}", x)).collect::<Vec<_>>();
+        // Unlike the reader, it stops at the first error.
+        assert_eq!(events, expected[..events.len()]);
+        assert!(events.last().unwrap().contains("MismatchedEnd"));
+
+        let mut reader = IoReader::new(Trickle(b"<a><b/></a><c/>"));
+        reader.settings(ReaderBuilder::new().stop_after_root(true));
+        assert_eq!(reader.map(Result::unwrap).count(), 3);
+    }
+
+    #[test]
+    fn owned_across_threads() {
+        let xml = String::from("<Frame id=\"1\">a &amp; b</Frame>");
+        let events = Reader::from_str(&xml)
+            .map(|x| x.map(Event::into_owned))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        drop(xml);
+        let (attr, text) = std::thread::spawn(move || match &events[..] {
+            [OwnedEvent::Start(tag), OwnedEvent::Text(text), OwnedEvent::End(_)] => {
+                let attr = tag.attributes().next().unwrap().unwrap().into_owned();
+                (attr, text.clone())
+            }
+            x => panic!("unexpected events {:?}", x),
+        })
+        .join()
+        .unwrap();
+        assert_eq!((attr.key(), attr.value()), ("id", "1"));
+        assert_eq!(text.content(), "a &amp; b");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn writer_round_trip() {
+        let xml = "<?xml version=\"1.0\"?><a x='1'><!-- c --><b>t &amp; <![CDATA[<]]></b><c/></a>";
+        let mut writer = Writer::new(Vec::new());
+        for event in Reader::from_str(xml).trim_whitespace(false) {
+            writer.write_event(&event.unwrap()).unwrap();
+        }
+        assert_eq!(writer.get_ref(), xml.as_bytes());
+
+        let mut writer = Writer::new(Vec::new());
+        writer.indent(1);
+        writer.write_start("a", &[("q", "\"<&>")]).unwrap();
+        writer.write_comment(" c ").unwrap();
+        writer.write_start("b", &[]).unwrap();
+        writer.write_text("1 < 2").unwrap();
+        writer.write_end("b").unwrap();
+        writer.write_start("c", &[]).unwrap();
+        writer.write_end("c").unwrap();
+        writer.write_cdata("]]>").unwrap();
+        writer.write_end("a").unwrap();
+        let expected = "<a q=\"&quot;&lt;&amp;&gt;\">\n <!-- c -->\n <b>1 &lt; 2</b>\n <c></c>\n \
+                        <![CDATA[]]]]><![CDATA[>]]>\n</a>";
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), expected);
+    }
+
+    #[test]
+    fn tree_drops_deep_nesting() {
+        let depth = 200_000;
+        let xml = format!("{}{}", "<a>".repeat(depth), "</a>".repeat(depth));
+        let doc = tree::Document::parse(&xml).unwrap();
+        assert_eq!(doc.root().unwrap().children().len(), 1);
+        drop(doc);
+    }
+
+    #[test]
+    fn tree_retains_comments_in_order() {
+        let xml = "<?xml version=\"1.0\"?><!-- head --><a>1<!-- mid --><b/><![CDATA[2]]></a>";
+        let doc = tree::Document::parse(xml).unwrap();
+        assert_eq!(doc.children().len(), 1);
+        let root = doc.root().unwrap();
+        assert_eq!(root.children().len(), 3);
+        assert_eq!(root.text(), "12");
+
+        let options = tree::TreeOptions::new()
+            .comments(true)
+            .processing_instructions(true);
+        let doc = tree::Document::parse_with(xml, options).unwrap();
+        let kinds = |nodes: &[tree::Node]| {
+            nodes
+                .iter()
+                .map(|x| match x.kind() {
+                    tree::NodeKind::Element(_) => 'e',
+                    tree::NodeKind::Text(_) => 't',
+                    tree::NodeKind::CData(_) => 'd',
+                    tree::NodeKind::Comment(_) => 'c',
+                    tree::NodeKind::ProcessingInstruction(_) => 'p',
+                })
+                .collect::<String>()
+        };
+        assert_eq!(kinds(doc.children()), "pce");
+        assert_eq!(kinds(doc.root().unwrap().children()), "tced");
+
+        assert!(matches!(
+            tree::Document::parse("<a><b></a>"),
+            Err(Error::MismatchedEnd { .. })
+        ));
+        assert!(matches!(
+            tree::Document::parse("<a><b/>"),
+            Err(Error::UnclosedTags(_))
+        ));
+        assert!(matches!(
+            tree::Document::parse("<a></a></b>"),
+            Err(Error::UnexpectedEndTag { .. })
+        ));
+    }
+
+    #[test]
+    fn e4x_queries() {
+        let xml = "<Anim><Script time=\"0\">a</Script><Script time=\"5\" id=\"2\">b</Script>\
+                   <Layer><Frame i=\"1\"/><Group><Frame i=\"2\"/></Group></Layer></Anim>";
+        let doc = tree::Document::parse(xml).unwrap();
+        let xml = e4x::XmlList::new(&doc);
+        let scripts = xml.children("Script");
+        assert_eq!(scripts.len(), 2);
+        assert_eq!(scripts.attrs("time"), ["0", "5"]);
+        assert_eq!(scripts.text(), "ab");
+        let script = scripts.filter(|x| x.attribute("id") == Some("2"));
+        assert_eq!(script.attr("time"), Some("5"));
+        assert_eq!(xml.children("*").len(), 3);
+        assert_eq!(xml.descendants("Frame").attrs("i"), ["1", "2"]);
+        assert!(xml.children("Missing").children("*").is_empty());
+
+        let depth = 200_000;
+        let xml = format!("{}{}", "<a>".repeat(depth), "</a>".repeat(depth));
+        let doc = tree::Document::parse(&xml).unwrap();
+        assert_eq!(e4x::XmlList::new(&doc).descendants("a").len(), depth - 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_from_str() {
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        enum Kind {
+            Tween,
+            Motion,
+        }
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct Frame {
+            #[serde(rename = "@index")]
+            index: u32,
+            kind: Kind,
+            #[serde(rename = "$value")]
+            label: String,
+        }
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct Layer {
+            name: String,
+            visible: bool,
+            #[serde(rename = "Frame")]
+            frames: Vec<Frame>,
+            #[serde(rename = "Missing")]
+            missing: Option<u8>,
+        }
+        let xml = "<Layer name=\"bg &amp; fg\"><visible>1</visible>\
+                   <Frame index='0' kind='Tween'>start</Frame><Frame index='3' kind=\"Motion\"/></Layer>";
+        let layer: Layer = de::from_str(xml).unwrap();
+        assert_eq!(
+            layer,
+            Layer {
+                name: "bg & fg".into(),
+                visible: true,
+                frames: vec![
+                    Frame {
+                        index: 0,
+                        kind: Kind::Tween,
+                        label: "start".into()
+                    },
+                    Frame {
+                        index: 3,
+                        kind: Kind::Motion,
+                        label: "".into()
+                    },
+                ],
+                missing: None,
+            }
+        );
+        let map: std::collections::BTreeMap<String, String> =
+            de::from_str("<a x='1'><b>2</b>3</a>").unwrap();
+        assert_eq!(map["@x"], "1");
+        assert_eq!(map["b"], "2");
+        assert_eq!(map["$value"], "3");
+        let map: std::collections::BTreeMap<String, String> =
+            de::from_str("<a>&lt;<![CDATA[&amp; <b>]]>&gt;</a>").unwrap();
+        assert_eq!(map["$value"], "<&amp; <b>>");
+        assert!(de::from_str::<Layer>("<Layer name='x'><visible>maybe</visible></Layer>").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_to_string() {
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        enum Kind {
+            Tween,
+        }
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Frame {
+            index: u32,
+            kind: Kind,
+            #[serde(rename = "$value")]
+            label: String,
+        }
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Layer {
+            #[serde(rename = "@name")]
+            name: String,
+            #[serde(rename = "Frame")]
+            frames: Vec<Frame>,
+        }
+        let layer = Layer {
+            name: "bg & fg".into(),
+            frames: vec![
+                Frame {
+                    index: 0,
+                    kind: Kind::Tween,
+                    label: "a<b".into(),
+                },
+                Frame {
+                    index: 3,
+                    kind: Kind::Tween,
+                    label: String::new(),
+                },
+            ],
+        };
+        let xml = ser::to_string(&layer).unwrap();
+        assert_eq!(
+            xml,
+            "<Layer name=\"bg &amp; fg\"><Frame>a&lt;b<index>0</index><kind>Tween</kind></Frame>\
+             <Frame><index>3</index><kind>Tween</kind></Frame></Layer>"
+        );
+        assert_eq!(de::from_str::<Layer>(&xml).unwrap(), layer);
+        let xml = ser::to_string_with(&layer, ser::FieldMapping::Attributes).unwrap();
+        assert_eq!(
+            xml,
+            "<Layer name=\"bg &amp; fg\"><Frame index=\"0\" kind=\"Tween\">a&lt;b</Frame>\
+             <Frame index=\"3\" kind=\"Tween\"/></Layer>"
+        );
+        assert_eq!(de::from_str::<Layer>(&xml).unwrap(), layer);
+    }
+
+    #[test]
+    fn namespace_resolution() {
+        let xml = b"<a xmlns='urn:a' xmlns:p='urn:p'><p:b p:x='1' y='2' xml:lang='en'/>\
+                    <c xmlns=''><p:d xmlns:p='urn:q'/></c><e/></a>";
+        let mut resolver = NamespaceResolver::<[u8]>::new();
+        let mut tags = Vec::new();
+        for event in Reader::from_bytes(xml) {
+            let event = event.unwrap();
+            resolver.push(&event).unwrap();
+            match &event {
+                Event::Start(tag) | Event::Empty(tag) | Event::End(tag) => {
+                    tags.push((tag.local_name(), resolver.resolve_tag(tag)));
+                }
+                _ => (),
+            }
+            if let Event::Empty(tag) = &event {
+                if tag.prefix() == Some(b"p") && tag.local_name() == b"b" {
+                    let attrs = tag.attributes().map(Result::unwrap).collect::<Vec<_>>();
+                    let uris = attrs
+                        .iter()
+                        .map(|x| resolver.resolve_attribute(x))
+                        .collect::<Vec<_>>();
+                    assert_eq!(
+                        uris,
+                        [Some(&b"urn:p"[..]), None, Some(XML_NAMESPACE.as_bytes())]
+                    );
+                }
+            }
+        }
+        let (a, p, q) = (
+            Some(&b"urn:a"[..]),
+            Some(&b"urn:p"[..]),
+            Some(&b"urn:q"[..]),
+        );
+        let expected: [(&[u8], _); 7] = [
+            (b"a", a),
+            (b"b", p),
+            (b"c", None),
+            (b"d", q),
+            (b"c", None),
+            (b"e", a),
+            (b"a", a),
+        ];
+        assert_eq!(tags, expected);
+    }
+
+    #[test]
+    fn reader_structure_errors() {
+        let mut reader = Reader::from_str("<a><b></a></b><c>");
+        reader.check_structure(true);
+        assert!(matches!(reader.next(), Some(Ok(Event::Start(_)))));
+        assert!(matches!(reader.next(), Some(Ok(Event::Start(_)))));
+        match reader.next() {
+            Some(Err(Error::MismatchedEnd {
+                expected,
+                found,
+                offset,
+            })) => {
+                assert_eq!((expected.as_str(), found.as_str(), offset), ("b", "a", 6));
+            }
+            x => panic!("expected mismatched end, got {:?}", x),
+        }
+        assert!(matches!(reader.next(), Some(Ok(Event::End(_)))));
+        assert!(matches!(reader.next(), Some(Ok(Event::Start(_)))));
+        match reader.next() {
+            Some(Err(Error::UnclosedTags(names))) => assert_eq!(names, ["a", "c"]),
+            x => panic!("expected unclosed tags, got {:?}", x),
+        }
+        assert!(reader.next().is_none());
+
+        let mut reader = Reader::from_str("<a><b>");
+        reader.check_structure(true);
+        let errors = reader.filter(Result::is_err).count();
+        assert_eq!(errors, 1);
+    }
+
+    #[test]
+    fn reader_positions() {
+        let xml = "<a>\n  <b>é</b>\r\n  <1>";
+        let mut reader = Reader::from_str(xml);
+        let error = loop {
+            if let Err(error) = reader.next().unwrap() {
+                break error;
+            }
+        };
+        let position = reader.position_of(error.offset().unwrap());
+        assert_eq!((position.line, position.column, position.byte), (3, 3, 19));
+        assert_eq!(reader.last_event_span(), 11..15);
+        let position = Reader::from_str(xml).position_of(14);
+        assert_eq!((position.line, position.column), (2, 10));
+        assert_eq!(Reader::from_str(xml).position_of(100).byte, xml.len());
+    }
+
+    #[test]
+    fn event_spans() {
+        let xml = "<a>\n <b k = 'v' x=\"\"/> text <!--c--><?pi d?></a>";
+        let mut spans = Vec::new();
+        for event in Reader::from_str(xml) {
+            match event.unwrap() {
+                Event::Start(tag) | Event::End(tag) | Event::ProcessingInstruction(tag) => {
+                    spans.push(&xml[tag.span()]);
+                }
+                Event::Empty(tag) => {
+                    spans.push(&xml[tag.span()]);
+                    for attr in tag.attributes() {
+                        let attr = attr.unwrap();
+                        assert_eq!(&xml[attr.key_span()], attr.key());
+                        assert_eq!(&xml[attr.value_span()], attr.value());
+                    }
+                }
+                Event::Text(text) | Event::Comment(text) => spans.push(&xml[text.span()]),
+                _ => (),
+            }
+        }
+        assert_eq!(
+            spans,
+            [
+                "<a>",
+                "<b k = 'v' x=\"\"/>",
+                "text",
+                "c",
+                "<?pi d?>",
+                "</a>"
+            ]
+        );
+    }
+
+    #[test]
+    fn find_attribute() {
+        let tag = Tag::new("a", " x='1' y=\"2\" x='3' z");
+        assert_eq!(tag.find_attribute("x").map(|attr| attr.value()), Some("1"));
+        assert_eq!(tag.find_attribute("y").map(|attr| attr.value()), Some("2"));
+        assert!(tag.has_attribute("x"));
+        assert!(!tag.has_attribute("z"));
+        let tag = Tag::new(&b"a"[..], &b" k='v'"[..]);
+        assert_eq!(
+            tag.find_attribute(b"k").map(|attr| attr.value()),
+            Some(&b"v"[..])
+        );
+    }
+
+    #[test]
+    fn attributes_collection() {
+        let tag = Tag::new("a", " x='1' Y='2' x='3'");
+        let attrs = Attributes::parse(&tag).unwrap();
+        let pairs: Vec<_> = attrs.iter().map(|a| (a.key(), a.value())).collect();
+        assert_eq!(pairs, [("x", "1"), ("Y", "2")]);
+        assert!(attrs.get("y").is_none());
+        assert_eq!(attrs.get_ignore_case("y").map(|a| a.value()), Some("2"));
+        let attrs = Attributes::parse_with(&tag, DuplicatePolicy::LastWins).unwrap();
+        let pairs: Vec<_> = attrs.iter().map(|a| (a.key(), a.value())).collect();
+        assert_eq!(pairs, [("x", "3"), ("Y", "2")]);
+        assert!(Attributes::parse(&Tag::new("a", " x='1' y")).is_err());
+    }
+
+    #[test]
+    fn reader_skip_current_element() {
+        let xml = "<a><skip x='1'><b><c/></b><![CDATA[</skip>]]><?pi </skip>?></skip><kept/></a>";
+        let mut reader = Reader::from_str(xml);
+        reader.check_structure(true);
+        let mut names = Vec::new();
+        while let Some(event) = reader.next() {
+            match event.unwrap() {
+                Event::Start(tag) if tag.name() == "skip" => {
+                    reader.skip_current_element().unwrap();
+                    assert_eq!(&xml[reader.last_event_span()], "</skip>");
+                }
+                Event::Start(tag) | Event::Empty(tag) | Event::End(tag) => names.push(tag.name()),
+                _ => (),
+            }
+        }
+        assert_eq!(names, ["a", "kept", "a"]);
+
+        let mut reader = Reader::from_str("<a><b></a>");
+        reader.next();
+        assert!(matches!(
+            reader.skip_current_element(),
+            Err(Error::UnexpectedEof)
+        ));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn reader_read_text() {
+        let xml = b"<a><Script>\n  x = '<b>';\n</Script><c/></a>";
+        let mut reader = Reader::from_bytes(xml);
+        reader.next();
+        reader.next();
+        assert_eq!(reader.read_text().unwrap(), b"\n  x = '<b>';\n");
+        assert!(matches!(reader.next(), Some(Ok(Event::Empty(_)))));
+        assert!(matches!(reader.next(), Some(Ok(Event::End(_)))));
+    }
+
+    #[test]
+    fn raw_slices() {
+        let xml = "<a  x = '1' >\n  text \n<!-- c --></a >";
+        let raw: Vec<_> = Reader::from_str(xml)
+            .map(|event| match event.unwrap() {
+                Event::Start(tag) | Event::End(tag) => tag.raw(),
+                Event::Text(text) | Event::Comment(text) => text.raw(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(raw, ["<a  x = '1' >", "\n  text \n", "<!-- c -->", "</a >"]);
+        assert_eq!(raw.concat(), xml);
+    }
+
+    #[test]
+    fn reader_builder_options() {
+        let xml = "<a>\n<!-- c --><b$/><b/></a>";
+        let events: Vec<_> = ReaderBuilder::new()
+            .emit_comments(false)
+            .emit_empty_text(true)
+            .from_str(xml)
+            .map(|event| match event.unwrap() {
+                Event::Start(tag) | Event::Empty(tag) | Event::End(tag) => tag.name(),
+                Event::Text(text) => text.content(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(events, ["", "a", "", "", "b$", "", "b", "", "a"]);
+
+        let mut reader = ReaderBuilder::new().strict_names(true).from_str(xml);
+        assert!(matches!(reader.nth(2), Some(Err(Error::InvalidName(14)))));
+
+        let mut reader = ReaderBuilder::new()
+            .max_tag_len(Some(2))
+            .from_str("<ab><abc>");
+        assert!(matches!(reader.next(), Some(Ok(Event::Start(_)))));
+        assert!(matches!(
+            reader.next(),
+            Some(Err(Error::LimitExceeded {
+                limit: Limit::TagLen,
+                offset: 4
+            }))
+        ));
+    }
+
+    #[test]
+    fn encoding_detection() {
+        use encoding::{decode_input, detect_encoding, Encoding};
+
+        let text = "<a>é</a>";
+        let be: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        assert_eq!(detect_encoding(&be), (Encoding::Utf16Be, 0));
+        assert_eq!(decode_input(&be), text);
+
+        let latin1 = b"<?xml version='1.0' encoding='ISO-8859-1'?><a>\xE9</a>";
+        assert_eq!(detect_encoding(latin1), (Encoding::Latin1, 0));
+        assert!(decode_input(latin1).ends_with(text));
+
+        let bom = b"\xEF\xBB\xBF<a/>";
+        assert_eq!(detect_encoding(bom), (Encoding::Utf8, 3));
+        assert!(matches!(decode_input(bom), Cow::Borrowed("<a/>")));
+    }
+
+    #[cfg(feature = "encoding")]
+    #[test]
+    fn encoding_legacy() {
+        let xml = b"<?xml version='1.0' encoding='windows-1252'?><Price>\x80 5</Price>";
+        let mut buf = String::new();
+        let mut reader = Reader::from_bytes_auto(xml, &mut buf);
+        assert_eq!(reader.take_text().unwrap().as_deref(), Some("€ 5"));
+    }
+
+    #[test]
+    fn str_char_boundaries() {
+        // Multi-byte characters right next to every byte the reader splits around.
+        let xml = "\u{a0}<à é='à'ü=\"ö\" /><ß>\u{a0}ä\u{3000}<!--à--><![CDATA[ö]]></ß>";
+        let mut pieces = Vec::new();
+        for event in Reader::from_str(xml) {
+            match event.unwrap() {
+                Event::Empty(tag) => {
+                    pieces.push(tag.name());
+                    for attr in tag.attributes() {
+                        let attr = attr.unwrap();
+                        pieces.extend([attr.key(), attr.value()]);
+                    }
+                }
+                Event::Start(tag) | Event::End(tag) => pieces.push(tag.name()),
+                Event::Text(text) | Event::Comment(text) | Event::CData(text) => {
+                    pieces.push(text.content())
+                }
+                _ => unreachable!(),
+            }
+        }
+        // Only ASCII whitespace is trimmed, so no-break and ideographic spaces are kept whole.
+        let expected = [
+            "\u{a0}",
+            "à",
+            "é",
+            "à",
+            "ü",
+            "ö",
+            "ß",
+            "\u{a0}ä\u{3000}",
+            "à",
+            "ö",
+            "ß",
+        ];
+        assert_eq!(pieces, expected);
+        assert_eq!(Reader::from_str(xml).count(), 7);
+    }
+
+    #[test]
+    fn fused_after_errors() {
+        let mut reader = Reader::from_str("<a><0b><c/></a>");
+        assert!(matches!(reader.next(), Some(Ok(Event::Start(_)))));
+        assert!(matches!(reader.next(), Some(Err(Error::InvalidName(3)))));
+        assert!(reader.next().is_none());
+        assert!(reader.next().is_none());
+        assert_eq!(reader.size_hint(), (0, Some(0)));
+
+        let reader = Reader::from_str("<a>text</a>");
+        let (lower, upper) = reader.size_hint();
+        assert!(lower <= 3 && upper >= Some(3));
+
+        let mut reader = Reader::from_str("a<!--x-->");
+        reader.emit_comments(false);
+        assert!(matches!(reader.next(), Some(Ok(Event::Text(_)))));
+        assert_eq!(reader.size_hint().0, 0);
+        assert!(reader.next().is_none());
+
+        let mut attrs = AttributeIter::new(" a='1' ='2' c='3'");
+        assert!(matches!(attrs.next(), Some(Ok(_))));
+        assert!(matches!(
+            attrs.next(),
+            Some(Err(Error::InvalidAttribute(_)))
+        ));
+        assert!(attrs.next().is_none());
+    }
+
+    #[test]
+    fn reader_recover_attributes() {
+        let xml = "<a x=1 y='2' =bad z=\"3\"/><b ok='1' broken=\"/>";
+        let mut reader = Reader::from_str(xml);
+        reader.recover(true).strict_attributes(true);
+        let mut attrs = Vec::new();
+        for event in reader {
+            if let Event::Start(tag) | Event::Empty(tag) = event.unwrap() {
+                attrs.extend(tag.attributes().map(|attr| attr.unwrap().key()));
+            }
+        }
+        assert_eq!(attrs, ["y", "z", "ok"]);
+    }
+
+    #[test]
+    fn error_display() {
+        let xml = "<a>\n<0bad attr='1'>\n</a>";
+        let error = Reader::from_str(xml).find_map(Result::err).unwrap();
+        assert_eq!(error.to_string(), "invalid tag name at byte 4");
+        assert_eq!(error.kind(), ErrorKind::Syntax);
+        assert_eq!(error.snippet(xml.as_bytes()), Some(&b"<0bad attr='1'>"[..]));
+
+        let error = Error::MismatchedEnd {
+            expected: "a".into(),
+            found: "b".into(),
+            offset: 3,
+        };
+        assert_eq!(
+            error.to_string(),
+            "expected `</a>` but found `</b>` at byte 3"
+        );
+        assert_eq!(error.kind(), ErrorKind::Structure);
+    }
+
+    #[test]
+    fn reader_depth() {
+        let mut reader = Reader::from_str("<a><b/><c><d></d></c></a>");
+        let mut depths = Vec::new();
+        while reader.next().is_some() {
+            depths.push(reader.depth());
+        }
+        assert_eq!(depths, [1, 1, 2, 3, 2, 1, 0]);
+
+        let mut reader = Reader::from_str("<a><b><c/></b><b><c></c></b></a>");
+        reader.max_depth(Some(2));
+        assert!(matches!(
+            reader.nth(5),
+            Some(Err(Error::LimitExceeded {
+                limit: Limit::Depth,
+                offset: 17
+            }))
+        ));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn reader_find_path() {
+        let xml = "<Root><Scripts><Other><Script lang='as3'/></Other>\
+                   <Script lang='as3'><Script lang='as3'/>body</Script><Script lang='js'/>\
+                   <Script lang=\"as3\"/></Scripts><Script lang='as3'/></Root>";
+        let mut reader = Reader::from_str(xml);
+        let mut matches = reader.find_path(&["Root", "Scripts", "Script[@lang='as3']"]);
+        let tag = matches.next().unwrap().unwrap();
+        assert_eq!(tag.name(), "Script");
+        assert_eq!(matches.children().count(), 2);
+        let tag = matches.next().unwrap().unwrap();
+        assert_eq!(tag.raw(), "<Script lang=\"as3\"/>");
+        assert!(matches.next().is_none());
+        assert!(reader.next().is_none());
+
+        let mut reader = Reader::from_str("<a><b><c/></b><c/></a><c/>");
+        assert!(matches!(reader.next(), Some(Ok(Event::Start(_)))));
+        assert_eq!(reader.find_path(&["*", "c"]).count(), 1);
+        assert!(matches!(reader.next(), Some(Ok(Event::Empty(_)))));
+    }
+
+    #[test]
+    fn xpath_select() {
+        use xpath::Path;
+        let xml =
+            "<Anim><Layer><Frame i='0'/><Sub><Frame i='1'>a</Frame></Sub><Frame i='2'/></Layer>\
+                   <Layer><Frame i='3'>b<![CDATA[c]]></Frame></Layer></Anim>";
+        let doc = tree::Document::parse(xml).unwrap();
+        let attrs = |path: &str| {
+            let nodes = Path::compile(path).unwrap().select_document(&doc);
+            nodes
+                .iter()
+                .map(|x| x.attribute("i").unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(attrs("//Frame"), ["0", "1", "2", "3"]);
+        assert_eq!(attrs("/Anim/Layer/Frame"), ["0", "2", "3"]);
+        assert_eq!(attrs("/Anim/*/Frame[2]"), ["2"]);
+        assert_eq!(attrs("//Frame[1]"), ["0", "1", "3"]);
+        assert_eq!(attrs("//Layer//Frame[@i=\"1\"]"), ["1"]);
+        assert_eq!(attrs("/Layer"), [""; 0]);
+        let root = doc.root().unwrap();
+        let texts = Path::compile("Layer/Frame/text()").unwrap().select(root);
+        assert_eq!(
+            texts.iter().map(|x| x.text()).collect::<Vec<_>>(),
+            ["b", "c"]
+        );
+        assert_eq!(Path::compile("/Anim").unwrap().select(root).len(), 1);
+        for (expr, offset) in [("", 0), ("a/", 2), ("a[0]", 2), ("a[@b=c]", 5), ("a[@b", 4)] {
+            assert_eq!(
+                Path::compile(expr).unwrap_err().offset(),
+                offset,
+                "{}",
+                expr
+            );
+        }
+
+        let depth = 200_000;
+        let xml = format!("{}{}", "<a>".repeat(depth), "</a>".repeat(depth));
+        let doc = tree::Document::parse(&xml).unwrap();
+        let nodes = Path::compile("//a").unwrap().select_document(&doc);
+        assert_eq!(nodes.len(), depth);
+        assert!(std::ptr::eq(nodes[0], doc.root().unwrap()));
+    }
+
+    #[test]
+    fn attributes_unquoted() {
+        let xml = "<Frame index=3 name = 'a b' empty=\"\" last=x/><Frame a=1>";
+        let mut reader = Reader::from_str(xml);
+        reader
+            .allow_unquoted_attributes(true)
+            .strict_attributes(true);
+        let Some(Ok(Event::Empty(tag))) = reader.next() else {
+            panic!("expected an empty tag");
+        };
+        let attrs: Vec<_> = tag
+            .attributes()
+            .map(|x| x.map(|x| (x.key(), x.value(), x.value_span())))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            attrs,
+            [
+                ("index", "3", 13..14),
+                ("name", "a b", 23..26),
+                ("empty", "", 35..35),
+                ("last", "x", 42..43)
+            ]
+        );
+        let Some(Ok(Event::Start(tag))) = reader.next() else {
+            panic!("expected a start tag");
+        };
+        let mut attrs = tag.attributes().max_value_len(Some(0));
+        assert!(matches!(
+            attrs.next(),
+            Some(Err(Error::ValueTooLong { offset: 0 }))
+        ));
+    }
+
+    #[test]
+    fn attributes_bare() {
+        let content = "enabled a = '1' \t checked\nb='2' last";
+        let attrs = |iter: AttributeIter<'static, str>| {
+            iter.map(|x| x.map(|x| (x.key(), x.value(), x.value_span())))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        assert_eq!(
+            attrs(AttributeIter::new(content).bare(true)).unwrap(),
+            [
+                ("enabled", "", 7..7),
+                ("a", "1", 13..14),
+                ("checked", "", 25..25),
+                ("b", "2", 29..30),
+                ("last", "", 36..36)
+            ]
+        );
+        assert!(attrs(AttributeIter::new(content)).is_err());
+
+        let iter = AttributeIter::new("a b c d e f").bare(true);
+        assert!(iter.size_hint().1 >= Some(6));
+        assert_eq!(iter.count(), 6);
+    }
+
+    #[test]
+    fn attributes_duplicate_policies() {
+        let mut reader = Reader::from_str("<a x='1' y='2' x='3'/>");
+        let Some(Ok(Event::Empty(tag))) = reader.next() else {
+            panic!("expected an empty tag");
+        };
+        let pairs = |policy| {
+            let attrs = tag.attributes_deduped(policy).unwrap();
+            attrs
+                .iter()
+                .map(|a| (a.key(), a.value()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(pairs(DuplicatePolicy::FirstWins), [("x", "1"), ("y", "2")]);
+        assert_eq!(pairs(DuplicatePolicy::LastWins), [("x", "3"), ("y", "2")]);
+        assert_eq!(
+            pairs(DuplicatePolicy::KeepAll),
+            [("x", "1"), ("y", "2"), ("x", "3")]
+        );
+        let attrs = tag.attributes_deduped(DuplicatePolicy::KeepAll).unwrap();
+        assert_eq!(attrs.get_all("x").count(), 2);
+        assert!(matches!(
+            tag.attributes_deduped(DuplicatePolicy::Error),
+            Err(Error::DuplicateAttribute { offset: 15 })
+        ));
+    }
+
+    #[test]
+    fn attribute_typed_values() {
+        let tag = Tag::new(
+            &b"a"[..],
+            &b" i=' -42 ' f='1.5e3' t='Yes' n='0' e='&#49;&#50;' bad='\xFF' x='maybe'"[..],
+        );
+        let attr = |key: &str| tag.find_attribute(key.as_bytes()).unwrap();
+        assert_eq!(attr("i").as_i64(), Some(-42));
+        assert_eq!(attr("i").parse::<i8>(), Ok(-42));
+        assert_eq!(attr("f").as_f64(), Some(1500.0));
+        assert_eq!(attr("f").as_i64(), None);
+        assert_eq!(attr("t").as_bool(), Some(true));
+        assert_eq!(attr("n").as_bool(), Some(false));
+        assert_eq!(attr("x").as_bool(), None);
+        assert_eq!(attr("e").parse::<u16>(), Ok(12));
+        assert_eq!(attr("e").as_i64(), None);
+        assert_eq!(attr("bad").parse::<u8>(), Err(ValueError::NotUtf8));
+        assert!(matches!(
+            attr("x").parse::<u8>(),
+            Err(ValueError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn text_typed_values() {
+        let mut reader = Reader::from_bytes(b"<a>\n  1.5 </a><b> x \t\r\n y  z\xC3</b><c>a b</c>");
+        reader.trim_whitespace(false);
+        let mut texts = reader.filter_map(|event| match event.unwrap() {
+            Event::Text(text) => Some(text),
+            _ => None,
+        });
+        let text = texts.next().unwrap();
+        assert_eq!(text.parse::<f32>(), Ok(1.5));
+        assert_eq!(text.normalized(), &b"1.5"[..]);
+        let text = texts.next().unwrap();
+        assert!(text.as_str().is_err());
+        assert_eq!(text.parse::<u8>(), Err(ValueError::NotUtf8));
+        assert_eq!(text.normalized(), &b"x y z\xC3"[..]);
+        let text = texts.next().unwrap();
+        assert_eq!(text.as_str(), Ok("a b"));
+        assert!(matches!(text.normalized(), Cow::Borrowed(b"a b")));
+        let text = Text::new(" \u{e9}\n\u{e9} ");
+        assert_eq!(text.normalized(), "\u{e9} \u{e9}");
+        assert_eq!(text.as_str(), " \u{e9}\n\u{e9} ");
+    }
+
+    #[test]
+    fn reader_ignore_name_case() {
+        let xml = "<Frame a='1'><x></X></frame>";
+        let mut reader = Reader::from_str(xml);
+        reader.check_structure(true);
+        assert!(reader.any(|event| event.is_err()));
+
+        let mut reader = ReaderBuilder::new()
+            .check_structure(true)
+            .ignore_name_case(true)
+            .from_str(xml);
+        let Some(Ok(Event::Start(tag))) = reader.next() else {
+            panic!("expected a start tag");
+        };
+        assert!(tag.name_eq_ignore_case("FRAME"));
+        assert!(!tag.name_eq_ignore_case("Fram"));
+        let attr = tag.attributes().next().unwrap().unwrap();
+        assert!(attr.key_eq_ignore_case("A"));
+        assert_eq!(reader.read_text().unwrap(), "<x></X>");
+        assert!(reader.next().is_none());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_records() {
+        let mut xml = String::from("<!-- header --><Root>");
+        for i in 0..100 {
+            xml.push_str(&format!("<Item id='{}'><![CDATA[</Item>]]></Item>text", i));
+        }
+        xml.push_str("<Last/></Root><Trailing/>");
+        let records = parallel::split_records(&xml).unwrap();
+        assert_eq!(records.len(), 101);
+        assert_eq!(records[100], "<Last/>");
+        let ids = parallel::map_records(&xml, |mut reader| match reader.next() {
+            Some(Ok(Event::Start(tag))) => tag.find_attribute("id").map(|x| x.value()),
+            _ => None,
+        })
+        .unwrap();
+        assert!(ids[..100]
+            .iter()
+            .enumerate()
+            .all(|(i, id)| *id == Some(&*i.to_string())));
+        assert!(parallel::split_records("<Root><Item><a>").is_err());
+    }
+
+    #[test]
+    fn reader_from_chunks() {
+        let chunks: &[&[u8]] = &[
+            b"<a x='1'>",
+            b"",
+            b"<b>one</b>",
+            b"<c><!-- split ",
+            b"--></c></a>",
+        ];
+        let mut reader = Reader::from_chunks(chunks);
+        let mut names = Vec::new();
+        for event in reader.by_ref() {
+            match event {
+                Ok(Event::Start(tag)) => names.push(tag.name()),
+                Ok(_) => (),
+                Err(error) => {
+                    assert!(matches!(error, Error::SplitToken { offset: 22 }));
+                    break;
+                }
+            }
+        }
+        assert_eq!(names, [&b"a"[..], b"b", b"c"]);
+        assert_eq!(reader.depth(), 2);
+        assert_eq!(reader.chunk_offset(), 19);
+        assert_eq!(reader.remaining(), (&b"<!-- split "[..], &chunks[4..]));
+        assert!(reader.next().is_none());
+        assert_eq!(Reader::from_chunks(&[]).count(), 0);
+
+        // The reader is already past the `<` once it found the tag after the text.
+        let chunks: &[&[u8]] = &[b"<a>text<b", b"/></a>"];
+        let mut reader = Reader::from_chunks(chunks);
+        assert_eq!(reader.by_ref().take_while(Result::is_ok).count(), 2);
+        assert_eq!(reader.remaining(), (&b"<b"[..], &chunks[1..]));
+        let mut reader = Reader::from_chunks(chunks);
+        assert!(matches!(
+            reader.nth(2),
+            Some(Err(Error::SplitToken { offset: 7 }))
+        ));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn async_reader_small_reads() {
+        let (mut tx, rx) = tokio::io::duplex(3);
+        let writer = tokio::spawn(async move {
+            use tokio::io::AsyncWriteExt;
+            tx.write_all(b"<Frames><Frame i='1'>text</Frame><Frame/></Frames>")
+                .await
+                .unwrap();
+        });
+        let mut reader = AsyncReader::new(rx);
+        let mut events = Vec::new();
+        while let Some(event) = reader.next_owned_event().await {
+            events.push(event.unwrap());
+        }
+        writer.await.unwrap();
+        assert_eq!(events.len(), 6);
+        assert!(matches!(&events[2], OwnedEvent::Text(text) if text.content() == b"text"));
+
+        let mut reader = AsyncReader::new(&b"<a><b"[..]);
+        let mut events = Vec::new();
+        while let Some(event) = reader.next_owned_event().await {
+            events.push(event);
+        }
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[1], Err(Error::UnexpectedEof)));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn feed_reader_pieces() {
+        let xml = b"<stream a='1'>hello <b/>world</stream><x";
+        let mut reader = FeedReader::new();
+        let mut events = Vec::new();
+        for piece in xml.chunks(3) {
+            reader.feed(piece);
+            loop {
+                match reader.next_event() {
+                    Some(Ok(event)) => events.push(event.into_owned()),
+                    Some(Err(Error::NeedMoreData)) => break,
+                    other => panic!("unexpected {:?}", other),
+                }
+            }
+        }
+        assert_eq!(events.len(), 5);
+        reader.finish();
+        assert!(matches!(
+            reader.next_event(),
+            Some(Err(Error::UnexpectedEof))
+        ));
+        assert!(reader.next_event().is_none());
+    }
+
+    #[test]
+    fn flash_policy() {
+        let xml = "<cross-domain-policy>\
+                   <allow-access-from domain='a.com' to-ports='*' secure='false'/>\
+                   <allow-access-from to-ports='80'/>\
+                   <nested><allow-access-from domain='hidden'/></nested>\
+                   <allow-access-from domain='b.com'></allow-access-from>\
+                   </cross-domain-policy>\0garbage<";
+        let parsed = policy::Policy::parse(xml).unwrap();
+        let rules = parsed.allow_access_from();
+        assert_eq!(rules.len(), 2);
+        assert!(rules[0].allows_port(843) && !rules[0].secure());
+        assert_eq!((rules[1].domain(), rules[1].to_ports()), ("b.com", None));
+        assert!(!rules[1].allows_port(843) && rules[1].secure());
+        let other = policy::Policy::parse("<other><allow-access-from domain='*'/></other>");
+        assert!(other.unwrap().allow_access_from().is_empty());
+        assert!(policy::is_policy_request(b"<policy-file-request/>\0"));
+        assert!(!policy::is_policy_request(b"<policy-file-request>\0"));
+    }
+
+    #[test]
+    fn reader_xml_decl() {
+        let xml = b"\xEF\xBB\xBF \n<?xml encoding=\"windows-1252\" version = '1.1'?><a/>";
+        let mut reader = Reader::from_bytes(xml);
+        let decl = reader.xml_decl().unwrap();
+        assert_eq!(decl.version(), Some(&b"1.1"[..]));
+        assert_eq!(decl.encoding(), Some(&b"windows-1252"[..]));
+        assert_eq!(decl.standalone(), None);
+        // The BOM is text to a reader that doesn't skip it.
+        assert!(matches!(
+            reader.nth(1),
+            Some(Ok(Event::ProcessingInstruction(_)))
+        ));
+        assert!(Reader::from_str("<a/><?xml version='1.0'?>")
+            .xml_decl()
+            .is_none());
+        assert!(Reader::from_str("<?xml-stylesheet href='a'?>")
+            .xml_decl()
+            .is_none());
+    }
+
+    #[test]
+    fn entity_table() {
+        let mut entities = decode::EntityTable::new();
+        entities
+            .insert("nbsp", "\u{a0}")
+            .insert("product", "Flash")
+            .insert("amp", "ignored");
+        assert_eq!(entities.get("product"), Some("Flash"));
+        entities.insert("product", "Flash Player");
+
+        let mut reader = Reader::from_str("<a title='&product;&nbsp;9'>&amp;&nbsp;&bogus;</a>");
+        let tag = match reader.next() {
+            Some(Ok(Event::Start(tag))) => tag,
+            x => panic!("{:?}", x),
+        };
+        let attr = tag.attributes().next().unwrap().unwrap();
+        assert_eq!(attr.decoded_value_with(&entities), "Flash Player\u{a0}9");
+        let text = match reader.next() {
+            Some(Ok(Event::Text(text))) => text,
+            x => panic!("{:?}", x),
+        };
+        assert_eq!(text.decoded_with(&entities), "&\u{a0}&bogus;");
+        assert_eq!(text.decoded(), "&&nbsp;&bogus;");
+        assert!(matches!(
+            entities.try_decode_str(text.content()),
+            Err(Error::UnknownEntity(11))
+        ));
+        assert!(matches!(
+            entities.decode("no entities".as_bytes()),
+            Cow::Borrowed(_)
+        ));
+        assert_eq!(
+            decode::EntityTable::html().decode_str("&mdash;&unknown;"),
+            "\u{2014}&unknown;"
+        );
+        // The `;` is only looked for as far as the longest name goes.
+        assert_eq!(
+            entities.decode_str("&product;&products;&&&&product;"),
+            "Flash Player&products;&&&Flash Player"
+        );
+    }
+
+    #[test]
+    fn html_dialect() {
+        let xml = "<P class=intro hidden>Hi<BR>there<img src='a.png'></img><br/>\
+                   <script>if (a<b) x = '</p>';</script><STYLE>p>b {}</style><script></script></p>";
+        let mut reader = ReaderBuilder::new()
+            .check_structure(true)
+            .dialect(Dialect::Html)
+            .from_str(xml);
+        let mut events = Vec::new();
+        for event in reader.by_ref() {
+            events.push(match event.unwrap() {
+                Event::Start(tag) => format!("<{}>", tag.name()),
+                Event::End(tag) => format!("</{}>", tag.name()),
+                Event::Empty(tag) => format!("<{}/>", tag.name()),
+                Event::Text(text) => text.content().to_string(),
+                x => panic!("{:?}", x),
+            });
+        }
+        assert_eq!(
+            events,
+            [
+                "<P>",
+                "Hi",
+                "<BR/>",
+                "there",
+                "<img/>",
+                "<br/>",
+                "<script>",
+                "if (a<b) x = '</p>';",
+                "</script>",
+                "<STYLE>",
+                "p>b {}",
+                "</style>",
+                "<script>",
+                "</script>",
+                "</p>",
+            ]
+        );
+        assert_eq!(reader.depth(), 0);
+
+        // Plain XML keeps treating them as ordinary elements.
+        let mut reader = Reader::from_str("<br><script>a<b/></script>");
+        assert_eq!(reader.by_ref().filter(Result::is_ok).count(), 5);
+        assert_eq!(reader.depth(), 1);
+
+        // Going back to XML undoes the leniency HTML came with, but not what was set before.
+        let mut reader = Reader::from_str("<a x=1 y></A>");
+        reader.allow_bare_attributes(true).dialect(Dialect::Html);
+        reader.dialect(Dialect::Xml).check_structure(true);
+        let Some(Ok(Event::Start(tag))) = reader.next() else {
+            unreachable!()
+        };
+        assert!(matches!(
+            tag.attributes().next(),
+            Some(Err(Error::InvalidAttribute(_)))
+        ));
+        assert!(matches!(
+            reader.next(),
+            Some(Err(Error::MismatchedEnd { .. }))
+        ));
+        assert!(reader.attribute_options.bare);
+    }
+
+    #[test]
+    fn fragment() {
+        let xml = "lead <a>1</a><b/> mid <c><d/></c> trail";
+        let mut reader = Reader::from_fragment(xml);
+        reader.check_structure(true);
+        let events: Vec<_> = reader.collect::<Result<_, _>>().unwrap();
+        assert_eq!(events.len(), 10);
+
+        let nodes = tree::Document::parse_fragment(xml).unwrap();
+        let names: Vec<_> = nodes.iter().map(|node| node.name()).collect();
+        assert_eq!(names, [None, Some("a"), Some("b"), None, Some("c"), None]);
+        assert_eq!(nodes[4].children()[0].name(), Some("d"));
+        assert!(tree::Document::parse_fragment("<a></b>").is_err());
+        assert!(tree::Document::parse_fragment("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn escape() {
+        use escape::{escape_attribute, escape_text, TagBuilder};
+        assert!(matches!(escape_text("plain 'text'"), Cow::Borrowed(_)));
+        assert_eq!(escape_text("<a> & \"b\""), "&lt;a&gt; &amp; \"b\"");
+        assert_eq!(
+            escape_attribute("<a> & \"b\""),
+            "&lt;a&gt; &amp; &quot;b&quot;"
+        );
+
+        let tag = TagBuilder::new("Frame")
+            .attr("label", "\"Intro\" & <Outro>")
+            .attr("id", "1")
+            .start();
+        let mut reader = Reader::from_str(&tag);
+        let tag = match reader.next() {
+            Some(Ok(Event::Start(tag))) => tag,
+            x => panic!("{:?}", x),
+        };
+        let attrs: Vec<_> = tag.attributes().map(Result::unwrap).collect();
+        assert_eq!(attrs[0].decoded_value(), "\"Intro\" & <Outro>");
+        assert_eq!(attrs[1].value(), "1");
+        assert_eq!(TagBuilder::new("Empty").empty(), "<Empty/>");
+    }
+
+    #[test]
+    fn rewrite() {
+        use rewrite::{rewrite, Rewrite};
+        let xml = "<?xml version='1.0'?>\r\n<Config>\n\t<A x = '1' ></A>\n\t<B/>  text  <C/>\n</Config>\n";
+        let same = rewrite(Reader::from_str(xml), |_| Rewrite::Keep).unwrap();
+        assert_eq!(same, xml.as_bytes());
+
+        let out = rewrite(Reader::from_str(xml), |event| match event {
+            Event::Start(tag) if tag.name() == "A" => Rewrite::InsertBefore(b"<New/>".to_vec()),
+            Event::Empty(tag) if tag.name() == "B" => Rewrite::InsertAfter(b"<After/>".to_vec()),
+            Event::Text(_) => Rewrite::Replace(b"TEXT".to_vec()),
+            Event::Empty(_) => Rewrite::Delete,
+            _ => Rewrite::Keep,
+        })
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "<?xml version='1.0'?>\r\n<Config>\n\t<New/><A x = '1' ></A>\n\t<B/><After/>  TEXT  \n</Config>\n"
+        );
+
+        // Resuming after text starts from the tag it stopped at.
+        let mut reader = Reader::from_str("a<b/>c");
+        reader.next();
+        assert_eq!(rewrite(reader, |_| Rewrite::Keep).unwrap(), b"<b/>c");
+        assert!(rewrite(Reader::from_str("<a><b"), |_| Rewrite::Keep).is_err());
+    }
+
+    #[test]
+    fn peek() {
+        let mut reader = Reader::from_str("<a>text<b></b></a><c");
+        reader.check_structure(true);
+        reader.next();
+        let (offset, depth) = (reader.offset(), reader.depth());
+        assert!(matches!(reader.peek(), Some(Ok(Event::Text(text))) if text.content() == "text"));
+        assert!(matches!(reader.peek_nth(2), Some(Ok(Event::End(tag))) if tag.name() == "b"));
+        assert_eq!((reader.offset(), reader.depth()), (offset, depth));
+        assert!(matches!(
+            reader.peek_nth(4),
+            Some(Err(Error::UnexpectedEof))
+        ));
+        assert!(matches!(
+            reader.peek_nth(10),
+            Some(Err(Error::UnexpectedEof))
+        ));
+        assert!(matches!(reader.next(), Some(Ok(Event::Text(_)))));
+        assert!(matches!(reader.peek(), Some(Ok(Event::Start(_)))));
+
+        let reader = Reader::from_str("<a/>");
+        assert!(reader.peek_nth(1).is_none());
+    }
+
+    #[test]
+    fn interner() {
+        let mut interner = Interner::new();
+        let frame = interner.intern("Frame");
+        let x = interner.intern(b"x");
+        assert_eq!((frame, x, interner.intern("Frame")), (0, 1, 0));
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.resolve(x), Some(&b"x"[..]));
+        assert_eq!(interner.resolve(2), None);
+
+        let xml = b"<Frame x='1'><frame/></Frame>";
+        let mut reader = Reader::from_bytes(xml);
+        assert!(matches!(reader.peek(), Some(Ok(Event::Start(tag))) if tag.symbol().is_none()));
+        reader.with_interner(&interner);
+        let symbols: Vec<_> = reader
+            .map(|event| match event.unwrap() {
+                Event::Start(tag) | Event::Empty(tag) | Event::End(tag) => tag.symbol(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(symbols, [Some(frame), None, Some(frame)]);
+        let tag = match Reader::from_bytes(xml).next() {
+            Some(Ok(Event::Start(tag))) => tag,
+            x => panic!("{:?}", x),
+        };
+        let attr = tag.attributes().next().unwrap().unwrap();
+        assert_eq!(interner.get(attr.key()), Some(x));
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn stats() {
+        let xml = "<?xml version='1.0'?><!DOCTYPE a><a>\n  <b>text<![CDATA[x]]></b> <c/><!-- skipped --><d><e/></d><<bad></a>";
+        let mut reader = Reader::from_str(xml);
+        reader.recover(true).emit_comments(false);
+        assert_eq!(reader.stats(), Stats::default());
+        let events = reader.by_ref().filter(Result::is_ok).count();
+        let stats = reader.stats();
+        assert_eq!(stats.events(), events);
+        assert_eq!(
+            stats,
+            Stats {
+                start: 3,
+                end: 3,
+                empty: 2,
+                text: 2,
+                comment: 0,
+                processing_instruction: 1,
+                cdata: 1,
+                doctype: 1,
+                recovered: 1,
+                max_depth: 2,
+                bytes: xml.len(),
+            }
+        );
+        // Peeking doesn't count.
+        let reader = Reader::from_str(xml);
+        reader.peek_nth(3);
+        assert_eq!(reader.stats().events(), 0);
+    }
+
+    #[test]
+    fn pretty() {
+        use crate::fmt::{pretty, PrettyOptions};
+        let xml = "<?xml version='1.0'?><!DOCTYPE doc><doc><p>Some <b>bold</b> text</p>\
+                   <code><![CDATA[<raw>]]></code><!-- gone --><a x='say \"hi\"'  y = \"2\" /><e></e></doc>";
+        let options = PrettyOptions::new().comments(false);
+        let out = pretty(xml, &options).unwrap();
+        assert_eq!(
+            out,
+            "<?xml version='1.0'?>\n<!DOCTYPE doc>\n<doc>\n  <p>Some <b>bold</b> text</p>\n  \
+             <code><![CDATA[<raw>]]></code>\n  <a x='say \"hi\"' y=\"2\"/>\n  <e></e>\n</doc>\n"
+        );
+        // Formatting is stable.
+        assert_eq!(pretty(&out, &options).unwrap(), out);
+        assert_eq!(pretty("", &options).unwrap(), "");
+        assert!(pretty("<a b=>", &options).is_err());
+    }
+
+    #[test]
+    fn minify() {
+        use crate::fmt::{minify, minify_with, pretty, MinifyOptions, PrettyOptions};
+        let xml = "<?xml version='1.0'?>\n<doc>\n  <p>Some <b>bold</b> text</p>\n  \
+                   <a x='say \"hi\"'  y = \"2\" ></a>\n  <c><!-- only --></c>\n  <e/>\n</doc>\n";
+        let min = "<?xml version='1.0'?><doc><p>Some <b>bold</b> text</p>\
+                   <a x='say \"hi\"' y=\"2\"/><c/><e/></doc>";
+        assert_eq!(minify(xml).unwrap(), min);
+        assert_eq!(
+            minify_with(xml, MinifyOptions::new().comments(true)).unwrap(),
+            min.replace("<c/>", "<c><!-- only --></c>")
+        );
+        // Minifying undoes pretty-printing, as far as whitespace goes.
+        let pretty = pretty(min, &PrettyOptions::new()).unwrap();
+        assert_eq!(minify(&pretty).unwrap(), min);
+        assert_eq!(minify("<a>").unwrap(), "<a>");
+        assert!(minify("<a></b").is_err());
+    }
+
+    #[test]
+    fn diff() {
+        use crate::diff::{diff, equivalent, DiffOptions};
+        let a =
+            "<doc>\n  <item b='2' a='1'>Tom &amp; Jerry</item>\n  <!-- note -->\n  <e/>\n</doc>";
+        let b = "<doc><item b=\"2\" a=\"1\">Tom &#38;   Jerry</item><e></e></doc>";
+        let strict = DiffOptions::new();
+        let lenient = DiffOptions::new()
+            .ignore_whitespace(true)
+            .ignore_comments(true);
+        assert!(equivalent(a, a, strict).unwrap());
+        assert!(!equivalent(a, b, strict).unwrap());
+        assert!(equivalent(a, b, lenient).unwrap());
+        assert!(!equivalent(a, b, lenient.ignore_comments(false)).unwrap());
+
+        let c = "<doc><item a='1' b='2'>Tom &amp; Jerry</item><e/></doc>";
+        assert!(!equivalent(a, c, lenient).unwrap());
+        let divergence = diff(a, c, lenient.ignore_attribute_order(false))
+            .unwrap()
+            .unwrap();
+        assert_eq!((divergence.left.line, divergence.left.column), (2, 3));
+        assert_eq!(divergence.right.byte, 5);
+        assert!(equivalent(a, c, lenient.ignore_attribute_order(true)).unwrap());
+
+        // Running out of events diverges at the end of the shorter document.
+        let divergence = diff("<a/>", "<a/><b/>", strict).unwrap().unwrap();
+        assert_eq!((divergence.left.byte, divergence.right.byte), (4, 4));
+        assert!(diff("<a/>", "<a/><b", strict).is_err());
+        assert!(equivalent("<a><![CDATA[<x>]]></a>", "<a>&lt;x&gt;</a>", strict).unwrap());
+    }
+
+    #[test]
+    fn with_attribute() {
+        let tag = match Reader::from_str("<Frame\n  id='1'\n  label=\"a\"\n  id='dup'\n>").next() {
+            Some(Ok(Event::Start(tag))) => tag,
+            x => panic!("{:?}", x),
+        };
+        let changed = tag.with_attribute("label", "it's \"b\"");
+        assert_eq!(
+            changed.content(),
+            "  id='1'\n  label=\"it's &quot;b&quot;\"\n  id='dup'\n"
+        );
+        let changed = tag.with_attribute("id", "'2'");
+        assert_eq!(
+            changed.content(),
+            "  id='&apos;2&apos;'\n  label=\"a\"\n  id='dup'\n"
+        );
+        let removed = tag.without_attribute("id");
+        assert_eq!(removed.content(), "label=\"a\"\n");
+        let removed = removed
+            .without_attribute("label")
+            .without_attribute("missing");
+        assert_eq!(removed.content(), "");
+        let added = removed.with_attribute("new", "1");
+        assert_eq!(added.content(), "new=\"1\"");
+        assert_eq!(added.as_tag().find_attribute("new").unwrap().value(), "1");
+        assert_eq!(added.name(), "Frame");
+        #[cfg(feature = "std")]
+        {
+            let mut writer = Writer::new(Vec::new());
+            writer.write_event(&Event::Start(added.as_tag())).unwrap();
+            assert_eq!(writer.into_inner(), b"<Frame new=\"1\">");
+        }
+
+        let tag = match Reader::from_str("<a x='1' y='2'/>").next() {
+            Some(Ok(Event::Empty(tag))) => tag,
+            x => panic!("{:?}", x),
+        };
+        assert_eq!(tag.without_attribute("x").content(), "y='2'");
+        assert_eq!(tag.without_attribute("y").content(), "x='1'");
+    }
+
+    #[test]
+    fn attribute_quote_and_span() {
+        let content = "a='1' b = \"it's\" c=3 d";
+        let mut attrs = AttributeIter::new(content).unquoted(true).bare(true);
+        let mut next = || attrs.next().unwrap().unwrap();
+        let (a, b, c, d) = (next(), next(), next(), next());
+        assert_eq!(a.quote_char(), Some(b'\''));
+        assert_eq!(&content[a.span()], "a='1'");
+        assert_eq!(b.quote_char(), Some(b'"'));
+        assert_eq!(&content[b.span()], "b = \"it's\"");
+        assert_eq!(c.quote_char(), None);
+        assert_eq!(&content[c.span()], "c=3");
+        assert_eq!(d.quote_char(), None);
+        assert_eq!(&content[d.span()], "d");
+    }
+
+    #[test]
+    fn text_trim_info() {
+        let reader = Reader::from_str("<a> x </a><!--c--><b>y</b>");
+        let trims = reader
+            .filter_map(|event| match event.unwrap() {
+                Event::Text(text) | Event::Comment(text) => Some(text.trim_info()),
+                _ => None,
+            })
+            .map(|info| (info.leading, info.trailing))
+            .collect::<Vec<_>>();
+        assert_eq!(trims, [(1, 1), (4, 3), (0, 0)]);
+        assert_eq!(Text::new("plain").trim_info(), TrimInfo::default());
+    }
+
+    #[test]
+    fn reader_honor_xml_space() {
+        let xml = "<a xml:space='preserve'><b> 1 </b><c xml:space='default'> 2 </c> </a>\
+                   <d xml:space='preserve'/><e> 3 </e>";
+        let mut reader = Reader::from_str(xml);
+        reader.honor_xml_space(true);
+        let texts = reader
+            .filter_map(|event| match event.unwrap() {
+                Event::Text(text) => Some(text.content()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(texts, [" 1 ", "2", " ", "3"]);
+    }
+
+    #[test]
+    fn reader_checkpoint() {
+        let xml = "<a><b>1</b><c>2</c></a>";
+        let mut reader = Reader::from_str(xml);
+        reader.check_structure(true);
+        reader.next(); // <a>
+        reader.next(); // <b>
+        let checkpoint = reader.checkpoint();
+        let rest = reader.by_ref().map(Result::unwrap).count();
+        assert_eq!(rest, 6);
+        reader.restore(checkpoint.clone());
+        assert_eq!(reader.depth(), 2);
+        assert_eq!(reader.by_ref().map(Result::unwrap).count(), rest);
+        reader.restore(checkpoint);
+        assert!(matches!(reader.next(), Some(Ok(Event::Text(text))) if text.content() == "1"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn reader_restore_other_input() {
+        let checkpoint = Reader::from_str("<a/>").checkpoint();
+        Reader::from_str("<b/>").restore(checkpoint);
+    }
+
+    #[test]
+    fn reader_split_off() {
+        let mut reader = Reader::from_bytes(b"<a>text<b/></a>");
+        reader.check_structure(true).emit_comments(false);
+        reader.next(); // <a>
+        reader.next(); // text
+        assert_eq!(reader.remaining(), b"<b/></a>");
+        let mut rest = reader.split_off();
+        assert!(reader.next().is_none());
+        assert_eq!(reader.remaining(), b"");
+        assert!(matches!(rest.next(), Some(Ok(Event::Empty(_)))));
+        assert_eq!(rest.last_event_span(), 0..4);
+        assert!(matches!(
+            rest.next(),
+            Some(Err(Error::UnexpectedEndTag { .. }))
+        ));
+    }
+
+    #[test]
+    fn reader_current_path() {
+        let mut reader = Reader::from_bytes(b"<a><b>text</b><c></c></a>");
+        reader.check_structure(true);
+        let mut paths = Vec::new();
+        while let Some(event) = reader.next() {
+            event.unwrap();
+            paths.push(reader.current_path().collect::<Vec<_>>().join(&b'/'));
+        }
+        assert_eq!(
+            paths,
+            ["a", "a/b", "a/b", "a", "a/c", "a", ""].map(str::as_bytes)
+        );
+        let mut unchecked = Reader::from_str("<a>");
+        unchecked.next();
+        assert!(unchecked.current_path().next().is_none());
+    }
+
+    #[test]
+    fn validate_like_reader() {
+        let cases = [
+            "",
+            "text only",
+            "<a/>",
+            "<a b='/'></a>",
+            "<a b='x/'/>",
+            "<a></a/>",
+            "<?xml version='1.0'?><!DOCTYPE a [<!ENTITY x '>'><!-- ] -->]><a><![CDATA[</b>]]></a>",
+            "<a><!-- <b> --></a> tail",
+            "<a>",
+            "</a>",
+            "<a></b>",
+            "<a><b></a></b>",
+            "<>",
+            "</>",
+            "<1/>",
+            "<!ELEMENT a>",
+            "<a><!-- unterminated",
+            "<a b='>'",
+            "<? x?>",
+            "<",
+        ];
+        for xml in cases.iter() {
+            let mut reader = Reader::from_str(xml);
+            reader.check_structure(true);
+            let expected = reader.all(|event| event.is_ok());
+            assert_eq!(validate(xml), expected, "{:?}", xml);
+        }
+        let deep = "<a>".repeat(129) + &"</a>".repeat(129);
+        assert!(!validate(&deep));
+        assert!(validate(&deep[3..deep.len() - 4]));
+    }
+
+    #[test]
+    #[cfg(feature = "fuzz")]
+    fn fuzz_short_inputs() {
+        // Every input up to a few bytes long made of the characters that matter to the reader.
+        let alphabet = b"<>/!?-=' a[]";
+        let flags = [0x00, 0x03, 0x07, 0x1b, 0x3c, 0xff];
+        let mut inputs = vec![Vec::new()];
+        for _ in 0..4 {
+            let longer = inputs
+                .iter()
+                .flat_map(|input: &Vec<u8>| {
+                    alphabet.iter().map(move |&ch| [&input[..], &[ch]].concat())
+                })
+                .collect::<Vec<_>>();
+            for input in &longer {
+                for &flag in flags.iter() {
+                    fuzz::parse_all(&[&[flag], &input[..]].concat());
+                }
+            }
+            inputs = longer;
+        }
+    }
+
+    #[test]
+    fn reader_limits() {
+        let text_limit = |xml, limit| {
+            let mut reader = Reader::from_str(xml);
+            reader.max_text_len(Some(limit));
+            reader
+                .map(|event| event.map(drop))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        assert!(text_limit("<a> 1234 </a>", 6).is_ok());
+        assert!(matches!(
+            text_limit("<a> 12345 </a>", 6),
+            Err(Error::LimitExceeded {
+                limit: Limit::TextLen,
+                offset: 3
+            })
+        ));
+        assert!(text_limit("1234567", 6).is_err());
+
+        let mut reader = Reader::from_str("<a x='1' y='2' z='3'/>");
+        reader.max_attributes(Some(2));
+        let Some(Ok(Event::Empty(tag))) = reader.next() else {
+            unreachable!()
+        };
+        let attrs = tag.attributes().collect::<Vec<_>>();
+        assert_eq!(attrs.len(), 3);
+        assert!(matches!(
+            attrs[2],
+            Err(Error::LimitExceeded {
+                limit: Limit::Attributes,
+                offset: 12
+            })
+        ));
+
+        let mut reader = Reader::from_str("<a x='1' 0 y='2' z='3'/>");
+        reader.max_attributes(Some(2)).recover(true);
+        let Some(Ok(Event::Empty(tag))) = reader.next() else {
+            unreachable!()
+        };
+        let attrs = tag.attributes().collect::<Vec<_>>();
+        assert_eq!(attrs.len(), 3);
+        assert!(matches!(
+            attrs[2],
+            Err(Error::LimitExceeded {
+                limit: Limit::Attributes,
+                offset: 14
+            })
+        ));
+
+        let mut reader = Reader::from_str("<a x='1' y='long' z='3'/>");
+        reader.max_attribute_value_len(Some(3)).recover(true);
+        let Some(Ok(Event::Empty(tag))) = reader.next() else {
+            unreachable!()
+        };
+        let attrs = tag.attributes().collect::<Vec<_>>();
+        assert_eq!(attrs.len(), 2);
+        assert!(matches!(attrs[1], Err(Error::ValueTooLong { offset: 6 })));
+
+        let mut reader = Reader::from_str("<a><b/></a>");
+        reader.max_events(Some(2));
+        assert_eq!(reader.filter(Result::is_err).count(), 1);
+    }
+
+    #[test]
+    fn skipped_comments_dont_recurse() {
+        let xml = format!("<a>{}</a>", "<!-- x -->".repeat(200_000));
+        let mut reader = Reader::from_str(&xml);
+        reader.emit_comments(false);
+        assert_eq!(reader.map(Result::unwrap).count(), 2);
+    }
+
+    #[test]
+    fn skipped_void_end_tags_dont_recurse() {
+        let xml = format!("<p>{}</p>", "</br>".repeat(200_000));
+        let mut reader = Reader::from_str(&xml);
+        reader.dialect(Dialect::Html);
+        assert_eq!(reader.map(Result::unwrap).count(), 2);
+    }
 }
@@ -0,0 +1,167 @@
+//! Namespace resolution layered on top of the event stream.
+
+use crate::{split_qname, Attribute, AttributeIter, Error, Event, Tag};
+use alloc::vec::Vec;
+use core::{marker::PhantomData, str};
+
+/// Namespace bound to the reserved `xml` prefix.
+pub const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// Tracks `xmlns` and `xmlns:prefix` bindings across the events of a [`Reader`](crate::Reader)
+/// to resolve the namespace URIs of tags and attributes.
+///
+/// This is a separate layer so that reading without namespaces stays as fast as before.\
+/// Every event has to be passed to [`NamespaceResolver::push`] in order,
+/// after which the names in it can be resolved.
+///
+/// # Example
+/// ```rust
+/// use lazyxml::{Event, NamespaceResolver, Reader};
+///
+/// let xml = "<svg xmlns='http://www.w3.org/2000/svg' xmlns:x='urn:x'><x:rect/></svg>";
+/// let mut resolver = NamespaceResolver::<str>::new();
+/// let mut uris = Vec::new();
+/// for event in Reader::from_str(xml) {
+///     let event = event.unwrap();
+///     resolver.push(&event).unwrap();
+///     if let Event::Start(tag) | Event::Empty(tag) = &event {
+///         uris.push(resolver.resolve_tag(tag));
+///     }
+/// }
+/// assert_eq!(uris, [Some("http://www.w3.org/2000/svg"), Some("urn:x")]);
+/// ```
+pub struct NamespaceResolver<'xml, T: ?Sized> {
+    /// Bound prefixes (`None` being the default namespace) with their URIs, innermost last.
+    bindings: Vec<(Option<&'xml [u8]>, &'xml [u8])>,
+    /// Number of bindings from before each open element.
+    scopes: Vec<usize>,
+    /// Whether the last element has ended, so its bindings go out of scope with the next event.
+    pending_pop: bool,
+    marker: PhantomData<&'xml T>,
+}
+
+impl<'xml, T: ?Sized> NamespaceResolver<'xml, T> {
+    /// Constructs a new resolver, with nothing bound but the `xml` prefix.
+    pub const fn new() -> Self {
+        Self {
+            bindings: Vec::new(),
+            scopes: Vec::new(),
+            pending_pop: false,
+            marker: PhantomData,
+        }
+    }
+
+    /// Updates the bindings for an event, given the attributes if it opens an element
+    /// and whether it closes one.
+    fn push_raw(
+        &mut self,
+        opens: Option<AttributeIter<'xml, [u8]>>,
+        closes: bool,
+    ) -> Result<(), Error> {
+        if self.pending_pop {
+            if let Some(len) = self.scopes.pop() {
+                self.bindings.truncate(len);
+            }
+        }
+        if let Some(attributes) = opens {
+            self.scopes.push(self.bindings.len());
+            for attr in attributes {
+                let attr = attr?;
+                match split_qname(attr.key()) {
+                    (None, b"xmlns") => self.bindings.push((None, attr.value())),
+                    (Some(b"xmlns"), prefix) => self.bindings.push((Some(prefix), attr.value())),
+                    _ => (),
+                }
+            }
+        }
+        // The bindings stay in scope for the closing tag's own names.
+        self.pending_pop = closes;
+        Ok(())
+    }
+
+    fn lookup(&self, prefix: Option<&[u8]>) -> Option<&'xml [u8]> {
+        if prefix == Some(b"xml") {
+            return Some(XML_NAMESPACE.as_bytes());
+        }
+        let (_, uri) = self
+            .bindings
+            .iter()
+            .rev()
+            .find(|(bound, _)| *bound == prefix)?;
+        // `xmlns=""` unbinds the default namespace.
+        Some(*uri).filter(|uri| !uri.is_empty())
+    }
+}
+
+impl<'xml> NamespaceResolver<'xml, [u8]> {
+    /// Updates the bindings in scope with the next event from the reader.
+    ///
+    /// Errors from parsing the attributes of a start tag are passed through.
+    pub fn push(&mut self, event: &Event<'xml, [u8]>) -> Result<(), Error> {
+        match event {
+            Event::Start(tag) => self.push_raw(Some(tag.attributes()), false),
+            Event::Empty(tag) => self.push_raw(Some(tag.attributes()), true),
+            Event::End(_) => self.push_raw(None, true),
+            _ => self.push_raw(None, false),
+        }
+    }
+
+    /// Resolves the namespace URI of a tag from the last pushed event,
+    /// which is the default namespace if its name has no prefix.
+    pub fn resolve_tag(&self, tag: &Tag<'xml, [u8]>) -> Option<&'xml [u8]> {
+        self.lookup(tag.prefix())
+    }
+
+    /// Resolves the namespace URI of an attribute from the last pushed event.
+    ///
+    /// Attributes without a prefix are in no namespace, rather than the default one.
+    pub fn resolve_attribute(&self, attr: &Attribute<'xml, [u8]>) -> Option<&'xml [u8]> {
+        attr.prefix().and_then(|prefix| self.lookup(Some(prefix)))
+    }
+}
+
+impl<'xml> NamespaceResolver<'xml, str> {
+    /// Updates the bindings in scope with the next event from the reader.
+    ///
+    /// Errors from parsing the attributes of a start tag are passed through.
+    pub fn push(&mut self, event: &Event<'xml, str>) -> Result<(), Error> {
+        let attributes = |tag: &Tag<'xml, str>| AttributeIter {
+            content: tag.content.as_bytes(),
+            offset: 0,
+            options: tag.options,
+            base: tag.content_start,
+            count: 0,
+        };
+        match event {
+            Event::Start(tag) => self.push_raw(Some(attributes(tag)), false),
+            Event::Empty(tag) => self.push_raw(Some(attributes(tag)), true),
+            Event::End(_) => self.push_raw(None, true),
+            _ => self.push_raw(None, false),
+        }
+    }
+
+    /// Resolves the namespace URI of a tag from the last pushed event,
+    /// which is the default namespace if its name has no prefix.
+    pub fn resolve_tag(&self, tag: &Tag<'xml, str>) -> Option<&'xml str> {
+        self.lookup(tag.prefix().map(str::as_bytes)).map(to_str)
+    }
+
+    /// Resolves the namespace URI of an attribute from the last pushed event.
+    ///
+    /// Attributes without a prefix are in no namespace, rather than the default one.
+    pub fn resolve_attribute(&self, attr: &Attribute<'xml, str>) -> Option<&'xml str> {
+        let prefix = attr.prefix()?;
+        self.lookup(Some(prefix.as_bytes())).map(to_str)
+    }
+}
+
+impl<'xml, T: ?Sized> Default for NamespaceResolver<'xml, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn to_str(uri: &[u8]) -> &str {
+    // SAFETY: URIs are attribute values from `str` events, split around ASCII quotes.
+    unsafe { str::from_utf8_unchecked(uri) }
+}
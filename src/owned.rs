@@ -0,0 +1,291 @@
+//! Owned counterparts of the borrowed event types, detached from the input's lifetime.
+
+use crate::{
+    escape::escape_attribute, Attribute, AttributeIter, AttributeOptions, Event, Tag, Text,
+};
+use alloc::{
+    borrow::ToOwned,
+    string::{String, ToString},
+};
+use core::ops::Range;
+use core::{borrow::Borrow, fmt};
+
+/// Owned version of [`Event`], created with [`Event::into_owned`].
+pub enum OwnedEvent<T: ?Sized + ToOwned> {
+    /// Owned XML `<Start>` tag.
+    Start(OwnedTag<T>),
+    /// Owned XML `</End>` tag.
+    End(OwnedTag<T>),
+    /// Owned XML `<Empty />` tag.
+    Empty(OwnedTag<T>),
+    /// Owned arbitrary text, inside or outside of XML elements.
+    Text(OwnedText<T>),
+    /// Owned XML `<!-- Comment -->`.
+    Comment(OwnedText<T>),
+    /// Owned XML `<?Target content?>` processing instruction.
+    ProcessingInstruction(OwnedTag<T>),
+    /// Owned XML `<![CDATA[ ... ]]>` section.
+    CData(OwnedText<T>),
+    /// Owned XML `<!DOCTYPE ...>` declaration.
+    DocType(OwnedText<T>),
+}
+
+/// Owned version of [`Tag`].
+#[derive(Debug)]
+pub struct OwnedTag<T: ?Sized + ToOwned> {
+    content: T::Owned,
+    name: T::Owned,
+    options: AttributeOptions,
+}
+
+/// Owned version of [`Attribute`].
+#[derive(Debug)]
+pub struct OwnedAttribute<T: ?Sized + ToOwned> {
+    key: T::Owned,
+    value: T::Owned,
+}
+
+/// Owned version of [`Text`].
+#[derive(Debug)]
+pub struct OwnedText<T: ?Sized + ToOwned> {
+    content: T::Owned,
+}
+
+impl<T: ?Sized + ToOwned + fmt::Debug> fmt::Debug for OwnedEvent<T>
+where
+    T::Owned: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OwnedEvent::Start(tag) => f.debug_tuple("Start").field(tag).finish(),
+            OwnedEvent::End(tag) => f.debug_tuple("End").field(tag).finish(),
+            OwnedEvent::Empty(tag) => f.debug_tuple("Empty").field(tag).finish(),
+            OwnedEvent::Text(text) => f.debug_tuple("Text").field(text).finish(),
+            OwnedEvent::Comment(text) => f.debug_tuple("Comment").field(text).finish(),
+            OwnedEvent::ProcessingInstruction(tag) => {
+                f.debug_tuple("ProcessingInstruction").field(tag).finish()
+            }
+            OwnedEvent::CData(text) => f.debug_tuple("CData").field(text).finish(),
+            OwnedEvent::DocType(text) => f.debug_tuple("DocType").field(text).finish(),
+        }
+    }
+}
+
+impl<'xml, T: ?Sized + ToOwned> Event<'xml, T> {
+    /// Copies the event's data into an [`OwnedEvent`] that can outlive the input.
+    pub fn into_owned(self) -> OwnedEvent<T> {
+        match self {
+            Event::Start(tag) => OwnedEvent::Start(tag.into_owned()),
+            Event::End(tag) => OwnedEvent::End(tag.into_owned()),
+            Event::Empty(tag) => OwnedEvent::Empty(tag.into_owned()),
+            Event::Text(text) => OwnedEvent::Text(text.into_owned()),
+            Event::Comment(text) => OwnedEvent::Comment(text.into_owned()),
+            Event::ProcessingInstruction(tag) => {
+                OwnedEvent::ProcessingInstruction(tag.into_owned())
+            }
+            Event::CData(text) => OwnedEvent::CData(text.into_owned()),
+            Event::DocType(text) => OwnedEvent::DocType(text.into_owned()),
+        }
+    }
+}
+
+impl<'xml, T: ?Sized + ToOwned> Tag<'xml, T> {
+    /// Copies the tag's data into an [`OwnedTag`] that can outlive the input.
+    pub fn into_owned(self) -> OwnedTag<T> {
+        OwnedTag {
+            content: self.content.to_owned(),
+            name: self.name.to_owned(),
+            options: self.options,
+        }
+    }
+}
+
+impl<'xml> Tag<'xml, str> {
+    /// Copies the tag into an [`OwnedTag`] with the value of an attribute set.
+    ///
+    /// See [`OwnedTag::with_attribute`] for details.
+    pub fn with_attribute(&self, key: &str, value: &str) -> OwnedTag<str> {
+        self.clone().into_owned().with_attribute(key, value)
+    }
+
+    /// Copies the tag into an [`OwnedTag`] without the attributes with the given key.
+    ///
+    /// See [`OwnedTag::without_attribute`] for details.
+    pub fn without_attribute(&self, key: &str) -> OwnedTag<str> {
+        self.clone().into_owned().without_attribute(key)
+    }
+}
+
+impl<'xml, T: ?Sized + ToOwned> Attribute<'xml, T> {
+    /// Copies the attribute's data into an [`OwnedAttribute`] that can outlive the input.
+    pub fn into_owned(self) -> OwnedAttribute<T> {
+        OwnedAttribute {
+            key: self.key().to_owned(),
+            value: self.value().to_owned(),
+        }
+    }
+}
+
+impl<'xml, T: ?Sized + ToOwned> Text<'xml, T> {
+    /// Copies the text's data into an [`OwnedText`] that can outlive the input.
+    pub fn into_owned(self) -> OwnedText<T> {
+        OwnedText {
+            content: self.content.to_owned(),
+        }
+    }
+}
+
+impl<T: ?Sized + ToOwned> OwnedTag<T> {
+    /// Gets the content of the tag this instance represents.
+    ///
+    /// See [`Tag::content`] for details.
+    pub fn content(&self) -> &T {
+        self.content.borrow()
+    }
+
+    /// Gets the name of the tag this instance represents.
+    ///
+    /// See [`Tag::name`] for details.
+    pub fn name(&self) -> &T {
+        self.name.borrow()
+    }
+
+    /// Returns an iterator over the tag's attributes, if any.
+    pub fn attributes(&self) -> AttributeIter<'_, T> {
+        AttributeIter {
+            content: self.content.borrow(),
+            offset: 0,
+            options: self.options,
+            base: 0,
+            count: 0,
+        }
+    }
+
+    /// Borrows the tag as a [`Tag`], such as to write it with
+    /// [`Writer::write_event`](crate::Writer::write_event).
+    pub fn as_tag(&self) -> Tag<'_, T> {
+        Tag {
+            options: self.options,
+            ..Tag::new(self.name.borrow(), self.content.borrow())
+        }
+    }
+}
+
+impl OwnedTag<str> {
+    /// Sets the value of the attribute with the given key, escaping it.
+    ///
+    /// An existing attribute keeps its place and quotes, and only has its value replaced.
+    /// Otherwise, the attribute is added after the others with double quotes.\
+    /// Everything else in the tag is kept as it was, down to the whitespace.
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::{Event, Reader};
+    ///
+    /// let Some(Ok(Event::Empty(tag))) = Reader::from_str("<Item  b='1'   a=\"2\" />").next() else { unreachable!() };
+    /// let tag = tag.with_attribute("a", "<3").with_attribute("c", "4").without_attribute("b");
+    /// assert_eq!(tag.content(), "a=\"&lt;3\" c=\"4\" ");
+    /// ```
+    pub fn with_attribute(mut self, key: &str, value: &str) -> Self {
+        let escaped = escape_attribute(value);
+        match self.find(key) {
+            Some((_, value_span, Some(b'\''))) => {
+                let escaped = escaped.replace('\'', "&apos;");
+                self.content.replace_range(value_span, &escaped);
+            }
+            Some((_, value_span, _)) => self.content.replace_range(value_span, &escaped),
+            None => {
+                let end = self.content.trim_end().len();
+                let mut attribute = match end {
+                    0 => String::new(),
+                    _ => " ".to_string(),
+                };
+                attribute.push_str(key);
+                attribute.push_str("=\"");
+                attribute.push_str(&escaped);
+                attribute.push('"');
+                self.content.insert_str(end, &attribute);
+            }
+        }
+        self
+    }
+
+    /// Removes every attribute with the given key, along with the whitespace before it.
+    ///
+    /// Everything else in the tag is kept as it was, see [`OwnedTag::with_attribute`].
+    pub fn without_attribute(mut self, key: &str) -> Self {
+        while let Some((mut start, value_span, quote)) = self.find(key) {
+            let bytes = self.content.as_bytes();
+            let mut end = value_span.end + quote.is_some() as usize;
+            while start > 0 && bytes[start - 1].is_ascii_whitespace() {
+                start -= 1;
+            }
+            // The first attribute takes the whitespace after it instead.
+            if start == 0 {
+                while bytes.get(end).is_some_and(u8::is_ascii_whitespace) {
+                    end += 1;
+                }
+            }
+            self.content.replace_range(start..end, "");
+        }
+        self
+    }
+
+    /// Finds where the first attribute with the given key starts, the span of its value,
+    /// and the quote around it if any.
+    ///
+    /// Malformed attributes end the search, as there's no telling where the next one starts.
+    fn find(&self, key: &str) -> Option<(usize, Range<usize>, Option<u8>)> {
+        let attr = self
+            .attributes()
+            .map_while(Result::ok)
+            .find(|attr| attr.key() == key)?;
+        Some((attr.key_span().start, attr.value_span(), attr.quote_char()))
+    }
+}
+
+impl<T: ?Sized + ToOwned> Clone for OwnedTag<T> {
+    fn clone(&self) -> Self {
+        Self {
+            content: self.content.borrow().to_owned(),
+            name: self.name.borrow().to_owned(),
+            options: self.options,
+        }
+    }
+}
+
+impl<T: ?Sized + ToOwned> OwnedAttribute<T> {
+    /// Gets the key of the attribute this instance represents.
+    pub fn key(&self) -> &T {
+        self.key.borrow()
+    }
+
+    /// Gets the raw and potentially escaped value of the attribute this instance represents.
+    pub fn value(&self) -> &T {
+        self.value.borrow()
+    }
+}
+
+impl<T: ?Sized + ToOwned> Clone for OwnedAttribute<T> {
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.borrow().to_owned(),
+            value: self.value.borrow().to_owned(),
+        }
+    }
+}
+
+impl<T: ?Sized + ToOwned> OwnedText<T> {
+    /// Gets the raw and potentially escaped content of the text this instance represents.
+    pub fn content(&self) -> &T {
+        self.content.borrow()
+    }
+}
+
+impl<T: ?Sized + ToOwned> Clone for OwnedText<T> {
+    fn clone(&self) -> Self {
+        Self {
+            content: self.content.borrow().to_owned(),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! Parallel parsing of record-style documents, where the root holds a long list of elements.
+//!
+//! The input is split at the boundaries of the root's children, only scanning for `<` and `>`
+//! as [`Reader::skip_current_element`] does, and every child is then parsed by its own
+//! [`Reader`] on the rayon thread pool.
+
+use crate::{Error, Event, Reader};
+use alloc::vec::Vec;
+use rayon::prelude::*;
+
+/// Splits `xml` into the markup of each child element of the root, in document order.
+///
+/// Text, comments and the like directly inside the root are left out, as is everything
+/// outside of it.\
+/// Only the tags of the root and its children are parsed, so errors deeper down
+/// go unnoticed until the records themselves are parsed.
+///
+/// # Example
+/// ```rust
+/// use lazyxml::parallel::split_records;
+///
+/// let records = split_records("<?xml version='1.0'?><Root><Item id='1'/> <Item>2</Item></Root>");
+/// assert_eq!(records.unwrap(), ["<Item id='1'/>", "<Item>2</Item>"]);
+/// ```
+pub fn split_records(xml: &str) -> Result<Vec<&str>, Error> {
+    let mut reader = Reader::from_str(xml);
+    loop {
+        match reader.next() {
+            Some(Ok(Event::Start(_))) => break,
+            Some(Ok(Event::Empty(_))) | None => return Ok(Vec::new()),
+            Some(Err(error)) => return Err(error),
+            Some(Ok(_)) => (),
+        }
+    }
+    let mut records = Vec::new();
+    loop {
+        match reader.next() {
+            Some(Ok(Event::Start(_))) => {
+                let start = reader.event_start;
+                reader.skip_element(false)?;
+                records.push(&xml[start..reader.event_end]);
+            }
+            Some(Ok(Event::Empty(_))) => records.push(&xml[reader.event_start..reader.event_end]),
+            Some(Ok(Event::End(_))) | None => break,
+            Some(Err(error)) => return Err(error),
+            Some(Ok(_)) => (),
+        }
+    }
+    Ok(records)
+}
+
+/// Hands a [`Reader`] over each record of `xml` to `f` on the rayon thread pool,
+/// collecting the results in document order.
+///
+/// Records are split with [`split_records`], so each reader starts at the record's start tag,
+/// and its offsets are relative to the record.
+///
+/// # Example
+/// ```rust
+/// use lazyxml::parallel::map_records;
+///
+/// let xml = "<Root><Item><a/><b/></Item><Item/><Item><c/></Item></Root>";
+/// let counts = map_records(xml, |reader| reader.count()).unwrap();
+/// assert_eq!(counts, [4, 1, 3]);
+/// ```
+pub fn map_records<'xml, F, R>(xml: &'xml str, f: F) -> Result<Vec<R>, Error>
+where
+    F: Fn(Reader<'xml, str>) -> R + Sync + Send,
+    R: Send,
+{
+    let records = split_records(xml)?;
+    Ok(records
+        .into_par_iter()
+        .map(|record| f(Reader::from_str(record)))
+        .collect())
+}
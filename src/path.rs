@@ -0,0 +1,184 @@
+//! Element-name path queries over the event stream, for picking out elements without a tree.
+
+use crate::{decode::eq_decoded, Attribute, AttributeIter, ChildIter, Error, Event, Reader, Tag};
+use alloc::vec::Vec;
+
+/// Iterator over the elements matching a path, created with [`Reader::find_path`].
+///
+/// Yields the start (or empty) tag of every match in document order.
+/// Everything that can't contain a match is skipped over without producing events,
+/// as with [`Reader::skip_current_element`].\
+/// The iterator ends once the element the query started in is closed, or at the end of the input.
+pub struct PathMatches<'r, 'p, 'xml, T: ?Sized> {
+    reader: &'r mut Reader<'xml, T>,
+    steps: Vec<Step<'p>>,
+    /// Depth of the reader when the query started, which the path is relative to.
+    base: usize,
+    /// How many of the elements open past `base` match the start of the path.
+    matched: usize,
+    /// Whether the last match was a start tag, which has children to look at.
+    in_match: bool,
+    done: bool,
+}
+
+/// Step of a path, as in `Name` or `Name[@key=value]`.
+struct Step<'p> {
+    name: &'p str,
+    predicate: Option<(&'p str, &'p str)>,
+}
+
+impl<'p> Step<'p> {
+    fn parse(step: &'p str) -> Self {
+        let predicate = step
+            .split_once("[@")
+            .and_then(|(name, rest)| Some((name, rest.strip_suffix(']')?.split_once('=')?)));
+        match predicate {
+            Some((name, (key, value))) => Self {
+                name,
+                predicate: Some((key, value.trim_matches(|ch| ch == '"' || ch == '\''))),
+            },
+            None => Self {
+                name: step,
+                predicate: None,
+            },
+        }
+    }
+
+    fn matches<'xml, T>(&self, tag: &Tag<'xml, T>) -> bool
+    where
+        T: ?Sized + AsRef<[u8]>,
+        AttributeIter<'xml, T>: Iterator<Item = Result<Attribute<'xml, T>, Error>>,
+    {
+        let name = self.name == "*" || tag.name().as_ref() == self.name.as_bytes();
+        name && self.predicate.is_none_or(|(key, value)| {
+            tag.attributes().map_while(Result::ok).any(|attr| {
+                attr.key().as_ref() == key.as_bytes()
+                    && eq_decoded(attr.value().as_ref(), value.as_bytes())
+            })
+        })
+    }
+}
+
+impl<'xml, T> Reader<'xml, T>
+where
+    T: ?Sized + AsRef<[u8]>,
+    Self: Iterator<Item = Result<Event<'xml, T>, Error>>,
+    AttributeIter<'xml, T>: Iterator<Item = Result<Attribute<'xml, T>, Error>>,
+{
+    /// Returns an iterator over the elements found by following `path` from where the reader is.
+    ///
+    /// Each step of the path is an element name, or `*` for any element,
+    /// optionally followed by a predicate on an attribute's decoded value, as in `Name[@key=value]`
+    /// (quoting the value is optional).\
+    /// At the start of the input, the first step is the root element. Right after an
+    /// [`Event::Start`], it's a child of that element instead, and the query ends with it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::Reader;
+    ///
+    /// let xml = "<Root><Scripts><Script lang='as3'>a</Script><Script lang='js'>b</Script>\
+    ///            </Scripts><Script lang='as3'>not in Scripts</Script></Root>";
+    /// let mut reader = Reader::from_str(xml);
+    /// let mut matches = reader.find_path(&["Root", "Scripts", "Script[@lang=as3]"]);
+    /// let mut bodies = Vec::new();
+    /// while let Some(tag) = matches.next() {
+    ///     assert_eq!(tag.unwrap().name(), "Script");
+    ///     for event in matches.children() {
+    ///         bodies.push(event.unwrap());
+    ///     }
+    /// }
+    /// assert_eq!(bodies.len(), 1);
+    /// ```
+    pub fn find_path<'p>(&mut self, path: &[&'p str]) -> PathMatches<'_, 'p, 'xml, T> {
+        PathMatches {
+            base: self.depth,
+            reader: self,
+            steps: path.iter().map(|step| Step::parse(step)).collect(),
+            matched: 0,
+            in_match: false,
+            done: false,
+        }
+    }
+}
+
+impl<'r, 'p, 'xml, T> PathMatches<'r, 'p, 'xml, T>
+where
+    T: ?Sized,
+    Reader<'xml, T>: Iterator<Item = Result<Event<'xml, T>, Error>>,
+{
+    /// Returns an iterator over the direct children of the match that was just yielded.
+    ///
+    /// This must be called right after this iterator yields a tag, see [`Reader::children`].
+    /// The children of an empty tag are empty.
+    pub fn children(&mut self) -> ChildIter<'_, 'xml, T> {
+        let done = !self.in_match;
+        self.in_match = false;
+        ChildIter {
+            depth: self.reader.depth,
+            reader: self.reader,
+            done,
+        }
+    }
+}
+
+impl<'r, 'p, 'xml, T> Iterator for PathMatches<'r, 'p, 'xml, T>
+where
+    T: ?Sized + AsRef<[u8]>,
+    Reader<'xml, T>: Iterator<Item = Result<Event<'xml, T>, Error>>,
+    AttributeIter<'xml, T>: Iterator<Item = Result<Attribute<'xml, T>, Error>>,
+{
+    type Item = Result<Tag<'xml, T>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.in_match = false;
+        while !self.done {
+            // Nothing inside an element that doesn't match (or fully matched) can match.
+            while self.reader.depth > self.base + self.matched {
+                if let Err(error) = self.reader.skip_element(false) {
+                    self.done = true;
+                    return Some(Err(error));
+                }
+            }
+            let event = match self.reader.next() {
+                Some(Ok(event)) => event,
+                Some(Err(error)) => {
+                    self.done = true;
+                    return Some(Err(error));
+                }
+                None => break,
+            };
+            let depth = self.reader.depth;
+            match event {
+                Event::Start(tag) => {
+                    let level = depth - 1 - self.base;
+                    if level == self.matched
+                        && self.steps.get(level).is_some_and(|step| step.matches(&tag))
+                    {
+                        self.matched += 1;
+                        if self.matched == self.steps.len() {
+                            // Its content gets skipped next, unless looked at with `children`.
+                            self.matched -= 1;
+                            self.in_match = true;
+                            return Some(Ok(tag));
+                        }
+                    }
+                }
+                Event::Empty(tag) => {
+                    let level = depth - self.base;
+                    if level == self.matched
+                        && level + 1 == self.steps.len()
+                        && self.steps[level].matches(&tag)
+                    {
+                        return Some(Ok(tag));
+                    }
+                }
+                Event::End(_) if depth < self.base => break,
+                Event::End(_) => self.matched = self.matched.min(depth - self.base),
+                _ => (),
+            }
+        }
+        self.done = true;
+        None
+    }
+}
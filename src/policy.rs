@@ -0,0 +1,141 @@
+//! Flash cross-domain and socket policy files, as served to the Flash Player.
+//!
+//! Socket policy servers get a `<policy-file-request/>` followed by a null byte,
+//! and answer with a `<cross-domain-policy>` document that's null-terminated the same way.
+
+use crate::{Event, Reader, Tag};
+use alloc::vec::Vec;
+
+/// Parsed `<cross-domain-policy>` document.
+///
+/// # Example
+/// ```rust
+/// use lazyxml::policy::Policy;
+///
+/// let xml = "<?xml version=\"1.0\"?>\
+///            <cross-domain-policy>\
+///              <site-control permitted-cross-domain-policies=\"master-only\"/>\
+///              <allow-access-from domain=\"*.example.com\" to-ports=\"507,516-523\"/>\
+///            </cross-domain-policy>\0";
+/// let policy = Policy::parse(xml).unwrap();
+/// assert_eq!(policy.site_control(), Some("master-only"));
+/// let rule = &policy.allow_access_from()[0];
+/// assert_eq!(rule.domain(), "*.example.com");
+/// assert!(rule.allows_port(518));
+/// assert!(!rule.allows_port(80));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Policy<'xml> {
+    site_control: Option<&'xml str>,
+    allow_access_from: Vec<AllowAccessFrom<'xml>>,
+}
+
+/// `<allow-access-from>` rule of a [`Policy`], granting access to a domain.
+#[derive(Clone, Debug)]
+pub struct AllowAccessFrom<'xml> {
+    domain: &'xml str,
+    to_ports: Option<&'xml str>,
+    secure: bool,
+}
+
+impl<'xml> Policy<'xml> {
+    /// Parses a policy file, ignoring anything from the first null byte on.
+    ///
+    /// Rules are only taken from directly inside a `<cross-domain-policy>` root,
+    /// so any other document parses as a policy that grants nothing.
+    /// Rules without a `domain` are skipped, like the Flash Player does.
+    pub fn parse(xml: &'xml str) -> Result<Self, crate::Error> {
+        let xml = xml.split('\0').next().unwrap_or(xml);
+        let mut reader = Reader::from_str(xml);
+        reader.stop_after_root(true);
+        let mut policy = Self::default();
+        let mut in_policy = false;
+        while let Some(event) = reader.next() {
+            let tag = match event? {
+                Event::Start(tag) if reader.depth() == 1 => {
+                    in_policy = tag.name() == "cross-domain-policy";
+                    continue;
+                }
+                // Children of the root, after which the depth is 2 for start tags and 1 otherwise.
+                Event::Start(tag) if in_policy && reader.depth() == 2 => tag,
+                Event::Empty(tag) if in_policy && reader.depth() == 1 => tag,
+                _ => continue,
+            };
+            match tag.name() {
+                "site-control" => {
+                    policy.site_control = attribute(&tag, "permitted-cross-domain-policies")
+                }
+                "allow-access-from" => {
+                    if let Some(domain) = attribute(&tag, "domain") {
+                        policy.allow_access_from.push(AllowAccessFrom {
+                            domain,
+                            to_ports: attribute(&tag, "to-ports"),
+                            secure: attribute(&tag, "secure") != Some("false"),
+                        });
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(policy)
+    }
+
+    /// Gets the `permitted-cross-domain-policies` of the `<site-control>` element, if any.
+    pub fn site_control(&self) -> Option<&'xml str> {
+        self.site_control
+    }
+
+    /// Gets the `<allow-access-from>` rules in document order.
+    pub fn allow_access_from(&self) -> &[AllowAccessFrom<'xml>] {
+        &self.allow_access_from
+    }
+}
+
+fn attribute<'xml>(tag: &Tag<'xml, str>, key: &str) -> Option<&'xml str> {
+    tag.find_attribute(key).map(|attr| attr.value().trim())
+}
+
+impl<'xml> AllowAccessFrom<'xml> {
+    /// Gets the domain access is granted to, which may contain `*` wildcards.
+    pub fn domain(&self) -> &'xml str {
+        self.domain
+    }
+
+    /// Gets the raw `to-ports` list, such as `507,516-523` or `*`, which socket policies need.
+    pub fn to_ports(&self) -> Option<&'xml str> {
+        self.to_ports
+    }
+
+    /// Gets whether only HTTPS content is granted access, which is the default.
+    pub fn secure(&self) -> bool {
+        self.secure
+    }
+
+    /// Checks whether `to-ports` includes `port`, as a single port, a range or `*`.
+    ///
+    /// Malformed entries of the list never match.
+    pub fn allows_port(&self, port: u16) -> bool {
+        let ports = match self.to_ports {
+            Some(ports) => ports,
+            None => return false,
+        };
+        ports.split(',').map(str::trim).any(|entry| match entry {
+            "*" => true,
+            _ => {
+                let (low, high) = entry.split_once('-').unwrap_or((entry, entry));
+                match (low.trim().parse::<u16>(), high.trim().parse::<u16>()) {
+                    (Ok(low), Ok(high)) => (low..=high).contains(&port),
+                    _ => false,
+                }
+            }
+        })
+    }
+}
+
+/// Checks whether `data` is a socket policy request, as Flash sends before anything else.
+///
+/// The trailing null byte is optional, as is whitespace around the request.
+pub fn is_policy_request(data: &[u8]) -> bool {
+    let data = data.strip_suffix(b"\0").unwrap_or(data);
+    data.trim_ascii() == b"<policy-file-request/>"
+}
@@ -0,0 +1,79 @@
+//! Surgical edits to a document, leaving everything that isn't touched byte-for-byte as it was.
+
+use crate::{Error, Event, Reader, ReaderState};
+use alloc::vec::Vec;
+
+/// What [`rewrite`] does with an event, as decided by its closure.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Rewrite {
+    /// Keeps the event's markup as it was.
+    Keep,
+    /// Writes these bytes instead of the event's markup.
+    Replace(Vec<u8>),
+    /// Leaves the event's markup out.
+    Delete,
+    /// Writes these bytes, followed by the event's markup.
+    InsertBefore(Vec<u8>),
+    /// Writes the event's markup, followed by these bytes.
+    InsertAfter(Vec<u8>),
+}
+
+/// Reads the rest of the input with `reader`, letting `f` decide what happens to every event,
+/// and returns the edited input.
+///
+/// Only the markup of events is affected: whitespace between them, as well as anything
+/// the reader doesn't yield (such as comments when [`Reader::emit_comments`] is disabled),
+/// is copied as-is. Events are spanned as the reader spans them, so text is trimmed
+/// unless [`Reader::trim_whitespace`] is disabled, and rewriting a start tag leaves
+/// the rest of its element alone.\
+/// Nothing is inserted or escaped on behalf of `f`, which has to write well-formed markup itself.
+///
+/// # Example
+/// ```rust
+/// use lazyxml::{rewrite::{rewrite, Rewrite}, Event, Reader};
+///
+/// let xml = "<Config>\n  <Volume value='3'/> <!-- loud -->\n  <Debug/>\n</Config>";
+/// let out = rewrite(Reader::from_str(xml), |event| match event {
+///     Event::Empty(tag) if tag.name() == "Volume" => Rewrite::Replace(b"<Volume value='7'/>".to_vec()),
+///     Event::Empty(tag) if tag.name() == "Debug" => Rewrite::Delete,
+///     _ => Rewrite::Keep,
+/// });
+/// assert_eq!(out.unwrap(), b"<Config>\n  <Volume value='7'/> <!-- loud -->\n  \n</Config>");
+/// ```
+pub fn rewrite<'xml, T, F>(mut reader: Reader<'xml, T>, mut f: F) -> Result<Vec<u8>, Error>
+where
+    T: ?Sized + AsRef<[u8]>,
+    Reader<'xml, T>: Iterator<Item = Result<Event<'xml, T>, Error>>,
+    F: FnMut(Event<'xml, T>) -> Rewrite,
+{
+    let source = reader.source.as_ref();
+    // After text, the reader is already past the `<` of the next tag.
+    let mut copied = match reader.state {
+        ReaderState::LocatedTag => reader.offset - 1,
+        _ => reader.offset,
+    };
+    let mut out = Vec::with_capacity(source.len() - copied);
+    while let Some(event) = reader.next() {
+        let event = event?;
+        let (start, end) = (reader.event_start, reader.event_end);
+        out.extend_from_slice(&source[copied..start]);
+        let markup = &source[start..end];
+        match f(event) {
+            Rewrite::Keep => out.extend_from_slice(markup),
+            Rewrite::Replace(bytes) => out.extend_from_slice(&bytes),
+            Rewrite::Delete => (),
+            Rewrite::InsertBefore(bytes) => {
+                out.extend_from_slice(&bytes);
+                out.extend_from_slice(markup);
+            }
+            Rewrite::InsertAfter(bytes) => {
+                out.extend_from_slice(markup);
+                out.extend_from_slice(&bytes);
+            }
+        }
+        copied = end;
+    }
+    // Whatever the reader stopped short of, such as with `stop_after_root`.
+    out.extend_from_slice(&source[copied..]);
+    Ok(out)
+}
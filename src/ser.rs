@@ -0,0 +1,542 @@
+//! serde serialization into XML through the [`Writer`].
+//!
+//! Values serialize into an element named after the struct, or after the field holding them,
+//! with fields mapped the same way [`de`](crate::de) looks them up:
+//!
+//! - `@key`: the attribute `key`, for use with `#[serde(rename = "@key")]`.
+//! - `$value`: the text content of the element.
+//! - anything else: a child element, or an attribute when using [`FieldMapping::Attributes`].
+//!
+//! Sequences serialize into repeated elements, and `None` fields are left out.
+
+pub use crate::de::Error;
+use crate::Writer;
+use serde::ser::{self, Impossible, Serialize};
+use std::{fmt::Display, io};
+
+/// How fields without an `@` or `$value` name are written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldMapping {
+    /// Every field is a child element, like `<Frame><index>0</index></Frame>`.
+    Elements,
+    /// Fields that fit in text are attributes, like `<Frame index="0"/>`,
+    /// which is how most AS3 code writes its XML.
+    Attributes,
+}
+
+/// Serializes `value` into an XML string, writing plain fields as child elements.
+///
+/// # Example
+/// ```rust
+/// #[derive(serde::Serialize)]
+/// struct Script {
+///     #[serde(rename = "@time")]
+///     time: u32,
+///     #[serde(rename = "$value")]
+///     body: String,
+/// }
+///
+/// let script = Script { time: 5, body: "a < b".into() };
+/// let xml = lazyxml::ser::to_string(&script).unwrap();
+/// assert_eq!(xml, "<Script time=\"5\">a &lt; b</Script>");
+/// ```
+pub fn to_string<T: ?Sized + Serialize>(value: &T) -> Result<String, Error> {
+    to_string_with(value, FieldMapping::Elements)
+}
+
+/// Serializes `value` into an XML string, writing plain fields as set by `mapping`.
+pub fn to_string_with<T: ?Sized + Serialize>(
+    value: &T,
+    mapping: FieldMapping,
+) -> Result<String, Error> {
+    let mut writer = Writer::new(Vec::new());
+    value.serialize(ElementSerializer {
+        writer: &mut writer,
+        name: None,
+        mapping,
+    })?;
+    String::from_utf8(writer.into_inner()).map_err(|error| Error::Custom(error.to_string()))
+}
+
+fn io_error(error: io::Error) -> Error {
+    Error::Custom(error.to_string())
+}
+
+fn unsupported(what: &str) -> Error {
+    Error::Custom(format!("{} can't be serialized into XML", what))
+}
+
+/// Serializer writing a value as an element, named after the field holding it if any.
+struct ElementSerializer<'w> {
+    writer: &'w mut Writer<Vec<u8>>,
+    name: Option<&'w str>,
+    mapping: FieldMapping,
+}
+
+/// Serializer turning a value into the text of an attribute or element,
+/// or `None` if there's nothing to write.
+struct TextSerializer;
+
+/// Collects the fields of a struct or map, as the attributes have to be written first.
+struct StructSerializer<'w> {
+    writer: &'w mut Writer<Vec<u8>>,
+    name: String,
+    mapping: FieldMapping,
+    attributes: Vec<(String, String)>,
+    text: Option<String>,
+    children: Writer<Vec<u8>>,
+    key: Option<String>,
+}
+
+/// Writes every item of a sequence as an element with the same name.
+struct SeqSerializer<'w> {
+    writer: &'w mut Writer<Vec<u8>>,
+    name: Option<&'w str>,
+    mapping: FieldMapping,
+}
+
+impl<'w> ElementSerializer<'w> {
+    fn element_name(&self, fallback: &'w str) -> &'w str {
+        self.name.unwrap_or(fallback)
+    }
+
+    fn write_text_element(self, text: &str) -> Result<(), Error> {
+        let name = self.element_name("value");
+        self.writer.write_start(name, &[]).map_err(io_error)?;
+        self.writer.write_text(text).map_err(io_error)?;
+        self.writer.write_end(name).map_err(io_error)
+    }
+
+    fn write_display(self, value: impl Display) -> Result<(), Error> {
+        self.write_text_element(&value.to_string())
+    }
+
+    fn write_empty_element(self) -> Result<(), Error> {
+        let name = self.element_name("value");
+        self.writer.write_empty(name, &[]).map_err(io_error)
+    }
+
+    fn into_struct(self, fallback: &'w str) -> StructSerializer<'w> {
+        StructSerializer {
+            name: self.element_name(fallback).to_owned(),
+            writer: self.writer,
+            mapping: self.mapping,
+            attributes: Vec::new(),
+            text: None,
+            children: Writer::new(Vec::new()),
+            key: None,
+        }
+    }
+}
+
+impl<'w> StructSerializer<'w> {
+    fn field<T: ?Sized + Serialize>(&mut self, key: &str, value: &T) -> Result<(), Error> {
+        if let Some(key) = key.strip_prefix('@') {
+            if let Some(text) = value.serialize(TextSerializer)? {
+                self.attributes.push((key.to_owned(), text));
+            }
+            return Ok(());
+        }
+        if key == "$value" {
+            if let Some(text) = value.serialize(TextSerializer)? {
+                self.text.get_or_insert_with(String::new).push_str(&text);
+            }
+            return Ok(());
+        }
+        if self.mapping == FieldMapping::Attributes {
+            if let Ok(text) = value.serialize(TextSerializer) {
+                if let Some(text) = text {
+                    self.attributes.push((key.to_owned(), text));
+                }
+                return Ok(());
+            }
+        }
+        value.serialize(ElementSerializer {
+            writer: &mut self.children,
+            name: Some(key),
+            mapping: self.mapping,
+        })
+    }
+
+    fn finish(self) -> Result<(), Error> {
+        let attributes: Vec<(&str, &str)> = self
+            .attributes
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+        let children = self.children.into_inner();
+        if self.text.as_deref().unwrap_or_default().is_empty() && children.is_empty() {
+            return self
+                .writer
+                .write_empty(&self.name, &attributes)
+                .map_err(io_error);
+        }
+        self.writer
+            .write_start(&self.name, &attributes)
+            .map_err(io_error)?;
+        if let Some(text) = &self.text {
+            self.writer.write_text(text).map_err(io_error)?;
+        }
+        io::Write::write_all(self.writer.get_mut(), &children).map_err(io_error)?;
+        self.writer.write_end(&self.name).map_err(io_error)
+    }
+}
+
+macro_rules! serialize_display {
+    ($($method:ident: $ty:ty,)*) => {
+        $(
+            fn $method(self, value: $ty) -> Result<Self::Ok, Error> {
+                self.write_display(value)
+            }
+        )*
+    };
+}
+
+macro_rules! serialize_text {
+    ($($method:ident: $ty:ty,)*) => {
+        $(
+            fn $method(self, value: $ty) -> Result<Self::Ok, Error> {
+                Ok(Some(value.to_string()))
+            }
+        )*
+    };
+}
+
+impl<'w> ser::Serializer for ElementSerializer<'w> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = SeqSerializer<'w>;
+    type SerializeTuple = SeqSerializer<'w>;
+    type SerializeTupleStruct = SeqSerializer<'w>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = StructSerializer<'w>;
+    type SerializeStruct = StructSerializer<'w>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    serialize_display! {
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_f32: f32,
+        serialize_f64: f64,
+        serialize_char: char,
+    }
+
+    fn serialize_str(self, value: &str) -> Result<(), Error> {
+        self.write_text_element(value)
+    }
+
+    fn serialize_bytes(self, _value: &[u8]) -> Result<(), Error> {
+        Err(unsupported("bytes"))
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        self.write_empty_element()
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<(), Error> {
+        let name = self.element_name(name);
+        self.writer.write_empty(name, &[]).map_err(io_error)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<(), Error> {
+        self.write_text_element(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), Error> {
+        Err(unsupported("newtype variants"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<SeqSerializer<'w>, Error> {
+        Ok(SeqSerializer {
+            writer: self.writer,
+            name: self.name,
+            mapping: self.mapping,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer<'w>, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer<'w>, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(unsupported("tuple variants"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<StructSerializer<'w>, Error> {
+        Ok(self.into_struct("map"))
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        _len: usize,
+    ) -> Result<StructSerializer<'w>, Error> {
+        Ok(self.into_struct(name))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(unsupported("struct variants"))
+    }
+}
+
+impl ser::Serializer for TextSerializer {
+    type Ok = Option<String>;
+    type Error = Error;
+    type SerializeSeq = Impossible<Option<String>, Error>;
+    type SerializeTuple = Impossible<Option<String>, Error>;
+    type SerializeTupleStruct = Impossible<Option<String>, Error>;
+    type SerializeTupleVariant = Impossible<Option<String>, Error>;
+    type SerializeMap = Impossible<Option<String>, Error>;
+    type SerializeStruct = Impossible<Option<String>, Error>;
+    type SerializeStructVariant = Impossible<Option<String>, Error>;
+
+    serialize_text! {
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_f32: f32,
+        serialize_f64: f64,
+        serialize_char: char,
+        serialize_str: &str,
+    }
+
+    fn serialize_bytes(self, _value: &[u8]) -> Result<Self::Ok, Error> {
+        Err(unsupported("bytes"))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Error> {
+        Ok(Some(String::new()))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Error> {
+        Ok(Some(String::new()))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Error> {
+        Ok(Some(variant.to_owned()))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Error> {
+        Err(unsupported("newtype variants in text"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(unsupported("sequences in text"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(unsupported("tuples in text"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(unsupported("tuple structs in text"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(unsupported("tuple variants in text"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(unsupported("maps in text"))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        Err(unsupported("structs in text"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(unsupported("struct variants in text"))
+    }
+}
+
+impl<'w> ser::SerializeSeq for SeqSerializer<'w> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(ElementSerializer {
+            writer: self.writer,
+            name: self.name,
+            mapping: self.mapping,
+        })
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'w> ser::SerializeTuple for SeqSerializer<'w> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'w> ser::SerializeTupleStruct for SeqSerializer<'w> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'w> ser::SerializeStruct for StructSerializer<'w> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+impl<'w> ser::SerializeMap for StructSerializer<'w> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Error> {
+        match key.serialize(TextSerializer)? {
+            Some(key) => self.key = Some(key),
+            None => return Err(unsupported("empty map keys")),
+        }
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| Error::Custom("value serialized before key".into()))?;
+        self.field(&key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
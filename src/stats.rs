@@ -0,0 +1,101 @@
+//! Counters kept by the [`Reader`] while it reads, for summarizing unknown documents.
+
+use crate::{Error, Event, Reader};
+
+/// Summary of what a [`Reader`] has read so far, as returned by [`Reader::stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Number of [`Event::Start`] events.
+    pub start: usize,
+    /// Number of [`Event::End`] events.
+    pub end: usize,
+    /// Number of [`Event::Empty`] events.
+    pub empty: usize,
+    /// Number of [`Event::Text`] events.
+    pub text: usize,
+    /// Number of [`Event::Comment`] events.
+    pub comment: usize,
+    /// Number of [`Event::ProcessingInstruction`] events.
+    pub processing_instruction: usize,
+    /// Number of [`Event::CData`] events.
+    pub cdata: usize,
+    /// Number of [`Event::DocType`] events.
+    pub doctype: usize,
+    /// Number of broken tags salvaged as text with [`Reader::recover`].
+    pub recovered: usize,
+    /// Deepest the reader has been, see [`Reader::depth`].
+    pub max_depth: usize,
+    /// Number of bytes of the input read so far, see [`Reader::offset`].
+    pub bytes: usize,
+}
+
+impl Stats {
+    pub(crate) const EMPTY: Self = Self {
+        start: 0,
+        end: 0,
+        empty: 0,
+        text: 0,
+        comment: 0,
+        processing_instruction: 0,
+        cdata: 0,
+        doctype: 0,
+        recovered: 0,
+        max_depth: 0,
+        bytes: 0,
+    };
+
+    /// Gets the total number of events.
+    pub const fn events(&self) -> usize {
+        self.start
+            + self.end
+            + self.empty
+            + self.text
+            + self.comment
+            + self.processing_instruction
+            + self.cdata
+            + self.doctype
+    }
+}
+
+impl<'xml, T: ?Sized> Reader<'xml, T> {
+    /// Gets what the reader has read so far.
+    ///
+    /// Events skipped by the reader's settings, such as comments with
+    /// [`Reader::emit_comments`] disabled, aren't counted.
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::Reader;
+    ///
+    /// let mut reader = Reader::from_str("<a><b>text</b><c/><!-- hi --></a>");
+    /// reader.by_ref().for_each(drop);
+    /// let stats = reader.stats();
+    /// assert_eq!((stats.start, stats.empty, stats.comment), (2, 1, 1));
+    /// assert_eq!((stats.events(), stats.max_depth), (7, 2));
+    /// ```
+    pub fn stats(&self) -> Stats {
+        Stats {
+            bytes: self.offset,
+            ..self.stats
+        }
+    }
+}
+
+impl<'xml> Reader<'xml, [u8]> {
+    /// Counts an event that was just read.
+    pub(crate) fn record(&mut self, item: &Option<Result<Event<'xml, [u8]>, Error>>) {
+        let stats = &mut self.stats;
+        match item {
+            Some(Ok(Event::Start(_))) => stats.start += 1,
+            Some(Ok(Event::End(_))) => stats.end += 1,
+            Some(Ok(Event::Empty(_))) => stats.empty += 1,
+            Some(Ok(Event::Text(_))) => stats.text += 1,
+            Some(Ok(Event::Comment(_))) => stats.comment += 1,
+            Some(Ok(Event::ProcessingInstruction(_))) => stats.processing_instruction += 1,
+            Some(Ok(Event::CData(_))) => stats.cdata += 1,
+            Some(Ok(Event::DocType(_))) => stats.doctype += 1,
+            Some(Err(_)) | None => (),
+        }
+        stats.max_depth = stats.max_depth.max(self.depth);
+    }
+}
@@ -0,0 +1,223 @@
+//! DOM-style element tree built on top of the [`Reader`], borrowing from the input.
+
+use crate::{Error, Event, Reader, Tag, Text};
+use alloc::{borrow::Cow, vec::Vec};
+use core::mem;
+
+/// Settings for building a [`Document`] with [`Document::parse_with`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TreeOptions {
+    comments: bool,
+    processing_instructions: bool,
+}
+
+/// Parsed XML document, holding the top-level nodes in document order.
+#[derive(Debug)]
+pub struct Document<'xml> {
+    children: Vec<Node<'xml>>,
+}
+
+/// Node of a [`Document`] along with its children, if it's an element.
+#[derive(Debug)]
+pub struct Node<'xml> {
+    kind: NodeKind<'xml>,
+    children: Vec<Node<'xml>>,
+}
+
+/// What kind of markup a [`Node`] was built from.
+#[derive(Debug)]
+pub enum NodeKind<'xml> {
+    /// Element from a `<Start>` and `</End>` tag pair, or an `<Empty />` tag.
+    Element(Tag<'xml, str>),
+    /// Arbitrary text, trimmed as the [`Reader`] does by default.
+    Text(Text<'xml, str>),
+    /// XML `<![CDATA[ ... ]]>` section.
+    CData(Text<'xml, str>),
+    /// XML `<!-- Comment -->`, only kept if enabled with [`TreeOptions::comments`].
+    Comment(Text<'xml, str>),
+    /// XML `<?Target content?>` processing instruction,
+    /// only kept if enabled with [`TreeOptions::processing_instructions`].
+    ProcessingInstruction(Tag<'xml, str>),
+}
+
+impl TreeOptions {
+    /// Constructs the default options, which drop comments and processing instructions.
+    pub const fn new() -> Self {
+        Self {
+            comments: false,
+            processing_instructions: false,
+        }
+    }
+
+    /// Sets whether comments are kept as [`NodeKind::Comment`] nodes.
+    pub const fn comments(mut self, keep: bool) -> Self {
+        self.comments = keep;
+        self
+    }
+
+    /// Sets whether processing instructions are kept as [`NodeKind::ProcessingInstruction`] nodes.
+    pub const fn processing_instructions(mut self, keep: bool) -> Self {
+        self.processing_instructions = keep;
+        self
+    }
+}
+
+impl<'xml> Document<'xml> {
+    /// Parses a whole document into a tree, dropping comments and processing instructions.
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::tree::Document;
+    ///
+    /// let doc = Document::parse("<Config><Item name=\"a\">1</Item><Item/></Config>").unwrap();
+    /// let root = doc.root().unwrap();
+    /// assert_eq!(root.name(), Some("Config"));
+    /// assert_eq!(root.children().len(), 2);
+    /// assert_eq!(root.children()[0].attribute("name"), Some("a"));
+    /// assert_eq!(root.children()[0].text(), "1");
+    /// ```
+    pub fn parse(xml: &'xml str) -> Result<Self, Error> {
+        Self::parse_with(xml, TreeOptions::new())
+    }
+
+    /// Parses a whole document into a tree with the given options.
+    ///
+    /// Every node's children are in document order, so retained comments and processing
+    /// instructions keep their place relative to their sibling elements and text.\
+    /// The document's structure is checked as with [`Reader::check_structure`],
+    /// so broken nesting yields the same errors.
+    pub fn parse_with(xml: &'xml str, options: TreeOptions) -> Result<Self, Error> {
+        let mut reader = Reader::from_str(xml);
+        reader.check_structure(true);
+        // Elements still open, along with the children of the innermost one.
+        let mut open: Vec<(Tag<'xml, str>, Vec<Node<'xml>>)> = Vec::new();
+        let mut children = Vec::new();
+        for event in reader {
+            let kind = match event? {
+                Event::Start(tag) => {
+                    open.push((tag, mem::take(&mut children)));
+                    continue;
+                }
+                Event::End(_) => {
+                    // The reader already rejects end tags without a matching start tag.
+                    let (tag, siblings) = open.pop().ok_or(Error::UnexpectedEof)?;
+                    let element = Node {
+                        kind: NodeKind::Element(tag),
+                        children: mem::replace(&mut children, siblings),
+                    };
+                    children.push(element);
+                    continue;
+                }
+                Event::Empty(tag) => NodeKind::Element(tag),
+                Event::Text(text) => NodeKind::Text(text),
+                Event::CData(text) => NodeKind::CData(text),
+                Event::Comment(text) if options.comments => NodeKind::Comment(text),
+                Event::ProcessingInstruction(tag) if options.processing_instructions => {
+                    NodeKind::ProcessingInstruction(tag)
+                }
+                _ => continue,
+            };
+            children.push(Node {
+                kind,
+                children: Vec::new(),
+            });
+        }
+        Ok(Self { children })
+    }
+
+    /// Parses a fragment of XML into its top-level nodes, dropping comments and processing instructions.
+    ///
+    /// Fragments may have any number of top-level elements with text around them,
+    /// see [`Reader::from_fragment`]. This is otherwise the same as [`Document::parse`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use lazyxml::tree::Document;
+    ///
+    /// let nodes = Document::parse_fragment("<b>Hello</b>, <i>world</i>!").unwrap();
+    /// assert_eq!(nodes.len(), 4);
+    /// assert_eq!(nodes[2].text(), "world");
+    /// ```
+    pub fn parse_fragment(xml: &'xml str) -> Result<Vec<Node<'xml>>, Error> {
+        Self::parse(xml).map(|doc| doc.children)
+    }
+
+    /// Gets the top-level nodes, such as the root element and the comments around it.
+    pub fn children(&self) -> &[Node<'xml>] {
+        &self.children
+    }
+
+    /// Gets the first top-level element, if any.
+    pub fn root(&self) -> Option<&Node<'xml>> {
+        self.children.iter().find(|x| x.name().is_some())
+    }
+}
+
+impl<'xml> Node<'xml> {
+    /// Gets what kind of markup this node was built from.
+    pub fn kind(&self) -> &NodeKind<'xml> {
+        &self.kind
+    }
+
+    /// Gets the child nodes in document order, which is empty for anything but elements.
+    pub fn children(&self) -> &[Node<'xml>] {
+        &self.children
+    }
+
+    /// Returns an iterator over the child elements, skipping text and other nodes.
+    pub fn elements(&self) -> impl Iterator<Item = &Node<'xml>> {
+        self.children.iter().filter(|x| x.name().is_some())
+    }
+
+    /// Gets the name of the element, or `None` if this isn't an element.
+    pub fn name(&self) -> Option<&'xml str> {
+        match &self.kind {
+            NodeKind::Element(tag) => Some(tag.name()),
+            _ => None,
+        }
+    }
+
+    /// Gets the raw value of the element's first attribute with the given key.
+    ///
+    /// Malformed attributes end the search, as there's no telling where the next one starts.
+    pub fn attribute(&self, key: &str) -> Option<&'xml str> {
+        match &self.kind {
+            NodeKind::Element(tag) => tag.find_attribute(key).map(|attr| attr.value()),
+            _ => None,
+        }
+    }
+
+    /// Gets the raw text directly inside the element, concatenating text and CDATA children.
+    ///
+    /// For text, CDATA and comment nodes, this is their own content instead.\
+    /// This only allocates if there are several pieces of text to join.
+    pub fn text(&self) -> Cow<'xml, str> {
+        match &self.kind {
+            NodeKind::Text(text) | NodeKind::CData(text) | NodeKind::Comment(text) => {
+                return Cow::Borrowed(text.content())
+            }
+            NodeKind::ProcessingInstruction(_) => return Cow::Borrowed(""),
+            NodeKind::Element(_) => (),
+        }
+        let mut pieces = self.children.iter().filter_map(|x| match &x.kind {
+            NodeKind::Text(text) | NodeKind::CData(text) => Some(text.content()),
+            _ => None,
+        });
+        let mut text = Cow::Borrowed(pieces.next().unwrap_or(""));
+        for piece in pieces {
+            text.to_mut().push_str(piece);
+        }
+        text
+    }
+}
+
+impl Drop for Node<'_> {
+    fn drop(&mut self) {
+        // Dropping the children recursively would overflow the stack on deeply nested documents,
+        // so every descendant is moved onto one list and dropped from there, childless.
+        let mut nodes = mem::take(&mut self.children);
+        while let Some(mut node) = nodes.pop() {
+            nodes.append(&mut node.children);
+        }
+    }
+}
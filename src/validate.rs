@@ -0,0 +1,203 @@
+//! Checking documents at compile time, for templates embedded into the binary.
+
+use crate::lut_name_start_chars;
+
+/// Same as the reader's, but usable in constant evaluation.
+const NAME_START: [bool; 256] = lut_name_start_chars();
+
+/// Deepest [`validate`] can check, as it can't allocate for the open elements.
+const MAX_DEPTH: usize = 128;
+
+/// Checks whether `xml` reads without errors, as a [`Reader`](crate::Reader) with
+/// [`check_structure`](crate::Reader::check_structure) enabled and otherwise default settings would.
+///
+/// Like the reader, this is lenient: attributes aren't looked into,
+/// and only the first character of names is checked.
+/// Elements nested deeper than 128 levels are rejected.
+///
+/// As a `const fn`, it can make a build fail over a mangled template.
+///
+/// # Example
+/// ```rust
+/// use lazyxml::validate;
+///
+/// const TEMPLATE: &str = "<Dialog><Title>Hello</Title><Button/></Dialog>";
+/// const _: () = assert!(validate(TEMPLATE), "template is malformed");
+///
+/// assert!(!validate("<Dialog><Title>Hello</Dialog>"));
+/// ```
+pub const fn validate(xml: &str) -> bool {
+    let bytes = xml.as_bytes();
+    // Spans of the names of the open elements.
+    let mut open = [(0usize, 0usize); MAX_DEPTH];
+    let mut depth = 0;
+    let mut offset = 0;
+    while offset < bytes.len() {
+        if bytes[offset] != b'<' {
+            offset += 1;
+            continue;
+        }
+        // Like in the reader, `offset` is past the `<` from here on.
+        offset += 1;
+        if offset == bytes.len() {
+            return false;
+        }
+        let end = match bytes[offset] {
+            b'!' if starts_with(bytes, offset, b"!--") => find(bytes, offset + 3, b"-->", 3),
+            b'!' if starts_with(bytes, offset, b"![CDATA[") => find(bytes, offset + 8, b"]]>", 3),
+            b'!' if starts_with(bytes, offset, b"!DOCTYPE") => doctype_end(bytes, offset + 8),
+            b'!' => None,
+            b'?' => match find(bytes, offset + 1, b"?>", 2) {
+                Some(end) if NAME_START[bytes[offset + 1] as usize] => Some(end),
+                _ => None,
+            },
+            _ => {
+                let gt = match find(bytes, offset, b">", 0) {
+                    Some(gt) => gt,
+                    None => return false,
+                };
+                let name_end = match position_space(bytes, offset, gt) {
+                    Some(space) => space,
+                    None => gt,
+                };
+                let is_empty = bytes[gt - 1] == b'/' && !ends_in_value(bytes, name_end, gt);
+                let is_end = bytes[offset] == b'/';
+                // The `/` of empty tags only belongs to the name without a space in between.
+                let name_end = match is_empty && name_end == gt {
+                    true => name_end - 1,
+                    false => name_end,
+                };
+                let name_start = match is_end {
+                    true => offset + 1,
+                    false => offset,
+                };
+                if name_start >= name_end || !NAME_START[bytes[name_start] as usize] {
+                    return false;
+                }
+                let name = (name_start, name_end);
+                if is_end {
+                    if depth == 0 || !names_eq(bytes, open[depth - 1], name) {
+                        return false;
+                    }
+                    depth -= 1;
+                } else if !is_empty {
+                    if depth == MAX_DEPTH {
+                        return false;
+                    }
+                    open[depth] = name;
+                    depth += 1;
+                }
+                Some(gt + 1)
+            }
+        };
+        offset = match end {
+            Some(end) => end,
+            None => return false,
+        };
+    }
+    depth == 0
+}
+
+const fn starts_with(bytes: &[u8], offset: usize, prefix: &[u8]) -> bool {
+    if bytes.len() - offset < prefix.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < prefix.len() {
+        if bytes[offset + i] != prefix[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Finds `needle` at or after `from`, returning the offset `skip` bytes past where it starts.
+const fn find(bytes: &[u8], from: usize, needle: &[u8], skip: usize) -> Option<usize> {
+    let mut offset = from;
+    while offset < bytes.len() {
+        if starts_with(bytes, offset, needle) {
+            return Some(offset + skip);
+        }
+        offset += 1;
+    }
+    None
+}
+
+/// Finds the first whitespace in `start..end`.
+const fn position_space(bytes: &[u8], start: usize, end: usize) -> Option<usize> {
+    let mut offset = start;
+    while offset < end {
+        if bytes[offset] <= b' ' {
+            return Some(offset);
+        }
+        offset += 1;
+    }
+    None
+}
+
+/// Checks whether the tag content in `start..end` ends in the middle of a quoted value,
+/// in which case a trailing `/` is part of that value.
+const fn ends_in_value(bytes: &[u8], start: usize, end: usize) -> bool {
+    let mut quote = 0;
+    let mut after_eq = false;
+    let mut offset = start;
+    while offset < end {
+        let ch = bytes[offset];
+        if quote != 0 {
+            if ch == quote {
+                quote = 0;
+            }
+        } else if ch == b'=' {
+            after_eq = true;
+        } else if after_eq && (ch == b'"' || ch == b'\'') {
+            quote = ch;
+            after_eq = false;
+        }
+        offset += 1;
+    }
+    quote != 0
+}
+
+/// Gets the offset past the `>` ending a DOCTYPE, skipping over its internal subset.
+const fn doctype_end(bytes: &[u8], from: usize) -> Option<usize> {
+    let (mut depth, mut quote) = (0usize, 0u8);
+    let mut offset = from;
+    while offset < bytes.len() {
+        let ch = bytes[offset];
+        if quote != 0 {
+            if ch == quote {
+                quote = 0;
+            }
+        } else if ch == b'"' || ch == b'\'' {
+            quote = ch;
+        } else if ch == b'[' {
+            depth += 1;
+        } else if ch == b']' {
+            depth = depth.saturating_sub(1);
+        } else if ch == b'>' && depth == 0 {
+            return Some(offset + 1);
+        } else if ch == b'<' && starts_with(bytes, offset, b"<!--") {
+            offset = match find(bytes, offset + 4, b"-->", 2) {
+                Some(end) => end,
+                None => return None,
+            };
+        }
+        offset += 1;
+    }
+    None
+}
+
+const fn names_eq(bytes: &[u8], a: (usize, usize), b: (usize, usize)) -> bool {
+    if a.1 - a.0 != b.1 - b.0 {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.1 - a.0 {
+        if bytes[a.0 + i] != bytes[b.0 + i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
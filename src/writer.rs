@@ -0,0 +1,196 @@
+//! Writer emitting XML events into an [`io::Write`] sink.
+
+use crate::{escape::escape, fmt::Layout, Event, Text};
+use std::io::{self, Write};
+
+/// XML writer over an [`io::Write`] sink, mirroring the [`Reader`](crate::Reader).
+///
+/// Names and raw markup are written as-is, while text and attribute values are escaped.\
+/// Nothing checks that the tags are balanced, that's up to the caller.
+///
+/// # Example
+/// ```rust
+/// let mut writer = lazyxml::Writer::new(Vec::new());
+/// writer.indent(2);
+/// writer.write_start("Config", &[("version", "2")]).unwrap();
+/// writer.write_empty("Item", &[("name", "a & b")]).unwrap();
+/// writer.write_end("Config").unwrap();
+/// let xml = String::from_utf8(writer.into_inner()).unwrap();
+/// assert_eq!(xml, "<Config version=\"2\">\n  <Item name=\"a &amp; b\"/>\n</Config>");
+/// ```
+pub struct Writer<W> {
+    inner: W,
+    /// What to indent every level with, or nothing to write everything on one line.
+    indent: String,
+    layout: Layout,
+}
+
+impl<W: Write> Writer<W> {
+    /// Constructs a new [`Writer`] over `inner`.
+    ///
+    /// Every event is written in a few small writes, so `inner` should be buffered.
+    pub const fn new(inner: W) -> Self {
+        Self {
+            inner,
+            indent: String::new(),
+            layout: Layout::new(),
+        }
+    }
+
+    /// Sets how many spaces to indent every level of nesting with.
+    ///
+    /// Tags are put on their own lines unless they directly follow text,
+    /// so that indenting never changes the text content of an element.\
+    /// This property is dynamic and can be changed while writing.
+    ///
+    /// Defaults to `0`, which writes everything on one line.
+    pub fn indent(&mut self, spaces: usize) -> &mut Self {
+        self.indent = " ".repeat(spaces);
+        self
+    }
+
+    /// Gets a reference to the underlying sink.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying sink.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwraps this [`Writer`], returning the underlying sink.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// Writes a `<Start>` tag with the given attributes.
+    pub fn write_start(&mut self, name: &str, attributes: &[(&str, &str)]) -> io::Result<()> {
+        self.write_tag(name, attributes, b">")?;
+        self.layout.start();
+        Ok(())
+    }
+
+    /// Writes an `<Empty/>` tag with the given attributes.
+    pub fn write_empty(&mut self, name: &str, attributes: &[(&str, &str)]) -> io::Result<()> {
+        self.write_tag(name, attributes, b"/>")?;
+        self.layout.other();
+        Ok(())
+    }
+
+    /// Writes an `</End>` tag.
+    pub fn write_end(&mut self, name: &str) -> io::Result<()> {
+        self.write_end_raw(name.as_bytes())
+    }
+
+    /// Writes text, escaping `&`, `<` and `>`.
+    pub fn write_text(&mut self, text: &str) -> io::Result<()> {
+        self.inner.write_all(&escape(text.as_bytes(), false))?;
+        self.layout.text();
+        Ok(())
+    }
+
+    /// Writes a `<!-- Comment -->`, given what goes between the markers as-is.
+    pub fn write_comment(&mut self, text: &str) -> io::Result<()> {
+        self.write_markup(b"<!--", text.as_bytes(), b"-->")
+    }
+
+    /// Writes a `<![CDATA[ ... ]]>` section, given what goes between the markers as-is.
+    ///
+    /// Any `]]>` in `text` is split across two sections, as it can't be written in one.
+    pub fn write_cdata(&mut self, text: &str) -> io::Result<()> {
+        let text = text.replace("]]>", "]]]]><![CDATA[>");
+        self.write_markup(b"<![CDATA[", text.as_bytes(), b"]]>")
+    }
+
+    /// Writes an event as it was read, such as to rewrite a document while keeping its markup.
+    ///
+    /// Tag contents and text are written raw, since they're still escaped from the input.
+    pub fn write_event<T: ?Sized + AsRef<[u8]>>(&mut self, event: &Event<'_, T>) -> io::Result<()> {
+        let (open, tag, close) = match event {
+            Event::Start(tag) => (&b"<"[..], tag, &b">"[..]),
+            Event::Empty(tag) => (&b"<"[..], tag, &b"/>"[..]),
+            Event::ProcessingInstruction(tag) => (&b"<?"[..], tag, &b"?>"[..]),
+            Event::End(tag) => return self.write_end_raw(tag.name().as_ref()),
+            Event::Text(text) => return self.write_text_raw(text),
+            Event::Comment(text) => return self.write_markup(b"<!--", raw(text), b"-->"),
+            Event::CData(text) => return self.write_markup(b"<![CDATA[", raw(text), b"]]>"),
+            Event::DocType(text) => return self.write_markup(b"<!DOCTYPE", raw(text), b">"),
+        };
+        self.line_break()?;
+        self.inner.write_all(open)?;
+        self.inner.write_all(tag.name().as_ref())?;
+        let content = tag.content().as_ref();
+        if !content.is_empty() {
+            self.inner.write_all(b" ")?;
+            self.inner.write_all(content)?;
+        }
+        self.inner.write_all(close)?;
+        match event {
+            Event::Start(_) => self.layout.start(),
+            _ => self.layout.other(),
+        }
+        Ok(())
+    }
+
+    fn write_tag(
+        &mut self,
+        name: &str,
+        attributes: &[(&str, &str)],
+        close: &[u8],
+    ) -> io::Result<()> {
+        self.line_break()?;
+        self.inner.write_all(b"<")?;
+        self.inner.write_all(name.as_bytes())?;
+        for (key, value) in attributes {
+            self.inner.write_all(b" ")?;
+            self.inner.write_all(key.as_bytes())?;
+            self.inner.write_all(b"=\"")?;
+            self.inner.write_all(&escape(value.as_bytes(), true))?;
+            self.inner.write_all(b"\"")?;
+        }
+        self.inner.write_all(close)
+    }
+
+    fn write_end_raw(&mut self, name: &[u8]) -> io::Result<()> {
+        let line_break = self.layout.end(&self.indent);
+        if !self.indent.is_empty() {
+            for part in line_break {
+                self.inner.write_all(part.as_bytes())?;
+            }
+        }
+        self.inner.write_all(b"</")?;
+        self.inner.write_all(name)?;
+        self.inner.write_all(b">")
+    }
+
+    fn write_text_raw<T: ?Sized + AsRef<[u8]>>(&mut self, text: &Text<'_, T>) -> io::Result<()> {
+        self.inner.write_all(raw(text))?;
+        self.layout.text();
+        Ok(())
+    }
+
+    fn write_markup(&mut self, open: &[u8], content: &[u8], close: &[u8]) -> io::Result<()> {
+        self.line_break()?;
+        self.inner.write_all(open)?;
+        self.inner.write_all(content)?;
+        self.inner.write_all(close)?;
+        self.layout.other();
+        Ok(())
+    }
+
+    /// Puts the next tag on its own line, if indenting and not directly after text.
+    fn line_break(&mut self) -> io::Result<()> {
+        if self.indent.is_empty() {
+            return Ok(());
+        }
+        for part in self.layout.line_break(&self.indent, 0) {
+            self.inner.write_all(part.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+fn raw<'a, T: ?Sized + AsRef<[u8]>>(text: &Text<'a, T>) -> &'a [u8] {
+    text.content().as_ref()
+}
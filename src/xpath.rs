@@ -0,0 +1,299 @@
+//! Small subset of XPath for querying a [`Document`], compiled once into a reusable [`Path`].
+//!
+//! | Syntax          | Selects                                                  |
+//! |-----------------|----------------------------------------------------------|
+//! | `/a`            | the root element if it's named `a`                       |
+//! | `a/b`           | `b` children of `a` children of the context              |
+//! | `//b`           | `b` elements at any depth                                |
+//! | `*`             | elements of any name                                     |
+//! | `text()`        | text and CDATA nodes                                     |
+//! | `b[@x]`         | `b` elements with an `x` attribute                       |
+//! | `b[@x='1']`     | `b` elements whose decoded `x` attribute is `1`          |
+//! | `b[2]`          | the second `b` of every parent, counting from 1          |
+
+use crate::{
+    decode::eq_decoded,
+    tree::{Document, Node, NodeKind},
+};
+use alloc::{
+    collections::BTreeSet,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{fmt, ptr, slice};
+
+/// Compiled path expression, see the [module documentation](self) for the syntax.
+///
+/// # Example
+/// ```rust
+/// use lazyxml::{tree::Document, xpath::Path};
+///
+/// let doc = Document::parse("<Anim><Layer><Frame i='0'/><Frame i='1'>a</Frame></Layer></Anim>")
+///     .unwrap();
+/// let path = Path::compile("/Anim/Layer/Frame[@i='1']/text()").unwrap();
+/// let texts = path.select_document(&doc);
+/// assert_eq!(texts[0].text(), "a");
+/// assert_eq!(Path::compile("//Frame").unwrap().select_document(&doc).len(), 2);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Path {
+    absolute: bool,
+    steps: Vec<Step>,
+}
+
+/// Error from compiling a malformed [`Path`] expression.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PathError {
+    offset: usize,
+}
+
+#[derive(Clone, Debug)]
+struct Step {
+    /// Whether this step was preceded by `//`, looking at descendants rather than children.
+    descendant: bool,
+    test: Test,
+    predicates: Vec<Predicate>,
+}
+
+#[derive(Clone, Debug)]
+enum Test {
+    Name(String),
+    Any,
+    Text,
+}
+
+#[derive(Clone, Debug)]
+enum Predicate {
+    Attribute(String, Option<String>),
+    /// Position among the siblings left by the previous predicates, counting from 1.
+    Index(usize),
+}
+
+impl Path {
+    /// Compiles a path expression.
+    pub fn compile(expr: &str) -> Result<Self, PathError> {
+        let mut parser = Parser { expr, pos: 0 };
+        let mut steps = Vec::new();
+        loop {
+            let descendant = parser.eat("//");
+            if !descendant {
+                parser.eat("/");
+            }
+            steps.push(parser.step(descendant)?);
+            if parser.pos == expr.len() {
+                break;
+            }
+            if !parser.expr[parser.pos..].starts_with('/') {
+                return parser.error();
+            }
+        }
+        Ok(Self {
+            absolute: expr.starts_with('/'),
+            steps,
+        })
+    }
+
+    /// Evaluates the path against a document, in which absolute paths start at the top level.
+    pub fn select_document<'a, 'xml>(&self, doc: &'a Document<'xml>) -> Vec<&'a Node<'xml>> {
+        self.eval(doc.children())
+    }
+
+    /// Evaluates the path with `node` as the context.
+    ///
+    /// Relative paths start at the node's children. Absolute ones take the node
+    /// as the root element, as if its subtree was the whole document.
+    pub fn select<'a, 'xml>(&self, node: &'a Node<'xml>) -> Vec<&'a Node<'xml>> {
+        match self.absolute {
+            true => self.eval(slice::from_ref(node)),
+            false => self.eval(node.children()),
+        }
+    }
+
+    /// Gets the matches, grouped by the node they were found in and in document order within that.
+    fn eval<'a, 'xml>(&self, nodes: &'a [Node<'xml>]) -> Vec<&'a Node<'xml>> {
+        let mut context = vec![nodes];
+        let mut matches = Vec::new();
+        for step in &self.steps {
+            matches = step.apply(&context);
+            context = matches.iter().map(|node| node.children()).collect();
+        }
+        matches
+    }
+}
+
+impl Step {
+    fn apply<'a, 'xml>(&self, context: &[&'a [Node<'xml>]]) -> Vec<&'a Node<'xml>> {
+        let mut matches = Vec::new();
+        if self.descendant {
+            // A context node inside another one would have its matches found twice.
+            let mut seen = BTreeSet::new();
+            for nodes in context {
+                self.walk(nodes, &mut matches, &mut seen);
+            }
+        } else {
+            for nodes in context {
+                matches.extend(self.select(nodes));
+            }
+        }
+        matches
+    }
+
+    /// Collects the matches among `nodes` and all their descendants, in document order.
+    fn walk<'a, 'xml>(
+        &self,
+        nodes: &'a [Node<'xml>],
+        matches: &mut Vec<&'a Node<'xml>>,
+        seen: &mut BTreeSet<*const Node<'xml>>,
+    ) {
+        // Every sibling list being walked, innermost last, as documents can be nested
+        // deeper than the call stack allows.
+        let mut stack = vec![(nodes.iter(), self.select(nodes).into_iter().peekable())];
+        while let Some((siblings, selected)) = stack.last_mut() {
+            let Some(node) = siblings.next() else {
+                stack.pop();
+                continue;
+            };
+            if selected.next_if(|x| ptr::eq(*x, node)).is_some() && seen.insert(node) {
+                matches.push(node);
+            }
+            let children = node.children();
+            stack.push((
+                children.iter(),
+                self.select(children).into_iter().peekable(),
+            ));
+        }
+    }
+
+    /// Gets the matches among sibling `nodes`, where indices count.
+    fn select<'a, 'xml>(&self, nodes: &'a [Node<'xml>]) -> Vec<&'a Node<'xml>> {
+        let mut selected: Vec<_> = nodes
+            .iter()
+            .filter(|node| self.test.matches(node))
+            .collect();
+        for predicate in &self.predicates {
+            match predicate {
+                Predicate::Attribute(key, value) => selected.retain(|node| {
+                    node.attribute(key).is_some_and(|attr| {
+                        value
+                            .as_ref()
+                            .is_none_or(|value| eq_decoded(attr.as_bytes(), value.as_bytes()))
+                    })
+                }),
+                Predicate::Index(index) => {
+                    selected = selected.get(index - 1).copied().into_iter().collect()
+                }
+            }
+        }
+        selected
+    }
+}
+
+impl Test {
+    fn matches(&self, node: &Node<'_>) -> bool {
+        match self {
+            Test::Name(name) => node.name() == Some(name.as_str()),
+            Test::Any => node.name().is_some(),
+            Test::Text => matches!(node.kind(), NodeKind::Text(_) | NodeKind::CData(_)),
+        }
+    }
+}
+
+struct Parser<'e> {
+    expr: &'e str,
+    pos: usize,
+}
+
+impl<'e> Parser<'e> {
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.expr[self.pos..].starts_with(token);
+        if found {
+            self.pos += token.len();
+        }
+        found
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'e str {
+        let rest = &self.expr[self.pos..];
+        let len = rest.find(|ch| !f(ch)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn error<T>(&self) -> Result<T, PathError> {
+        Err(PathError { offset: self.pos })
+    }
+
+    fn name(&mut self) -> Result<&'e str, PathError> {
+        let name = self.take_while(|ch| {
+            !matches!(ch, '/' | '[' | ']' | '@' | '=' | '\'' | '"') && !ch.is_whitespace()
+        });
+        match name {
+            "" => self.error(),
+            name => Ok(name),
+        }
+    }
+
+    fn step(&mut self, descendant: bool) -> Result<Step, PathError> {
+        let test = match self.name()? {
+            "*" => Test::Any,
+            "text()" => Test::Text,
+            name => Test::Name(name.to_string()),
+        };
+        let mut predicates = Vec::new();
+        while self.eat("[") {
+            let predicate = if self.eat("@") {
+                let key = self.name()?.to_string();
+                let value = match self.eat("=") {
+                    true => Some(self.quoted()?.to_string()),
+                    false => None,
+                };
+                Predicate::Attribute(key, value)
+            } else {
+                let start = self.pos;
+                match self.take_while(|ch| ch.is_ascii_digit()).parse() {
+                    Ok(index) if index > 0 => Predicate::Index(index),
+                    _ => return Err(PathError { offset: start }),
+                }
+            };
+            if !self.eat("]") {
+                return self.error();
+            }
+            predicates.push(predicate);
+        }
+        Ok(Step {
+            descendant,
+            test,
+            predicates,
+        })
+    }
+
+    fn quoted(&mut self) -> Result<&'e str, PathError> {
+        for quote in ["'", "\""] {
+            if self.eat(quote) {
+                let value = self.take_while(|ch| !quote.starts_with(ch));
+                return match self.eat(quote) {
+                    true => Ok(value),
+                    false => self.error(),
+                };
+            }
+        }
+        self.error()
+    }
+}
+
+impl PathError {
+    /// Gets the byte offset into the expression where it stopped making sense.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "malformed path expression at byte {}", self.offset)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PathError {}